@@ -1,20 +1,51 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde_json::json;
-use tauri::AppHandle;
-#[cfg(target_os = "macos")]
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
 use tauri_plugin_store::StoreExt;
 use uuid::Uuid;
 
+use crate::config::{self, ConfigDiff};
+use crate::dashboard::DashboardSummary;
 use crate::error::{Result, ShrikeError};
+use crate::logging::{self, LogLevel};
+use crate::manifest::{self, ManifestVerification};
+use crate::migration::{self, MigrationReport};
+use crate::scheduler;
 use crate::sync;
-use crate::types::{AgentTree, AppSettings, BackupEntry, DetectedConfig, ItemType, SyncResult};
+use crate::types::{
+    AddedEntries, AgentTree, AppSettings, BackupEntry, DetectedConfig, DriveStatus, EntryGroup,
+    FeatureSupport, Freshness, ItemType, Onboarding, PagedEntries, Relocation, SyncHistoryEntry,
+    SyncResult, TokenStrength, TreeChild, VersionInfo, WebhookStatus, push_history, stamp_synced,
+};
 
 const STORE_FILE: &str = "shrike_data.json";
 const ITEMS_KEY: &str = "items";
 const SETTINGS_KEY: &str = "settings";
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+const HISTORY_KEY: &str = "history";
+/// How long measuring a single entry's size may take before giving up.
+const ENTRY_SIZE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// Event emitted when a sync from the app, the scheduler or the webhook
+/// starts.
+pub const SYNC_STARTED_EVENT: &str = "sync://started";
+/// Event emitted with `true` on success when such a sync finishes.
+pub const SYNC_FINISHED_EVENT: &str = "sync://finished";
+/// Event emitted with a `SyncProgress` payload while `trigger_sync` runs.
+const SYNC_PROGRESS_EVENT: &str = "sync://progress";
+/// Event emitted with a warning when the store was unreadable at startup.
+pub const STORE_CORRUPT_EVENT: &str = "store://corrupt";
+/// Event emitted with the new `AppSettings` when a command other than
+/// `update_settings` changes them, so the settings UI can refresh.
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+/// Name a store that failed to load is preserved under, next to the store.
+pub const CORRUPT_STORE_FILE: &str = "shrike_data.corrupt.json";
+
+/// Warning recorded by `load_startup_settings`, reported by `store_warning`.
+static STORE_WARNING: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
 
 /// Validate that a path exists and is readable, returning its item type.
 fn validate_path(path: &str) -> Result<ItemType> {
@@ -51,9 +82,7 @@ fn load_items(app: &AppHandle) -> Result<Vec<BackupEntry>> {
 
     match store.get(ITEMS_KEY) {
         Some(val) => {
-            let items: Vec<BackupEntry> =
-                serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?;
-            Ok(items)
+            serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))
         }
         None => Ok(Vec::new()),
     }
@@ -70,27 +99,226 @@ fn save_items(app: &AppHandle, items: &[BackupEntry]) -> Result<()> {
     Ok(())
 }
 
+/// Load the sync history (newest first), returning an empty vec if not found.
+fn load_history(app: &AppHandle) -> Result<Vec<SyncHistoryEntry>> {
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+
+    match store.get(HISTORY_KEY) {
+        Some(val) => {
+            serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Append a run to the stored sync history, keeping the newest
+/// `SYNC_HISTORY_CAP` runs.
+fn record_history(app: &AppHandle, entry: SyncHistoryEntry) -> Result<()> {
+    let mut history = load_history(app)?;
+    push_history(&mut history, entry);
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+    store.set(HISTORY_KEY.to_string(), json!(history));
+    Ok(())
+}
+
+/// Read the store's schema version (0 if never migrated).
+fn stored_schema_version(app: &AppHandle) -> Result<u32> {
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+    Ok(store
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32)
+}
+
+/// Normalize stored entries to the current schema and record the version.
+///
+/// Canonicalizes paths, merges duplicates, and backfills defaulted fields.
+/// Safe to run repeatedly; an up-to-date store is left unchanged.
+#[tauri::command]
+pub fn migrate_store(app: AppHandle) -> Result<MigrationReport> {
+    let from_version = stored_schema_version(&app)?;
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+
+    let raw: Vec<serde_json::Value> = match store.get(ITEMS_KEY) {
+        Some(val) => {
+            serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?
+        }
+        None => Vec::new(),
+    };
+    let (items, report) = migration::migrate_items(&raw, from_version)?;
+    for path in &report.merged_duplicates {
+        logging::log(
+            LogLevel::Warn,
+            format_args!("merged duplicate entries for {path}"),
+        );
+    }
+
+    if report.changed() {
+        save_items(&app, &items)?;
+    }
+    store.set(
+        SCHEMA_VERSION_KEY.to_string(),
+        json!(migration::SCHEMA_VERSION),
+    );
+
+    Ok(report)
+}
+
+/// Run `migrate_store` if the store predates the current schema version.
+pub fn migrate_store_if_needed(app: &AppHandle) -> Result<Option<MigrationReport>> {
+    if stored_schema_version(app)? >= migration::SCHEMA_VERSION {
+        return Ok(None);
+    }
+    migrate_store(app.clone()).map(Some)
+}
+
 /// Add a file or directory to the backup list.
+///
+/// The entry is saved first and measured afterwards, off the main thread.
 #[tauri::command]
-pub fn add_entry(app: AppHandle, path: String) -> Result<BackupEntry> {
-    let item_type = validate_path(&path)?;
+pub async fn add_entry(app: AppHandle, path: String) -> Result<BackupEntry> {
+    let settings = get_settings(app.clone())?;
+    let mut items = load_items(&app)?;
+    settings.check_entry_count(items.len() + 1)?;
+    let entry = new_entry(&path, &items)?;
+    items.push(entry.clone());
+    save_items(&app, &items)?;
+    warn_near_entry_limit(&settings, items.len());
 
-    // Canonicalize the path to resolve symlinks and relative segments
-    let canonical = fs::canonicalize(&path)?;
-    let canonical_str = canonical.to_string_lossy().to_string();
+    let items = measure_entries(&app, vec![entry.clone()]).await?;
+    Ok(items
+        .into_iter()
+        .find(|e| e.id == entry.id)
+        .unwrap_or(entry))
+}
 
+/// Add several files or directories (e.g. detected agent configs) at once.
+///
+/// Paths that are missing, unreadable or already tracked are skipped
+/// rather than failing the batch; the store is written once. A batch that
+/// would take the list past `max_entries` adds nothing. As with
+/// `add_entry`, sizes are measured after saving.
+#[tauri::command]
+pub async fn add_detected_configs(app: AppHandle, paths: Vec<String>) -> Result<AddedEntries> {
+    let settings = get_settings(app.clone())?;
     let mut items = load_items(&app)?;
+    let mut added = add_paths(&mut items, &paths, &settings)?;
+    if added.added.is_empty() {
+        return Ok(added);
+    }
+    save_items(&app, &items)?;
+    warn_near_entry_limit(&settings, items.len());
+
+    let items = measure_entries(&app, added.added.clone()).await?;
+    for entry in &mut added.added {
+        if let Some(stored) = items.iter().find(|e| e.id == entry.id) {
+            entry.size_bytes = stored.size_bytes;
+        }
+    }
+    Ok(added)
+}
+
+/// Log a warning once the backup list nears `max_entries`.
+fn warn_near_entry_limit(settings: &AppSettings, count: usize) {
+    if settings.entries_near_limit(count) {
+        logging::log(
+            LogLevel::Warn,
+            format_args!(
+                "backup list has {count} of at most {} entries",
+                settings.max_entries
+            ),
+        );
+    }
+}
+
+/// Validate and canonicalize `path` into a new entry, rejecting it if
+/// `items` already tracks it.
+fn new_entry(path: &str, items: &[BackupEntry]) -> Result<BackupEntry> {
+    let item_type = validate_path(path)?;
+
+    // Canonicalize the path to resolve symlinks and relative segments
+    let canonical = fs::canonicalize(path)?;
+    let canonical_str = canonical.to_string_lossy().to_string();
 
     // Check for duplicates
     if items.iter().any(|e| e.path == canonical_str) {
         return Err(ShrikeError::DuplicateEntry(canonical_str));
     }
 
-    let entry = BackupEntry::new(canonical_str, item_type);
-    items.push(entry.clone());
-    save_items(&app, &items)?;
+    Ok(BackupEntry::new(canonical_str, item_type))
+}
 
-    Ok(entry)
+/// Append an entry to `items` for each of `paths` that `new_entry`
+/// accepts, in order; the others are reported as skipped.
+///
+/// The batch is all-or-nothing: if the accepted entries don't fit under
+/// `max_entries`, this fails and `items` is left as it was.
+fn add_paths(
+    items: &mut Vec<BackupEntry>,
+    paths: &[String],
+    settings: &AppSettings,
+) -> Result<AddedEntries> {
+    let original_len = items.len();
+    let mut result = AddedEntries::default();
+    for path in paths {
+        match new_entry(path, items) {
+            Ok(entry) => {
+                if let Err(e) = settings.check_entry_count(items.len() + 1) {
+                    items.truncate(original_len);
+                    return Err(e);
+                }
+                items.push(entry.clone());
+                result.added.push(entry);
+            }
+            Err(_) => result.skipped.push(path.clone()),
+        }
+    }
+    Ok(result)
+}
+
+/// Measure an entry's size, or `None` if the walk fails or times out.
+fn entry_size(path: &Path) -> Option<u64> {
+    sync::validation::dir_size_within(path, ENTRY_SIZE_TIMEOUT).ok()
+}
+
+/// Re-measure the size of every entry and return the updated list.
+///
+/// Entries that can't be measured get `size_bytes: None`.
+#[tauri::command]
+pub async fn refresh_entry_sizes(app: AppHandle) -> Result<Vec<BackupEntry>> {
+    let entries = load_items(&app)?;
+    measure_entries(&app, entries).await
+}
+
+/// Measure `entries` on a blocking thread, store their sizes, and return
+/// the updated list.
+async fn measure_entries(app: &AppHandle, entries: Vec<BackupEntry>) -> Result<Vec<BackupEntry>> {
+    let sizes: Vec<(Uuid, Option<u64>)> = tauri::async_runtime::spawn_blocking(move || {
+        entries
+            .iter()
+            .map(|e| (e.id, entry_size(Path::new(&e.path))))
+            .collect()
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?;
+
+    // Reload, so entries edited while measuring aren't reverted
+    let mut items = load_items(app)?;
+    for (id, size) in sizes {
+        if let Some(entry) = items.iter_mut().find(|e| e.id == id) {
+            entry.size_bytes = size;
+        }
+    }
+    save_items(app, &items)?;
+    Ok(items)
 }
 
 /// Remove an entry by its UUID.
@@ -110,12 +338,205 @@ pub fn remove_entry(app: AppHandle, id: String) -> Result<()> {
     Ok(())
 }
 
+/// List entries grouped by their top-level directory (e.g. `~/.config`).
+#[tauri::command]
+pub fn grouped_entries(app: AppHandle) -> Result<Vec<EntryGroup>> {
+    let items = load_items(&app)?;
+    Ok(crate::types::group_entries(
+        &items,
+        dirs::home_dir().as_deref(),
+    ))
+}
+
+/// Include or exclude an entry from scheduled syncs.
+///
+/// Manual syncs always include every entry regardless of this flag.
+#[tauri::command]
+pub fn set_entry_scheduled(app: AppHandle, id: String, scheduled: bool) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+
+    let mut items = load_items(&app)?;
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.scheduled = scheduled;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
+/// Enable or disable an entry. Disabled entries stay in the list but are
+/// skipped by every sync.
+#[tauri::command]
+pub fn set_entry_enabled(app: AppHandle, id: String, enabled: bool) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+
+    let mut items = load_items(&app)?;
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.enabled = enabled;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
+/// Trim rsync patterns, dropping blank ones and duplicates (keeping the
+/// first).
+fn clean_patterns(patterns: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        if !cleaned.iter().any(|p| p == pattern) {
+            cleaned.push(pattern.to_string());
+        }
+    }
+    cleaned
+}
+
+/// Replace an entry's exclude patterns.
+///
+/// Patterns use rsync `--exclude` syntax and only apply to this entry.
+/// Blank patterns are dropped and duplicates removed, keeping the first.
+#[tauri::command]
+pub fn set_entry_excludes(
+    app: AppHandle,
+    id: String,
+    excludes: Vec<String>,
+) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let patterns = clean_patterns(&excludes);
+
+    let mut items = load_items(&app)?;
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.excludes = patterns;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
+/// Replace an entry's allowlist: with any patterns set, only matching files
+/// are synced from it (e.g. `*.md` for a notes folder); an empty list syncs
+/// everything again.
+///
+/// Patterns use rsync `--include` syntax and only apply to this entry. The
+/// entry's excludes still win over them. Blank patterns are dropped and
+/// duplicates removed, keeping the first.
+#[tauri::command]
+pub fn set_entry_includes(
+    app: AppHandle,
+    id: String,
+    includes: Vec<String>,
+) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let patterns = clean_patterns(&includes);
+
+    let mut items = load_items(&app)?;
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.includes = patterns;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
+/// Point an existing entry at a new path, e.g. after its source was moved.
+///
+/// The new path is validated and canonicalized like in `add_entry`, and
+/// must not already be tracked by a different entry.
+#[tauri::command]
+pub fn update_entry_path(app: AppHandle, id: String, path: String) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let item_type = validate_path(&path)?;
+    let canonical_str = fs::canonicalize(&path)?.to_string_lossy().to_string();
+
+    let mut items = load_items(&app)?;
+    if items
+        .iter()
+        .any(|e| e.id != uuid && e.path == canonical_str)
+    {
+        return Err(ShrikeError::DuplicateEntry(canonical_str));
+    }
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.path = canonical_str;
+    entry.item_type = item_type;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
+/// Suggest new locations under the home directory for entries whose
+/// source path no longer exists. Confirm a suggestion with
+/// `update_entry_path`.
+#[tauri::command]
+pub async fn suggest_relocations(app: AppHandle) -> Result<Vec<Relocation>> {
+    let home = dirs::home_dir().ok_or_else(|| ShrikeError::PathNotFound("~".to_string()))?;
+    let items = load_items(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::relocate::suggest_relocations(
+            &items,
+            &home,
+            crate::relocate::SearchBounds::default(),
+        )
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))
+}
+
+/// Override compression for one entry; `None` inherits the global setting.
+#[tauri::command]
+pub fn set_entry_compress(
+    app: AppHandle,
+    id: String,
+    compress: Option<bool>,
+) -> Result<BackupEntry> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+
+    let mut items = load_items(&app)?;
+    let entry = items
+        .iter_mut()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    entry.compress = compress;
+    let updated = entry.clone();
+
+    save_items(&app, &items)?;
+    Ok(updated)
+}
+
 /// List all backup entries.
 #[tauri::command]
 pub fn list_entries(app: AppHandle) -> Result<Vec<BackupEntry>> {
     load_items(&app)
 }
 
+/// List one page of backup entries together with the total count.
+#[tauri::command]
+pub fn list_entries_paged(app: AppHandle, offset: usize, limit: usize) -> Result<PagedEntries> {
+    Ok(PagedEntries::page(&load_items(&app)?, offset, limit))
+}
+
+/// Find entries nested inside other entries, as `(outer, nested)` id pairs,
+/// so the UI can offer to remove the redundant ones.
+#[tauri::command]
+pub fn check_entries(app: AppHandle) -> Result<Vec<(Uuid, Uuid)>> {
+    Ok(crate::types::find_overlapping_entries(&load_items(&app)?))
+}
+
 /// Get current application settings.
 #[tauri::command]
 pub fn get_settings(app: AppHandle) -> Result<AppSettings> {
@@ -124,11 +545,7 @@ pub fn get_settings(app: AppHandle) -> Result<AppSettings> {
         .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
 
     match store.get(SETTINGS_KEY) {
-        Some(val) => {
-            let settings: AppSettings =
-                serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?;
-            Ok(settings)
-        }
+        Some(val) => parse_settings(val),
         None => {
             let defaults = AppSettings::default();
             store.set(SETTINGS_KEY.to_string(), json!(defaults));
@@ -137,32 +554,589 @@ pub fn get_settings(app: AppHandle) -> Result<AppSettings> {
     }
 }
 
+/// Deserialize stored settings, reporting a malformed value as a
+/// `StoreError` instead of falling back to the defaults.
+pub fn parse_settings(value: serde_json::Value) -> Result<AppSettings> {
+    serde_json::from_value(value)
+        .map_err(|e| ShrikeError::StoreError(format!("stored settings are invalid: {e}")))
+}
+
+/// Move the store at `store_path` aside to `CORRUPT_STORE_FILE`, so a
+/// store that failed to load is kept for the user to repair.
+pub fn preserve_corrupt_store(store_path: &Path) -> std::io::Result<PathBuf> {
+    let preserved = store_path.with_file_name(CORRUPT_STORE_FILE);
+    fs::rename(store_path, &preserved)?;
+    Ok(preserved)
+}
+
+/// Load the settings the app starts with.
+///
+/// A store or settings value that fails to load is preserved with
+/// `preserve_corrupt_store`, recorded for `store_warning` and emitted as
+/// `STORE_CORRUPT_EVENT`. Once the file is moved aside, the bad settings are
+/// also dropped from the live store, so the next save can't write them back;
+/// the app then starts on the defaults.
+pub fn load_startup_settings(app: &AppHandle) -> AppSettings {
+    let loaded = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))
+        .and_then(|store| match store.get(SETTINGS_KEY) {
+            Some(val) => parse_settings(val),
+            None => Ok(AppSettings::default()),
+        });
+    let error = match loaded {
+        Ok(settings) => return settings,
+        Err(e) => e,
+    };
+
+    let preserved = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| preserve_corrupt_store(&dir.join(STORE_FILE)).map_err(|e| e.to_string()));
+    if preserved.is_ok()
+        && let Ok(store) = app.store(STORE_FILE)
+    {
+        store.delete(SETTINGS_KEY);
+    }
+    let warning = match preserved {
+        Ok(path) => format!("{error}; the store was saved to {}", path.display()),
+        Err(e) => format!("{error}; the store could not be preserved: {e}"),
+    };
+    logging::log(LogLevel::Error, &warning);
+    *STORE_WARNING.lock().unwrap_or_else(|e| e.into_inner()) = Some(warning.clone());
+    let _ = app.emit(STORE_CORRUPT_EVENT, warning);
+    AppSettings::default()
+}
+
+/// Why the store failed to load at startup, or `None` if it loaded.
+///
+/// Covers the window before the UI listens for `STORE_CORRUPT_EVENT`.
+#[tauri::command]
+pub fn store_warning() -> Result<Option<String>> {
+    Ok(STORE_WARNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone())
+}
+
 /// Update application settings.
+///
+/// A configured `rsync_binary` must resolve to an executable file. The
+/// webhook server is restarted when its address changes.
 #[tauri::command]
 pub fn update_settings(app: AppHandle, settings: AppSettings) -> Result<()> {
+    settings.validate()?;
+    if let Some(binary) = settings.rsync_binary.as_deref().filter(|b| !b.is_empty()) {
+        sync::capabilities::check_executable(binary)?;
+    }
+    let addrs = settings.webhook_addrs()?;
+    let previous = get_settings(app.clone()).ok();
+    let previous_addrs = previous
+        .as_ref()
+        .and_then(|previous| previous.webhook_addrs().ok());
+
     let store = app
         .store(STORE_FILE)
         .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
 
     store.set(SETTINGS_KEY.to_string(), json!(settings));
+    if previous.as_ref().map(|p| &p.log_level) != Some(&settings.log_level)
+        && let Ok(level) = settings.log_level.parse()
+    {
+        logging::set_level(level);
+    }
+    logging::log(
+        LogLevel::Debug,
+        format_args!("settings updated: {settings:?}"),
+    );
+    // Restart the timer only if the interval changed, so saving unrelated
+    // settings doesn't push back the next scheduled sync
+    let previous_interval = previous.as_ref().map(|p| p.auto_sync_interval_minutes);
+    if previous_interval != Some(settings.auto_sync_interval_minutes) {
+        scheduler::reschedule(settings.auto_sync_interval_minutes);
+    }
+    // Rebind the webhook server if its address changed
+    if previous_addrs.as_ref() != Some(&addrs) {
+        crate::webhook::start_webhook_server(app, addrs);
+    }
+
+    Ok(())
+}
+
+/// Detect the Google Drive root again, for users who signed into Drive after
+/// first launch (detection otherwise only runs for the default settings).
+///
+/// The detected path becomes `gdrive_path` if none is set, or with `force`;
+/// a path the user chose is otherwise kept. Emits `SETTINGS_CHANGED_EVENT`
+/// when the setting changes. Returns the detected path, or `None` if Drive
+/// wasn't found.
+#[tauri::command]
+pub fn redetect_gdrive_path(app: AppHandle, force: Option<bool>) -> Result<Option<String>> {
+    let Some(detected) = crate::types::default_cloud_storage_dir()
+        .and_then(|dir| crate::types::detect_gdrive_path(&dir))
+    else {
+        return Ok(None);
+    };
+
+    let mut settings = get_settings(app.clone())?;
+    if settings.adopt_detected_gdrive_path(&detected, force.unwrap_or(false)) {
+        logging::log(
+            LogLevel::Info,
+            format_args!("gdrive_path set to detected {}", detected.display()),
+        );
+        update_settings(app.clone(), settings.clone())?;
+        let _ = app.emit(SETTINGS_CHANGED_EVENT, settings);
+    }
+    Ok(Some(detected.to_string_lossy().into_owned()))
+}
+
+/// Every Google Drive folder that can be used as `gdrive_path`, across all
+/// signed-in accounts: "My Drive" (or its localized name) and each Shared
+/// Drive. Lets the UI offer a choice other than the detected default.
+#[tauri::command]
+pub fn list_gdrive_roots() -> Result<Vec<String>> {
+    Ok(crate::types::default_cloud_storage_dir()
+        .map(|dir| crate::types::list_gdrive_roots(&dir))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
 
+/// Delete this machine's backup folder from the primary destination and
+/// return the bytes freed. `confirm` must be the machine name, typed by the
+/// user, so a stray call can't wipe a backup.
+#[tauri::command]
+pub async fn purge_machine_backup(app: AppHandle, confirm: String) -> Result<u64> {
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || sync::purge_machine_backup(&settings, &confirm))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Restart the webhook server on the stored settings' address.
+///
+/// `update_settings` already does this when the address changes; the
+/// command exists for retrying after a failed bind.
+#[tauri::command]
+pub fn restart_webhook_server(app: AppHandle) -> Result<()> {
+    let addrs = get_settings(app.clone())?.webhook_addrs()?;
+    crate::webhook::start_webhook_server(app, addrs);
     Ok(())
 }
 
-/// Trigger a sync of all backup entries via rsync.
+/// Restart the auto-sync timer from the stored `auto_sync_interval_minutes`.
 ///
-/// This command is async so that the blocking rsync subprocess does not
-/// freeze the Tauri IPC thread (and therefore the UI).
+/// `update_settings` already does this; the command exists for callers that
+/// edit the store directly.
+#[tauri::command]
+pub fn reschedule_auto_sync(app: AppHandle) -> Result<()> {
+    scheduler::reschedule(get_settings(app)?.auto_sync_interval_minutes);
+    Ok(())
+}
+
+/// Change the log level at runtime and persist it in settings.
+///
+/// Takes effect immediately, without restarting the app. At `debug`, each
+/// rsync invocation is logged in full; at `trace`, its output is too.
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, level: String) -> Result<()> {
+    let parsed: LogLevel = level.parse()?;
+
+    // update_settings applies the new level
+    let mut settings = get_settings(app.clone())?;
+    settings.log_level = parsed.to_string();
+    update_settings(app, settings)
+}
+
+/// Path of today's log file, for attaching to bug reports.
+#[tauri::command]
+pub fn get_log_path() -> Result<String> {
+    logging::log_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "log file is not enabled").into()
+        })
+}
+
+/// Check whether a webhook token is strong enough to use.
+///
+/// Used by the settings UI to warn when the user enters a custom token.
+#[tauri::command]
+pub fn validate_token_strength(token: String) -> Result<TokenStrength> {
+    Ok(crate::webhook::token_strength(&token))
+}
+
+/// Report whether the webhook server is listening, and why not if it
+/// failed to start.
+#[tauri::command]
+pub fn webhook_status() -> Result<WebhookStatus> {
+    Ok(crate::webhook::server_status())
+}
+
+/// Trigger a sync of all backup entries to every destination via rsync.
+///
+/// The returned result combines all destinations. This command is async so
+/// that the blocking rsync subprocess does not freeze the Tauri IPC thread
+/// (and therefore the UI). Progress is emitted as `sync://progress` events
+/// while rsync runs. On success, each entry's `last_synced` is updated.
 #[tauri::command]
 pub async fn trigger_sync(app: AppHandle) -> Result<SyncResult> {
     let entries = load_items(&app)?;
+    let settings = get_settings(app.clone())?;
+    tauri::async_runtime::spawn_blocking(move || sync_and_record(&app, &entries, &settings))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Sync the entries included in scheduled syncs, as the auto-sync timer does.
+///
+/// Blocks until rsync finishes. Returns `None` without syncing when no entry
+/// is scheduled.
+pub fn run_scheduled_sync(app: &AppHandle) -> Result<Option<SyncResult>> {
+    let entries = sync::scheduled_entries(&load_items(app)?);
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    let settings = get_settings(app.clone())?;
+    sync_and_record(app, &entries, &settings).map(Some)
+}
+
+/// Sync `entries` to every destination, emitting progress events, then
+/// record the run in the history and stamp `last_synced` on success.
+fn sync_and_record(
+    app: &AppHandle,
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+) -> Result<SyncResult> {
+    // Disabled entries are neither synced nor stamped
+    let entries = &sync::enabled_entries(entries);
+    let started_at = chrono::Utc::now();
+    sync_started(app);
+    let outcome = sync::merge_destination_results(sync::execute_sync_all_with_progress(
+        entries,
+        settings,
+        false,
+        &mut |progress| {
+            let _ = app.emit(SYNC_PROGRESS_EVENT, progress.clone());
+        },
+    ));
+    let run = SyncHistoryEntry::from_outcome(started_at, chrono::Utc::now(), &outcome);
+    if let Err(e) = record_history(app, run) {
+        logging::log(
+            LogLevel::Warn,
+            format_args!("failed to record sync history: {e}"),
+        );
+    }
+    sync_finished(app, settings, &outcome);
+    let result = outcome?;
+    let synced_ids = sync::synced_entry_ids(entries, &result);
+    mark_synced(app, &synced_ids, result.synced_at)?;
+    Ok(result)
+}
+
+/// Format a byte count with binary units, e.g. `1.5 MB`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Title and body of the notification shown when a sync finishes.
+pub fn sync_notification(outcome: &Result<SyncResult>) -> (String, String) {
+    match outcome {
+        Ok(result) => {
+            let files = match result.files_transferred {
+                1 => "1 file".to_string(),
+                n => format!("{n} files"),
+            };
+            let mut body = format!(
+                "{files} transferred ({})",
+                format_bytes(result.bytes_transferred)
+            );
+            if !result.skipped_paths.is_empty() {
+                body.push_str(&format!(", {} skipped", result.skipped_paths.len()));
+            }
+            ("Sync complete".to_string(), body)
+        }
+        // Keep multi-line rsync stderr readable in a single notification
+        Err(e) => (
+            "Sync failed".to_string(),
+            e.to_string()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    }
+}
+
+/// Announce a sync run with `SYNC_STARTED_EVENT`.
+pub fn sync_started(app: &AppHandle) {
+    let _ = app.emit(SYNC_STARTED_EVENT, ());
+}
+
+/// Announce a finished sync with `SYNC_FINISHED_EVENT` and, unless
+/// `notify_on_sync` is off, a system notification.
+pub fn sync_finished(app: &AppHandle, settings: &AppSettings, outcome: &Result<SyncResult>) {
+    let _ = app.emit(SYNC_FINISHED_EVENT, outcome.is_ok());
+    if !settings.notify_on_sync {
+        return;
+    }
+    let (title, body) = sync_notification(outcome);
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        logging::log(
+            LogLevel::Warn,
+            format_args!("failed to show sync notification: {e}"),
+        );
+    }
+}
+
+/// Classify an rsync exit code, e.g. from the sync history, so the UI can
+/// treat soft failures such as vanished files as warnings.
+#[tauri::command]
+pub fn classify_rsync_exit(code: i32) -> Result<sync::executor::RsyncExitClass> {
+    Ok(sync::executor::classify_exit_code(code))
+}
+
+/// List recorded sync runs, newest first.
+#[tauri::command]
+pub fn list_sync_history(app: AppHandle) -> Result<Vec<SyncHistoryEntry>> {
+    load_history(&app)
+}
+
+/// Export recorded sync runs, newest first, as CSV for spreadsheets.
+#[tauri::command]
+pub fn export_history_csv(app: AppHandle) -> Result<String> {
+    Ok(crate::types::history_to_csv(&load_history(&app)?))
+}
+
+/// Preview a sync of all backup entries with `rsync --dry-run`.
+///
+/// The result reports what would be transferred; nothing is written to the
+/// destination and `last_synced` is left unchanged.
+#[tauri::command]
+pub async fn trigger_sync_preview(app: AppHandle) -> Result<SyncResult> {
+    let entries = load_items(&app)?;
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        sync::merge_destination_results(sync::execute_sync_all(&entries, &settings, true))
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Record `synced_at` as the last sync time of the given entries.
+fn mark_synced(
+    app: &AppHandle,
+    ids: &[Uuid],
+    synced_at: chrono::DateTime<chrono::Utc>,
+) -> Result<()> {
+    let mut items = load_items(app)?;
+    stamp_synced(&mut items, ids, synced_at);
+    save_items(app, &items)
+}
+
+/// Report first-run state so the UI can decide whether to show onboarding.
+///
+/// Unlike `get_settings`, this never writes default settings to the store.
+#[tauri::command]
+pub fn onboarding_status(app: AppHandle) -> Result<Onboarding> {
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+    let settings: Option<AppSettings> = match store.get(SETTINGS_KEY) {
+        Some(val) => {
+            Some(serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?)
+        }
+        None => None,
+    };
+    let items = load_items(&app)?;
+    let provider = settings
+        .as_ref()
+        .map(|s| s.cloud_provider)
+        .unwrap_or_default();
+    let detected = crate::types::default_cloud_storage_dir()
+        .and_then(|dir| crate::types::detect_cloud_root(provider, &dir));
+
+    Ok(Onboarding::evaluate(
+        settings.as_ref(),
+        &items,
+        detected.as_deref(),
+    ))
+}
+
+/// Entry count, tracked size, last sync, destination free space and sync
+/// state in a single call for the dashboard.
+///
+/// The size walk runs off the main thread and is bounded; see
+/// `DashboardSummary::total_partial`.
+#[tauri::command]
+pub async fn dashboard_summary(app: AppHandle) -> Result<DashboardSummary> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::dashboard::build_summary(&items, &settings, &crate::dashboard::SIZE_CACHE)
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))
+}
+
+/// Report how long ago the backup last succeeded and whether that exceeds
+/// the configured staleness threshold.
+#[tauri::command]
+pub fn backup_freshness(app: AppHandle) -> Result<Freshness> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    Ok(Freshness::evaluate(
+        &items,
+        settings.stale_after_hours,
+        chrono::Utc::now(),
+    ))
+}
+
+/// List the paths the next sync covers, with its destination and any
+/// validation issues. rsync is not run and nothing is written.
+#[tauri::command]
+pub fn sync_plan(app: AppHandle) -> Result<sync::SyncPlan> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    sync::sync_plan(&items, &settings)
+}
+
+/// Preview the next sync using the given (unsaved) settings.
+///
+/// Runs the filelist and validation layers against the stored entries and
+/// returns the would-be destination, rsync argv, validation report, and
+/// size estimate. Nothing is written and the settings are not persisted.
+#[tauri::command]
+pub async fn simulate_with_settings(
+    app: AppHandle,
+    settings: AppSettings,
+) -> Result<sync::Preflight> {
+    let items = load_items(&app)?;
+    tauri::async_runtime::spawn_blocking(move || sync::preflight(&items, &settings))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// List files under a directory entry that the next sync will skip.
+///
+/// Applies the same exclude patterns the sync would pass to rsync for this
+/// entry. Returns at most `MAX_PREVIEW_PATHS` paths; file entries have
+/// nothing to exclude and return an empty list.
+#[tauri::command]
+pub fn preview_excluded(app: AppHandle, entry_id: String) -> Result<Vec<String>> {
+    let uuid = Uuid::parse_str(&entry_id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let entry = load_items(&app)?
+        .into_iter()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(entry_id.clone()))?;
+    if entry.item_type != ItemType::Directory {
+        return Ok(Vec::new());
+    }
+
+    let settings = get_settings(app)?;
+    let destination = settings.destination_path()?;
+    let patterns = sync::plan_passes(std::slice::from_ref(&entry), &settings, &destination)
+        .into_iter()
+        .flat_map(|pass| pass.options.excludes)
+        .collect::<Vec<_>>();
+
+    Ok(sync::filter::excluded_paths(
+        Path::new(&entry.path),
+        &patterns,
+        sync::filter::MAX_PREVIEW_PATHS,
+    ))
+}
+
+/// Compare two exported configs, returning entries added, removed, and
+/// changed from `a_json` to `b_json`.
+#[tauri::command]
+pub fn diff_configs(a_json: String, b_json: String) -> Result<ConfigDiff> {
+    let a = config::parse_config(&a_json)?;
+    let b = config::parse_config(&b_json)?;
+    Ok(config::diff_configs(&a, &b))
+}
+
+/// Report pairs of entries that would be written to the same destination path.
+///
+/// Returns `(first_id, second_id, shared_path)` for each collision under
+/// the configured `path_layout`. Under `full` only exact duplicate entries
+/// can collide; under `relative_to_home`, e.g. `~/etc` and `/etc` do too.
+#[tauri::command]
+pub fn detect_destination_collisions(app: AppHandle) -> Result<Vec<(Uuid, Uuid, String)>> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    let destination = settings.destination_path()?;
+    Ok(sync::layout::detect_destination_collisions(
+        &items,
+        &destination,
+        settings.path_layout,
+    ))
+}
+
+/// Write a signed SHA-256 manifest of the backup destination.
+///
+/// The manifest is signed with the webhook token so tampering can be
+/// detected later via `verify_manifest`. Returns the manifest file path.
+#[tauri::command]
+pub async fn write_signed_manifest(app: AppHandle) -> Result<String> {
     let settings = get_settings(app)?;
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        sync::execute_sync(&entries, &settings)
+    let destination = settings.destination_path()?;
+    let path = tauri::async_runtime::spawn_blocking(move || {
+        manifest::write_signed_manifest(Path::new(&destination), &settings.webhook_token)
     })
     .await
     .map_err(|e| ShrikeError::SyncFailed(e.to_string()))??;
-    Ok(result)
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Verify the signed manifest against the current backup contents.
+#[tauri::command]
+pub async fn verify_manifest(app: AppHandle) -> Result<ManifestVerification> {
+    let settings = get_settings(app)?;
+    let destination = settings.destination_path()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        manifest::verify_manifest(Path::new(&destination), &settings.webhook_token)
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// List optional rsync features and whether the installed rsync supports them.
+///
+/// Lets the settings UI disable options that won't work on the user's rsync.
+#[tauri::command]
+pub fn supported_features(app: AppHandle) -> Result<Vec<FeatureSupport>> {
+    let settings = get_settings(app)?;
+    let caps = sync::capabilities::rsync_capabilities(settings.rsync_program());
+    Ok(sync::capabilities::supported_features(caps.as_ref()))
+}
+
+/// Report Shrike, rsync, and OS versions for bug reports.
+#[tauri::command]
+pub fn version_info(app: AppHandle) -> Result<VersionInfo> {
+    let settings = get_settings(app)?;
+    Ok(sync::capabilities::version_info(settings.rsync_program()))
+}
+
+/// Heuristically check whether Google Drive is online for the destination.
+///
+/// Informational only: syncing is never blocked on the result.
+#[tauri::command]
+pub fn check_drive_online(app: AppHandle) -> Result<DriveStatus> {
+    let settings = get_settings(app)?;
+    Ok(crate::drive::check_drive_online(&settings.gdrive_path))
 }
 
 /// Check if autostart is enabled.
@@ -240,25 +1214,249 @@ pub fn set_dock_visible(app: AppHandle, visible: bool) -> Result<()> {
     Ok(())
 }
 
+/// The home and config directories to scan for coding agent configs.
+fn agent_dirs() -> Result<(PathBuf, PathBuf)> {
+    let home = dirs::home_dir().ok_or_else(|| ShrikeError::PathNotFound("~".to_string()))?;
+    let config_dir = crate::types::config_dir_for(&home, std::env::var_os("XDG_CONFIG_HOME"));
+    Ok((home, config_dir))
+}
+
 /// Scan the user's home directory for known coding agent configurations.
 #[tauri::command]
 pub fn scan_coding_configs() -> Result<Vec<DetectedConfig>> {
-    let home = dirs::home_dir().ok_or_else(|| ShrikeError::PathNotFound("~".to_string()))?;
-    Ok(crate::types::scan_coding_configs(&home))
+    let (home, config_dir) = agent_dirs()?;
+    Ok(crate::types::scan_coding_configs(&home, &config_dir))
 }
 
 /// Scan the user's home directory for coding agent configurations,
 /// returning a tree structure with first-level children and sibling files.
 #[tauri::command]
 pub fn scan_coding_configs_tree() -> Result<Vec<AgentTree>> {
+    let (home, config_dir) = agent_dirs()?;
+    Ok(crate::types::scan_coding_configs_tree(&home, &config_dir))
+}
+
+/// List one level of this machine's backup, at `sub_path` inside the
+/// destination (its root when omitted).
+#[tauri::command]
+pub fn list_backup_tree(app: AppHandle, sub_path: Option<String>) -> Result<Vec<TreeChild>> {
+    let destination = get_settings(app)?.destination_path()?;
+    crate::types::list_backup_tree(Path::new(&destination), sub_path.as_deref())
+}
+
+/// Back up every detected coding agent config (including sibling files)
+/// without adding them as entries.
+#[tauri::command]
+pub async fn sync_coding_configs(app: AppHandle) -> Result<SyncResult> {
+    let (home, config_dir) = agent_dirs()?;
+    let trees = crate::types::scan_coding_configs_tree(&home, &config_dir);
+    let paths = crate::types::coding_config_paths(&trees);
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || sync::execute_sync_with_paths(&paths, &settings))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Restore the coding agent configs found in this machine's backup to the
+/// home directory, overwriting the local copies.
+#[tauri::command]
+pub async fn restore_coding_configs(app: AppHandle) -> Result<SyncResult> {
     let home = dirs::home_dir().ok_or_else(|| ShrikeError::PathNotFound("~".to_string()))?;
-    Ok(crate::types::scan_coding_configs_tree(&home))
+    let settings = get_settings(app)?;
+    let backup_home = sync::layout::backup_path_for(
+        &home.to_string_lossy(),
+        &settings.destination_path()?,
+        settings.path_layout,
+    );
+    let paths = crate::types::restorable_coding_config_paths(&home, &backup_home);
+    tauri::async_runtime::spawn_blocking(move || sync::restore_paths(&paths, &settings))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Compare every entry with its backup, listing files that match, differ,
+/// or are missing from the backup.
+#[tauri::command]
+pub async fn verify_sync(app: AppHandle) -> Result<sync::executor::VerifyReport> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || sync::verify_sync(&items, &settings))
+        .await
+        .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Restore one entry from the backup to its original location.
+///
+/// Refuses to overwrite local files newer than their backup unless `force`
+/// is set; `dry_run` only reports what would be restored.
+#[tauri::command]
+pub async fn restore_entry(
+    app: AppHandle,
+    id: String,
+    dry_run: bool,
+    force: bool,
+) -> Result<SyncResult> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let entry = load_items(&app)?
+        .into_iter()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        sync::restore::restore_entry(&entry, &settings, dry_run, force)
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// Show an entry's backup copy in Finder.
+///
+/// Fails with `NotBackedUp` if the entry has no backup copy yet.
+#[tauri::command]
+pub fn reveal_backup(app: AppHandle, id: String) -> Result<()> {
+    use tauri_plugin_shell::ShellExt;
+    let uuid = Uuid::parse_str(&id).map_err(|e| ShrikeError::EntryNotFound(e.to_string()))?;
+    let entry = load_items(&app)?
+        .into_iter()
+        .find(|e| e.id == uuid)
+        .ok_or_else(|| ShrikeError::EntryNotFound(id.clone()))?;
+    let settings = get_settings(app.clone())?;
+    let backup = sync::layout::entry_backup_path(&entry, &settings)?;
+    if !backup.exists() {
+        return Err(ShrikeError::NotBackedUp(entry.path));
+    }
+
+    let (program, args) = sync::layout::reveal_command(&backup);
+    app.shell()
+        .command(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| ShrikeError::SyncFailed(format!("failed to run {program}: {e}")))?;
+    Ok(())
+}
+
+/// Restore every entry from this machine's encrypted archive.
+///
+/// `identity` is the age secret key matching the configured recipient; it
+/// is used for this call only and never stored. As with `restore_entry`,
+/// local files newer than their backup are only overwritten with `force`,
+/// and `dry_run` only reports what would be restored.
+#[tauri::command]
+pub async fn restore_encrypted_backup(
+    app: AppHandle,
+    identity: String,
+    dry_run: bool,
+    force: bool,
+) -> Result<SyncResult> {
+    let items = load_items(&app)?;
+    let settings = get_settings(app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        sync::restore_encrypted(&identity, &items, &settings, dry_run, force)
+    })
+    .await
+    .map_err(|e| ShrikeError::SyncFailed(e.to_string()))?
+}
+
+/// For one agent's config tree, report which first-level children are
+/// already backed up by an entry (the child itself or an ancestor).
+#[tauri::command]
+pub fn child_coverage(app: AppHandle, agent: String) -> Result<Vec<(String, bool)>> {
+    let (home, config_dir) = agent_dirs()?;
+    let tree = crate::types::scan_coding_configs_tree(&home, &config_dir)
+        .into_iter()
+        .find(|t| t.agent == agent)
+        .ok_or(ShrikeError::EntryNotFound(agent))?;
+    let items = load_items(&app)?;
+    Ok(crate::types::child_coverage(&tree, &items))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sync_result(files: u64, bytes: u64) -> SyncResult {
+        SyncResult {
+            files_transferred: files,
+            dirs_transferred: 0,
+            bytes_transferred: bytes,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn parse_settings_reports_malformed_value() {
+        let err = parse_settings(json!({"gdrive_path": 42})).unwrap_err();
+        assert!(
+            matches!(&err, ShrikeError::StoreError(m) if m.contains("stored settings are invalid")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parse_settings_accepts_stored_settings() {
+        let settings = AppSettings {
+            webhook_port: 9001,
+            ..AppSettings::default()
+        };
+        assert_eq!(parse_settings(json!(settings)).unwrap(), settings);
+    }
+
+    #[test]
+    fn preserve_corrupt_store_moves_the_file_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join(STORE_FILE);
+        fs::write(&store, "{\"settings\": {oops").unwrap();
+
+        let preserved = preserve_corrupt_store(&store).unwrap();
+
+        assert_eq!(preserved, dir.path().join(CORRUPT_STORE_FILE));
+        assert_eq!(
+            fs::read_to_string(preserved).unwrap(),
+            "{\"settings\": {oops"
+        );
+        assert!(!store.exists());
+    }
+
+    #[test]
+    fn sync_notification_reports_files_and_bytes() {
+        let (title, body) = sync_notification(&Ok(sync_result(12, 1536)));
+        assert_eq!(title, "Sync complete");
+        assert_eq!(body, "12 files transferred (1.5 KB)");
+
+        let (_, body) = sync_notification(&Ok(sync_result(1, 512)));
+        assert_eq!(body, "1 file transferred (512 B)");
+    }
+
+    #[test]
+    fn sync_notification_mentions_skipped_paths() {
+        let mut result = sync_result(8, 3 * 1024 * 1024);
+        result.skipped_paths = vec!["/a".into(), "/b".into()];
+        let (_, body) = sync_notification(&Ok(result));
+        assert_eq!(body, "8 files transferred (3.0 MB), 2 skipped");
+    }
+
+    #[test]
+    fn sync_notification_flattens_error() {
+        let outcome = Err(ShrikeError::RsyncError {
+            code: 23,
+            message: "rsync: link_stat failed\nsome files could not be transferred".into(),
+        });
+        let (title, body) = sync_notification(&outcome);
+        assert_eq!(title, "Sync failed");
+        assert!(!body.contains('\n'));
+        assert!(body.contains("link_stat failed some files"), "{body}");
+    }
+
     #[test]
     fn validate_path_existing_file() {
         // /etc/hosts exists on all macOS systems
@@ -294,6 +1492,89 @@ mod tests {
         assert_eq!(result.unwrap(), ItemType::Directory);
     }
 
+    #[test]
+    fn add_paths_skips_duplicates_and_missing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracked = dir.path().join("tracked.txt");
+        let fresh = dir.path().join("fresh");
+        fs::write(&tracked, "").unwrap();
+        fs::create_dir(&fresh).unwrap();
+        let tracked = fs::canonicalize(&tracked).unwrap();
+        let fresh = fs::canonicalize(&fresh).unwrap();
+
+        let mut items = vec![BackupEntry::new(
+            tracked.to_string_lossy().to_string(),
+            ItemType::File,
+        )];
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        let fresh_str = fresh.to_string_lossy().to_string();
+        let paths = vec![
+            fresh_str.clone(),
+            tracked.to_string_lossy().to_string(),
+            missing.clone(),
+            // Repeated within the batch
+            fresh_str.clone(),
+        ];
+
+        let result = add_paths(
+            &mut items,
+            &paths,
+            &AppSettings::for_test("/tmp/test_gdrive"),
+        )
+        .unwrap();
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].path, fresh_str);
+        assert_eq!(result.added[0].item_type, ItemType::Directory);
+        assert_eq!(result.skipped, vec![paths[1].clone(), missing, fresh_str]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1], result.added[0]);
+    }
+
+    #[test]
+    fn add_paths_rejects_entries_past_the_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                fs::write(&path, "").unwrap();
+                fs::canonicalize(path)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        let settings = AppSettings {
+            max_entries: 2,
+            ..AppSettings::for_test("/tmp/test_gdrive")
+        };
+
+        // A batch that overflows midway adds nothing
+        let mut items = vec![];
+        assert!(add_paths(&mut items, &paths, &settings).is_err());
+        assert!(items.is_empty());
+
+        let result = add_paths(&mut items, &paths[..2], &settings).unwrap();
+        assert_eq!(result.added.len(), 2);
+        assert!(settings.entries_near_limit(items.len()));
+
+        let err = add_paths(&mut items, &paths[2..], &settings).unwrap_err();
+        assert!(matches!(err, ShrikeError::TooManyEntries(2)));
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            err.to_string(),
+            "entry limit reached: the backup list holds at most 2 entries"
+        );
+
+        // Without a cap the same addition goes through
+        let unlimited = AppSettings {
+            max_entries: 0,
+            ..AppSettings::for_test("/tmp/test_gdrive")
+        };
+        assert!(add_paths(&mut items, &paths[2..], &unlimited).is_ok());
+        assert_eq!(items.len(), 3);
+    }
+
     #[test]
     fn validate_path_home_dir() {
         let home = std::env::var("HOME").unwrap();
@@ -0,0 +1,219 @@
+//! Shareable backup config exports.
+//!
+//! A config export is the portable part of a backup spec: which paths are
+//! backed up and how. Teams sharing a dotfiles spec can diff two exports to
+//! see which entries were added, removed, or changed.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ShrikeError};
+use crate::types::ItemType;
+
+/// A single entry in a config export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    pub path: String,
+    pub item_type: ItemType,
+    /// rsync exclude patterns applied under this entry
+    #[serde(default)]
+    pub excludes: Vec<String>,
+}
+
+/// A portable backup config, as exchanged between machines or teammates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigExport {
+    pub entries: Vec<ConfigEntry>,
+}
+
+/// An entry present in both configs whose spec differs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedEntry {
+    pub before: ConfigEntry,
+    pub after: ConfigEntry,
+}
+
+/// Delta between two config exports, each list sorted by canonical path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    /// Entries only in the second config
+    pub added: Vec<ConfigEntry>,
+    /// Entries only in the first config
+    pub removed: Vec<ConfigEntry>,
+    /// Entries in both configs with a different type or excludes
+    pub changed: Vec<ChangedEntry>,
+}
+
+impl ConfigDiff {
+    /// Returns true if the two configs describe the same backup spec.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Lexically normalize a path so equivalent spellings compare equal.
+///
+/// Removes `.` components, resolves `..` against the preceding component,
+/// and drops trailing slashes. The filesystem is not consulted, so exports
+/// from other machines can be compared.
+pub fn canonical_path(path: &str) -> String {
+    let mut out = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out.to_string_lossy().to_string()
+}
+
+fn by_canonical_path(config: &ConfigExport) -> BTreeMap<String, &ConfigEntry> {
+    config
+        .entries
+        .iter()
+        .map(|entry| (canonical_path(&entry.path), entry))
+        .collect()
+}
+
+/// Compute the entries added, removed, and changed from `a` to `b`.
+///
+/// Entries are matched by canonical path. Exclude order is ignored when
+/// deciding whether an entry changed.
+pub fn diff_configs(a: &ConfigExport, b: &ConfigExport) -> ConfigDiff {
+    let before = by_canonical_path(a);
+    let after = by_canonical_path(b);
+    let mut diff = ConfigDiff::default();
+
+    for (path, old) in &before {
+        match after.get(path) {
+            None => diff.removed.push((*old).clone()),
+            Some(new) if !same_spec(old, new) => diff.changed.push(ChangedEntry {
+                before: (*old).clone(),
+                after: (*new).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (path, new) in &after {
+        if !before.contains_key(path) {
+            diff.added.push((*new).clone());
+        }
+    }
+    diff
+}
+
+fn same_spec(a: &ConfigEntry, b: &ConfigEntry) -> bool {
+    let mut a_excludes = a.excludes.clone();
+    let mut b_excludes = b.excludes.clone();
+    a_excludes.sort();
+    b_excludes.sort();
+    a.item_type == b.item_type && a_excludes == b_excludes
+}
+
+/// Parse a config export from JSON.
+pub fn parse_config(json: &str) -> Result<ConfigExport> {
+    serde_json::from_str(json).map_err(|e| ShrikeError::InvalidConfig(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, item_type: ItemType, excludes: &[&str]) -> ConfigEntry {
+        ConfigEntry {
+            path: path.to_string(),
+            item_type,
+            excludes: excludes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn config(entries: Vec<ConfigEntry>) -> ConfigExport {
+        ConfigExport { entries }
+    }
+
+    #[test]
+    fn canonical_path_normalizes_spellings() {
+        assert_eq!(canonical_path("/Users/me/.claude/"), "/Users/me/.claude");
+        assert_eq!(canonical_path("/Users/me/./.claude"), "/Users/me/.claude");
+        assert_eq!(
+            canonical_path("/Users/me/x/../.claude"),
+            "/Users/me/.claude"
+        );
+    }
+
+    #[test]
+    fn diff_detects_added_entry() {
+        let a = config(vec![entry("/Users/me/.zshrc", ItemType::File, &[])]);
+        let b = config(vec![
+            entry("/Users/me/.zshrc", ItemType::File, &[]),
+            entry("/Users/me/.gitconfig", ItemType::File, &[]),
+        ]);
+        let diff = diff_configs(&a, &b);
+        assert_eq!(
+            diff.added,
+            vec![entry("/Users/me/.gitconfig", ItemType::File, &[])]
+        );
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn diff_detects_removed_entry() {
+        let a = config(vec![
+            entry("/Users/me/.zshrc", ItemType::File, &[]),
+            entry("/Users/me/.vimrc", ItemType::File, &[]),
+        ]);
+        let b = config(vec![entry("/Users/me/.zshrc", ItemType::File, &[])]);
+        let diff = diff_configs(&a, &b);
+        assert_eq!(
+            diff.removed,
+            vec![entry("/Users/me/.vimrc", ItemType::File, &[])]
+        );
+        assert!(diff.added.is_empty());
+    }
+
+    #[test]
+    fn diff_detects_changed_excludes() {
+        let a = config(vec![entry(
+            "/Users/me/.claude",
+            ItemType::Directory,
+            &["*.log"],
+        )]);
+        let b = config(vec![entry(
+            "/Users/me/.claude/",
+            ItemType::Directory,
+            &["*.log", "projects/*/node_modules"],
+        )]);
+        let diff = diff_configs(&a, &b);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.excludes, vec!["*.log"]);
+        assert_eq!(diff.changed[0].after.excludes.len(), 2);
+    }
+
+    #[test]
+    fn diff_ignores_exclude_order() {
+        let a = config(vec![entry("/data", ItemType::Directory, &["a", "b"])]);
+        let b = config(vec![entry("/data", ItemType::Directory, &["b", "a"])]);
+        assert!(diff_configs(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn parse_config_defaults_missing_excludes() {
+        let json = r#"{"entries":[{"path":"/etc/hosts","item_type":"file"}]}"#;
+        let parsed = parse_config(json).unwrap();
+        assert_eq!(parsed.entries[0].excludes, Vec::<String>::new());
+    }
+
+    #[test]
+    fn parse_config_rejects_invalid_json() {
+        let err = parse_config("not json").unwrap_err();
+        assert!(matches!(err, ShrikeError::InvalidConfig(_)));
+    }
+}
@@ -0,0 +1,241 @@
+//! One-call dashboard summary.
+//!
+//! Bundles what the main screen shows (entry count, tracked size, last sync,
+//! destination free space, running state) so the frontend needs a single
+//! round-trip. The tracked-size walk is the only expensive part: it is
+//! bounded by `SIZE_TIMEOUT` and cached for a short TTL.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::sync;
+use crate::types::{AppSettings, BackupEntry};
+
+/// How long a computed tracked size is reused.
+pub const SIZE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Maximum time spent measuring the tracked size.
+pub const SIZE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Everything the dashboard shows, computed in one call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DashboardSummary {
+    /// Number of backup entries
+    pub entry_count: usize,
+    /// Combined on-disk size of all entries in bytes (cached, see `SIZE_CACHE_TTL`)
+    pub total_bytes: u64,
+    /// The size walk ran out of time, so `total_bytes` is a lower bound
+    pub total_partial: bool,
+    /// Most recent successful sync of any entry
+    pub last_synced: Option<DateTime<Utc>>,
+    /// Newest line of the destination's sync log (result of the last attempt)
+    pub last_result: Option<String>,
+    /// Resolved destination directory, if the settings are complete
+    pub destination: Option<String>,
+    /// Free space available at the destination in bytes, if it can be read
+    pub free_bytes: Option<u64>,
+    /// Whether a sync is running right now
+    pub sync_running: bool,
+}
+
+/// A single-slot cache of the tracked size and whether it is partial,
+/// keyed by the entry paths.
+pub struct SizeCache {
+    ttl: Duration,
+    slot: Mutex<Option<(Vec<String>, Instant, (u64, bool))>>,
+}
+
+impl SizeCache {
+    pub const fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            slot: Mutex::new(None),
+        }
+    }
+
+    /// Return the cached size for `paths`, or compute and cache it if the
+    /// paths changed or the cached value is older than the TTL.
+    pub fn get_or_compute(
+        &self,
+        paths: &[String],
+        compute: impl FnOnce(&[String]) -> (u64, bool),
+    ) -> (u64, bool) {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_paths, at, bytes)) = slot.as_ref()
+            && cached_paths == paths
+            && at.elapsed() < self.ttl
+        {
+            return *bytes;
+        }
+        let bytes = compute(paths);
+        *slot = Some((paths.to_vec(), Instant::now(), bytes));
+        bytes
+    }
+}
+
+/// Process-wide cache used by the `dashboard_summary` command.
+pub static SIZE_CACHE: SizeCache = SizeCache::new(SIZE_CACHE_TTL);
+
+/// Total size of `paths` (each counted once), measured within `timeout`.
+///
+/// Returns the bytes measured and whether the walk ran out of time, in which
+/// case the total is a lower bound. Paths that can't be read count as zero.
+pub fn bounded_size(paths: &[String], timeout: Duration) -> (u64, bool) {
+    let deadline = Instant::now() + timeout;
+    let unique: HashSet<&String> = paths.iter().collect();
+    let mut total = 0;
+    for path in unique {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match sync::validation::dir_size_within(Path::new(path), remaining) {
+            Ok(bytes) => total += bytes,
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => return (total, true),
+            Err(_) => {}
+        }
+    }
+    (total, false)
+}
+
+/// Assemble the dashboard summary from stored entries and settings.
+pub fn build_summary(
+    items: &[BackupEntry],
+    settings: &AppSettings,
+    cache: &SizeCache,
+) -> DashboardSummary {
+    let paths: Vec<String> = items.iter().map(|e| e.path.clone()).collect();
+    let (total_bytes, total_partial) =
+        cache.get_or_compute(&paths, |paths| bounded_size(paths, SIZE_TIMEOUT));
+    let destination = settings.destination_path().ok();
+    let last_result = destination
+        .as_deref()
+        .and_then(|d| sync::changelog::last_summary(Path::new(d)));
+    let free_bytes = destination
+        .as_deref()
+        .and_then(|d| sync::validation::free_space(Path::new(d)));
+
+    DashboardSummary {
+        entry_count: items.len(),
+        total_bytes,
+        total_partial,
+        last_synced: items.iter().filter_map(|e| e.last_synced).max(),
+        last_result,
+        destination,
+        free_bytes,
+        sync_running: sync::is_sync_running(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ItemType;
+    use std::cell::Cell;
+    use std::fs;
+
+    #[test]
+    fn build_summary_populates_every_field() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("a.txt"), "12345").unwrap();
+        fs::write(source.path().join("b.txt"), "678").unwrap();
+        let gdrive = tempfile::tempdir().unwrap();
+
+        let settings = AppSettings {
+            gdrive_path: gdrive.path().to_string_lossy().to_string(),
+            backup_dir_name: "Backup".to_string(),
+            machine_name: "TestMac".to_string(),
+            ..AppSettings::default()
+        };
+        let destination = settings.destination_path().unwrap();
+        fs::create_dir_all(&destination).unwrap();
+        sync::changelog::append_summary(Path::new(&destination), "last line").unwrap();
+
+        let mut synced = BackupEntry::new(
+            source.path().join("a.txt").to_string_lossy().to_string(),
+            ItemType::File,
+        );
+        let synced_at = Utc::now();
+        synced.last_synced = Some(synced_at);
+        let items = vec![
+            synced,
+            BackupEntry::new(
+                source.path().join("b.txt").to_string_lossy().to_string(),
+                ItemType::File,
+            ),
+        ];
+
+        let cache = SizeCache::new(SIZE_CACHE_TTL);
+        let summary = build_summary(&items, &settings, &cache);
+        assert_eq!(summary.entry_count, 2);
+        assert_eq!(summary.total_bytes, 8);
+        assert!(!summary.total_partial);
+        assert_eq!(summary.last_synced, Some(synced_at));
+        assert_eq!(summary.last_result.as_deref(), Some("last line"));
+        assert_eq!(summary.destination.as_deref(), Some(destination.as_str()));
+        assert!(summary.free_bytes.is_some_and(|b| b > 0));
+    }
+
+    #[test]
+    fn build_summary_without_destination() {
+        let settings = AppSettings {
+            gdrive_path: String::new(),
+            ..AppSettings::default()
+        };
+        let summary = build_summary(&[], &settings, &SizeCache::new(SIZE_CACHE_TTL));
+        assert_eq!(summary.entry_count, 0);
+        assert_eq!(summary.destination, None);
+        assert_eq!(summary.free_bytes, None);
+        assert_eq!(summary.last_result, None);
+    }
+
+    #[test]
+    fn bounded_size_marks_an_interrupted_walk_partial() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("a.txt"), "12345").unwrap();
+        let paths = vec![
+            source.path().to_string_lossy().to_string(),
+            source.path().to_string_lossy().to_string(),
+            "/nonexistent/shrike".to_string(),
+        ];
+
+        assert_eq!(bounded_size(&paths, SIZE_TIMEOUT), (5, false));
+        let (_, partial) = bounded_size(&paths[..1], Duration::ZERO);
+        assert!(partial);
+    }
+
+    #[test]
+    fn size_cache_reuses_value_within_ttl() {
+        let cache = SizeCache::new(SIZE_CACHE_TTL);
+        let calls = Cell::new(0);
+        let compute = |_: &[String]| {
+            calls.set(calls.get() + 1);
+            (42, false)
+        };
+        let paths = vec!["/a".to_string()];
+
+        assert_eq!(cache.get_or_compute(&paths, compute), (42, false));
+        assert_eq!(cache.get_or_compute(&paths, compute), (42, false));
+        assert_eq!(calls.get(), 1);
+
+        // Different entries invalidate the cache
+        cache.get_or_compute(&["/b".to_string()], compute);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn size_cache_recomputes_after_ttl() {
+        let cache = SizeCache::new(Duration::ZERO);
+        let calls = Cell::new(0);
+        let compute = |_: &[String]| {
+            calls.set(calls.get() + 1);
+            (0, false)
+        };
+        cache.get_or_compute(&[], compute);
+        cache.get_or_compute(&[], compute);
+        assert_eq!(calls.get(), 2);
+    }
+}
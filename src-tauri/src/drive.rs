@@ -0,0 +1,155 @@
+//! Heuristic Google Drive online check.
+//!
+//! A destination inside `~/Library/CloudStorage` can exist while Drive is
+//! paused or quit, in which case synced files queue locally and never reach
+//! the cloud. This is informational only and never blocks a sync; the
+//! blocking check that the mount answers at all is
+//! `sync::validation::is_destination_mounted`.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::sync::validation::{MOUNT_PROBE_TIMEOUT, is_destination_mounted};
+use crate::types::DriveStatus;
+
+/// Name of the Google Drive for desktop process.
+const DRIVE_PROCESS: &str = "Google Drive";
+
+/// Observations used to decide the Drive status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriveProbe {
+    /// Destination lies inside a `CloudStorage/GoogleDrive-*` account
+    pub in_cloud_storage: bool,
+    /// The Drive root (`gdrive_path`) currently exists as a directory
+    pub mount_present: bool,
+    /// Whether the Drive process is running, if it could be determined
+    pub process_running: Option<bool>,
+}
+
+/// Decide the Drive status from probe results.
+///
+/// Destinations outside CloudStorage are `Unknown` (not a Drive mount we can
+/// reason about). A missing mount or a stopped Drive process is `Offline`.
+pub fn determine_drive_status(probe: DriveProbe) -> DriveStatus {
+    if !probe.in_cloud_storage {
+        return DriveStatus::Unknown;
+    }
+    if !probe.mount_present {
+        return DriveStatus::Offline;
+    }
+    match probe.process_running {
+        Some(true) => DriveStatus::Online,
+        Some(false) => DriveStatus::Offline,
+        None => DriveStatus::Unknown,
+    }
+}
+
+/// Returns true if `path` is inside a `CloudStorage/GoogleDrive-*` directory.
+pub fn is_in_cloud_storage(path: &Path) -> bool {
+    let components: Vec<_> = path.components().map(|c| c.as_os_str()).collect();
+    components.windows(2).any(|pair| {
+        pair[0] == "CloudStorage" && pair[1].to_string_lossy().starts_with("GoogleDrive-")
+    })
+}
+
+/// Check whether the Drive process is running via `pgrep`.
+///
+/// Returns `None` if `pgrep` cannot be run or reports an error.
+fn drive_process_running() -> Option<bool> {
+    let status = Command::new("pgrep")
+        .args(["-x", DRIVE_PROCESS])
+        .output()
+        .ok()?
+        .status;
+    match status.code() {
+        Some(0) => Some(true),
+        Some(1) => Some(false),
+        _ => None,
+    }
+}
+
+/// Probe the system and report the Drive status for `gdrive_path`.
+pub fn check_drive_online(gdrive_path: &str) -> DriveStatus {
+    let path = Path::new(gdrive_path);
+    let in_cloud_storage = !gdrive_path.is_empty() && is_in_cloud_storage(path);
+    let probe = DriveProbe {
+        in_cloud_storage,
+        mount_present: is_destination_mounted(path, MOUNT_PROBE_TIMEOUT),
+        process_running: if in_cloud_storage {
+            drive_process_running()
+        } else {
+            None
+        },
+    };
+    determine_drive_status(probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(in_cloud_storage: bool, mount_present: bool, running: Option<bool>) -> DriveProbe {
+        DriveProbe {
+            in_cloud_storage,
+            mount_present,
+            process_running: running,
+        }
+    }
+
+    #[test]
+    fn online_when_mounted_and_running() {
+        assert_eq!(
+            determine_drive_status(probe(true, true, Some(true))),
+            DriveStatus::Online
+        );
+    }
+
+    #[test]
+    fn offline_when_process_stopped() {
+        assert_eq!(
+            determine_drive_status(probe(true, true, Some(false))),
+            DriveStatus::Offline
+        );
+    }
+
+    #[test]
+    fn offline_when_mount_missing() {
+        assert_eq!(
+            determine_drive_status(probe(true, false, Some(true))),
+            DriveStatus::Offline
+        );
+    }
+
+    #[test]
+    fn unknown_outside_cloud_storage_or_without_process_info() {
+        assert_eq!(
+            determine_drive_status(probe(false, true, Some(true))),
+            DriveStatus::Unknown
+        );
+        assert_eq!(
+            determine_drive_status(probe(true, true, None)),
+            DriveStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn is_in_cloud_storage_matches_drive_accounts() {
+        assert!(is_in_cloud_storage(Path::new(
+            "/Users/me/Library/CloudStorage/GoogleDrive-me@example.com/My Drive"
+        )));
+        assert!(!is_in_cloud_storage(Path::new(
+            "/Users/me/Library/CloudStorage/Dropbox/backup"
+        )));
+        assert!(!is_in_cloud_storage(Path::new("/mnt/gdrive")));
+    }
+
+    #[test]
+    fn check_drive_online_non_drive_path_is_unknown() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            check_drive_online(dir.path().to_str().unwrap()),
+            DriveStatus::Unknown
+        );
+        assert_eq!(check_drive_online(""), DriveStatus::Unknown);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::sync::executor::{RsyncExitClass, classify_exit_code};
+
 #[derive(Debug, Error)]
 pub enum ShrikeError {
     #[error("path does not exist: {0}")]
@@ -9,25 +11,66 @@ pub enum ShrikeError {
     #[error("path is not readable: {0}")]
     PathNotReadable(String),
 
+    #[error("path contains a line break: {0:?}")]
+    PathHasLineBreak(String),
+
     #[error("duplicate entry: {0}")]
     DuplicateEntry(String),
 
+    #[error("entry limit reached: the backup list holds at most {0} entries")]
+    TooManyEntries(u32),
+
     #[error("entry not found: {0}")]
     EntryNotFound(String),
 
+    #[error("not backed up yet: {0}")]
+    NotBackedUp(String),
+
     #[error("sync failed: {0}")]
     SyncFailed(String),
 
-    #[error("rsync error (exit code {code}): {message}")]
+    #[error("a sync operation is already in progress")]
+    SyncBusy,
+
+    #[error("purge refused: {0}")]
+    PurgeRefused(String),
+
+    /// Every path failed validation; the report says how.
+    #[error("no valid paths to sync: {}", .0.summary())]
+    ValidationFailed(crate::sync::validation::ValidationReport),
+
+    #[error(
+        "rsync error (exit code {code}, {}): {message}",
+        crate::sync::executor::explain_exit_code(*.code)
+    )]
     RsyncError { code: i32, message: String },
 
     #[error("store error: {0}")]
     StoreError(String),
 
+    #[error("manifest error: {0}")]
+    ManifestError(String),
+
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("invalid settings: {0}")]
+    InvalidSettings(String),
+
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
 }
 
+impl ShrikeError {
+    /// Classification of the rsync exit code, for `RsyncError` only.
+    pub fn rsync_exit_class(&self) -> Option<RsyncExitClass> {
+        match self {
+            ShrikeError::RsyncError { code, .. } => Some(classify_exit_code(*code)),
+            _ => None,
+        }
+    }
+}
+
 // Serialize for Tauri IPC — Tauri requires commands return Result<T, String>
 // or a serializable error type
 pub type Result<T> = std::result::Result<T, ShrikeError>;
@@ -63,6 +106,67 @@ mod tests {
         assert_eq!(err.to_string(), "path does not exist: /foo/bar");
     }
 
+    #[test]
+    fn error_displays_line_break_path_escaped() {
+        let err = ShrikeError::PathHasLineBreak("/a\nb".into());
+        assert_eq!(err.to_string(), r#"path contains a line break: "/a\nb""#);
+    }
+
+    #[test]
+    fn error_displays_not_backed_up() {
+        let err = ShrikeError::NotBackedUp("/Users/me/.zshrc".into());
+        assert_eq!(err.to_string(), "not backed up yet: /Users/me/.zshrc");
+    }
+
+    #[test]
+    fn error_displays_too_many_entries() {
+        assert_eq!(
+            ShrikeError::TooManyEntries(100).to_string(),
+            "entry limit reached: the backup list holds at most 100 entries"
+        );
+    }
+
+    #[test]
+    fn error_displays_sync_busy() {
+        assert_eq!(
+            ShrikeError::SyncBusy.to_string(),
+            "a sync operation is already in progress"
+        );
+    }
+
+    #[test]
+    fn error_displays_purge_refused() {
+        assert_eq!(
+            ShrikeError::PurgeRefused("confirmation does not match".into()).to_string(),
+            "purge refused: confirmation does not match"
+        );
+    }
+
+    #[test]
+    fn error_displays_manifest_error() {
+        assert_eq!(
+            ShrikeError::ManifestError("signing key is empty".into()).to_string(),
+            "manifest error: signing key is empty"
+        );
+    }
+
+    #[test]
+    fn error_displays_validation_summary() {
+        let err = ShrikeError::ValidationFailed(crate::sync::validation::ValidationReport {
+            total: 2,
+            valid_count: 0,
+            errors: vec![
+                crate::sync::validation::PathValidation::NotFound("/a".into()),
+                crate::sync::validation::PathValidation::NotFound("/b".into()),
+            ],
+            duplicates: vec![],
+        });
+        assert_eq!(
+            err.to_string(),
+            "no valid paths to sync: 0/2 paths valid; issues: 2 not found"
+        );
+    }
+
     #[test]
     fn error_displays_rsync_error() {
         let err = ShrikeError::RsyncError {
@@ -71,10 +175,20 @@ mod tests {
         };
         assert_eq!(
             err.to_string(),
-            "rsync error (exit code 23): partial transfer"
+            "rsync error (exit code 23, partial transfer due to errors): partial transfer"
+        );
+        assert_eq!(
+            err.rsync_exit_class(),
+            Some(RsyncExitClass::PartialTransfer)
         );
     }
 
+    #[test]
+    fn rsync_exit_class_only_for_rsync_errors() {
+        let err = ShrikeError::SyncFailed("boom".into());
+        assert_eq!(err.rsync_exit_class(), None);
+    }
+
     #[test]
     fn error_serializes_to_string() {
         let err = ShrikeError::DuplicateEntry("/a/b".into());
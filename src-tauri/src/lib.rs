@@ -1,5 +1,14 @@
 pub mod commands;
+pub mod config;
+pub mod dashboard;
+pub mod drive;
 pub mod error;
+pub mod logging;
+pub mod manifest;
+pub mod migration;
+pub mod oneshot;
+pub mod relocate;
+pub mod scheduler;
 pub mod sync;
 pub mod types;
 pub mod webhook;
@@ -7,11 +16,13 @@ pub mod webhook;
 use tauri::image::Image;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{Manager, WindowEvent};
+use tauri::{AppHandle, Listener, Manager, WindowEvent};
 use tauri_plugin_autostart::MacosLauncher;
-use tauri_plugin_store::StoreExt;
 
 const TRAY_ICON_BYTES: &[u8] = include_bytes!("../icons/tray-icon.png");
+const TRAY_SYNCING_ICON_BYTES: &[u8] = include_bytes!("../icons/tray-icon-syncing.png");
+/// How often the idle tray tooltip's "last sync" age is refreshed.
+const TRAY_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
 #[cfg(target_os = "macos")]
 const APP_ICON_BYTES: &[u8] = include_bytes!("../icons/icon.png");
 
@@ -38,10 +49,73 @@ pub fn restore_dock_icon() {
     }
 }
 
+/// Human-readable age of a sync, e.g. `just now`, `3m ago`, `2h ago`.
+fn format_age(secs: i64) -> String {
+    match secs {
+        ..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// Tray tooltip for the sync state, e.g. `Shrike — syncing…` or
+/// `Shrike — last sync 3m ago`.
+pub fn format_tray_tooltip(
+    status: types::SyncStatus,
+    last_synced: Option<chrono::DateTime<chrono::Utc>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    match (status, last_synced) {
+        (types::SyncStatus::Running, _) => "Shrike — syncing…".to_string(),
+        (types::SyncStatus::Idle, None) => "Shrike — never synced".to_string(),
+        (types::SyncStatus::Idle, Some(at)) => {
+            let age = format_age((now - at).num_seconds());
+            format!("Shrike — last sync {age}")
+        }
+    }
+}
+
+/// Point the tray icon and tooltip at the current sync state.
+fn update_tray(app: &AppHandle, status: types::SyncStatus) {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+    let last_synced = commands::backup_freshness(app.clone())
+        .ok()
+        .and_then(|f| f.last_synced);
+    let _ = tray.set_tooltip(Some(format_tray_tooltip(
+        status,
+        last_synced,
+        chrono::Utc::now(),
+    )));
+    let bytes = match status {
+        types::SyncStatus::Running => TRAY_SYNCING_ICON_BYTES,
+        types::SyncStatus::Idle => TRAY_ICON_BYTES,
+    };
+    if let Ok(icon) = Image::from_bytes(bytes) {
+        let _ = tray.set_icon(Some(icon));
+        let _ = tray.set_icon_as_template(true);
+    }
+}
+
 pub fn run() {
+    // One-shot mode for launchd/cron: sync and exit before any UI is built
+    if oneshot::wants_sync_once(std::env::args()) {
+        let code = match oneshot::default_store_path() {
+            Some(path) => oneshot::sync_once(&path),
+            None => {
+                eprintln!("shrike: could not locate the app data directory");
+                1
+            }
+        };
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
@@ -49,30 +123,107 @@ pub fn run() {
         ))
         .invoke_handler(tauri::generate_handler![
             commands::add_entry,
+            commands::add_detected_configs,
             commands::remove_entry,
             commands::list_entries,
+            commands::list_entries_paged,
+            commands::check_entries,
+            commands::refresh_entry_sizes,
+            commands::grouped_entries,
+            commands::migrate_store,
+            commands::set_entry_scheduled,
+            commands::set_entry_enabled,
+            commands::set_entry_compress,
+            commands::set_entry_excludes,
+            commands::set_entry_includes,
+            commands::update_entry_path,
+            commands::suggest_relocations,
             commands::get_settings,
             commands::update_settings,
+            commands::store_warning,
+            commands::reschedule_auto_sync,
+            commands::set_log_level,
+            commands::get_log_path,
+            commands::validate_token_strength,
+            commands::webhook_status,
+            commands::restart_webhook_server,
+            commands::redetect_gdrive_path,
+            commands::list_gdrive_roots,
+            commands::purge_machine_backup,
             commands::trigger_sync,
+            commands::trigger_sync_preview,
+            commands::list_sync_history,
+            commands::export_history_csv,
+            commands::classify_rsync_exit,
+            commands::onboarding_status,
+            commands::backup_freshness,
+            commands::dashboard_summary,
+            commands::simulate_with_settings,
+            commands::sync_plan,
+            commands::preview_excluded,
+            commands::diff_configs,
+            commands::detect_destination_collisions,
+            commands::write_signed_manifest,
+            commands::verify_manifest,
+            commands::supported_features,
+            commands::version_info,
+            commands::check_drive_online,
             commands::get_autostart,
             commands::set_autostart,
             commands::set_tray_visible,
             commands::set_dock_visible,
             commands::scan_coding_configs,
             commands::scan_coding_configs_tree,
+            commands::list_backup_tree,
+            commands::sync_coding_configs,
+            commands::restore_coding_configs,
+            commands::restore_entry,
+            commands::reveal_backup,
+            commands::verify_sync,
+            commands::restore_encrypted_backup,
+            commands::child_coverage,
         ])
         .setup(|app| {
-            // Load settings
-            let store = app.store("shrike_data.json")?;
-            let settings = match store.get("settings") {
-                Some(val) => {
-                    serde_json::from_value::<types::AppSettings>(val).unwrap_or_default()
+            // Log to a file as well, so a corrupt store is reported there
+            match app.path().app_log_dir() {
+                Ok(dir) => {
+                    if let Err(e) = logging::init_file(&dir) {
+                        eprintln!("log file disabled: {e}");
+                    }
                 }
-                None => types::AppSettings::default(),
-            };
+                Err(e) => eprintln!("log file disabled: {e}"),
+            }
+
+            // Normalize entries saved by older versions (runs once per schema bump)
+            if let Err(e) = commands::migrate_store_if_needed(app.handle()) {
+                logging::log(
+                    logging::LogLevel::Error,
+                    format_args!("store migration failed: {e}"),
+                );
+            }
+
+            // Load settings; a corrupt store is preserved and reported
+            let settings = commands::load_startup_settings(app.handle());
+
+            if let Ok(level) = settings.log_level.parse() {
+                logging::set_level(level);
+            }
+            logging::log(
+                logging::LogLevel::Debug,
+                format_args!("loaded settings: {settings:?}"),
+            );
 
             // Start webhook server
-            webhook::start_webhook_server(app.handle().clone(), settings.webhook_port);
+            match settings.webhook_addrs() {
+                Ok(addrs) => webhook::start_webhook_server(app.handle().clone(), addrs),
+                Err(e) => logging::log(
+                    logging::LogLevel::Error,
+                    format_args!("webhook server not started: {e}"),
+                ),
+            }
+
+            // Start the auto-sync timer (idle until an interval is set)
+            scheduler::start_auto_sync(app.handle().clone(), settings.auto_sync_interval_minutes);
 
             // Build system tray
             let quit_i = MenuItem::with_id(app, "quit", "Quit Shrike", true, None::<&str>)?;
@@ -115,6 +266,25 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Follow sync runs in the tray, and keep the idle age current
+            let handle = app.handle().clone();
+            app.listen(commands::SYNC_STARTED_EVENT, move |_| {
+                update_tray(&handle, types::SyncStatus::Running);
+            });
+            let handle = app.handle().clone();
+            app.listen(commands::SYNC_FINISHED_EVENT, move |_| {
+                update_tray(&handle, types::SyncStatus::Idle);
+            });
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    if !sync::is_sync_running() {
+                        update_tray(&handle, types::SyncStatus::Idle);
+                    }
+                    tokio::time::sleep(TRAY_REFRESH_INTERVAL).await;
+                }
+            });
+
             // Apply tray visibility from settings
             if !settings.show_tray_icon
                 && let Some(tray) = app.tray_by_id("main-tray")
@@ -153,3 +323,37 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use types::SyncStatus;
+
+    #[test]
+    fn format_tray_tooltip_while_syncing() {
+        let now = Utc::now();
+        assert_eq!(
+            format_tray_tooltip(SyncStatus::Running, Some(now), now),
+            "Shrike — syncing…"
+        );
+    }
+
+    #[test]
+    fn format_tray_tooltip_shows_last_sync_age() {
+        let now = Utc::now();
+        let tooltip = |ago: Duration| format_tray_tooltip(SyncStatus::Idle, Some(now - ago), now);
+        assert_eq!(tooltip(Duration::zero()), "Shrike — last sync just now");
+        assert_eq!(tooltip(Duration::minutes(3)), "Shrike — last sync 3m ago");
+        assert_eq!(tooltip(Duration::hours(5)), "Shrike — last sync 5h ago");
+        assert_eq!(tooltip(Duration::days(2)), "Shrike — last sync 2d ago");
+    }
+
+    #[test]
+    fn format_tray_tooltip_never_synced() {
+        assert_eq!(
+            format_tray_tooltip(SyncStatus::Idle, None, Utc::now()),
+            "Shrike — never synced"
+        );
+    }
+}
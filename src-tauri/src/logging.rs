@@ -0,0 +1,247 @@
+//! Runtime-adjustable log filter and log file.
+//!
+//! Shrike logs to stderr and, once `init_file` has run, to a daily log file
+//! (`shrike-YYYY-MM-DD.log`) in the app's log directory, so logs survive a
+//! launch from Finder. The active level lives in a global atomic so it can
+//! be raised (e.g. to `debug` to see full rsync invocations) without
+//! restarting the app.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ShrikeError;
+
+/// Log verbosity, from least to most verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+    Trace = 4,
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Error,
+            1 => Self::Warn,
+            2 => Self::Info,
+            3 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ShrikeError;
+
+    /// Parse a level name, case-insensitively ("warning" is accepted for `warn`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" | "warning" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(ShrikeError::InvalidSettings(format!(
+                "invalid log level: {s:?} (expected error, warn, info, debug, or trace)"
+            ))),
+        }
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Returns the active log level.
+pub fn level() -> LogLevel {
+    LogLevel::from_u8(LOG_LEVEL.load(Ordering::Relaxed))
+}
+
+/// Set the active log level.
+pub fn set_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns true if messages at `level` are currently emitted.
+pub fn enabled(level: LogLevel) -> bool {
+    level <= self::level()
+}
+
+/// Directory the log files are written to, once `init_file` has run.
+static LOG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Daily log files kept by `init_file`; older ones are deleted.
+pub const MAX_LOG_FILES: usize = 7;
+
+/// Name of the log file for `date`.
+pub fn log_file_name(date: NaiveDate) -> String {
+    format!("shrike-{}.log", date.format("%Y-%m-%d"))
+}
+
+/// Format one log file line (without the trailing newline).
+pub fn format_line(at: DateTime<Utc>, level: LogLevel, message: impl fmt::Display) -> String {
+    let timestamp = at.to_rfc3339_opts(SecondsFormat::Millis, true);
+    format!("{timestamp} [{level}] {message}")
+}
+
+/// Start writing logs to daily files in `dir`, creating it if needed, and
+/// delete all but the newest `MAX_LOG_FILES` of them.
+pub fn init_file(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    prune_log_files(dir, MAX_LOG_FILES)?;
+    *LOG_DIR.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir.to_path_buf());
+    Ok(())
+}
+
+/// Delete the oldest log files in `dir` so at most `keep` remain.
+fn prune_log_files(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("shrike-") && n.ends_with(".log"))
+        })
+        .collect();
+    // Dated names sort chronologically
+    files.sort();
+    let excess = files.len().saturating_sub(keep);
+    for path in &files[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Path of today's log file, or `None` before `init_file` has run.
+pub fn log_path() -> Option<PathBuf> {
+    let dir = LOG_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone()?;
+    Some(dir.join(log_file_name(Utc::now().date_naive())))
+}
+
+/// Write `message` to stderr and the log file if `level` is enabled.
+///
+/// A log file that can't be written is ignored; stderr still gets the message.
+pub fn log(level: LogLevel, message: impl fmt::Display) {
+    if !enabled(level) {
+        return;
+    }
+    eprintln!("[shrike {level}] {message}");
+    if let Some(path) = log_path() {
+        let line = format_line(Utc::now(), level, message);
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{line}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_known_levels() {
+        assert_eq!("error".parse::<LogLevel>().unwrap(), LogLevel::Error);
+        assert_eq!("WARN".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!("warning".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!(" info ".parse::<LogLevel>().unwrap(), LogLevel::Info);
+        assert_eq!("Debug".parse::<LogLevel>().unwrap(), LogLevel::Debug);
+        assert_eq!("trace".parse::<LogLevel>().unwrap(), LogLevel::Trace);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_level() {
+        for value in ["", "verbose", "5", "infoo"] {
+            let err = value.parse::<LogLevel>().unwrap_err();
+            assert!(matches!(err, ShrikeError::InvalidSettings(_)));
+        }
+    }
+
+    #[test]
+    fn level_display_roundtrips() {
+        for level in [
+            LogLevel::Error,
+            LogLevel::Warn,
+            LogLevel::Info,
+            LogLevel::Debug,
+            LogLevel::Trace,
+        ] {
+            assert_eq!(level.to_string().parse::<LogLevel>().unwrap(), level);
+            assert_eq!(LogLevel::from_u8(level as u8), level);
+        }
+    }
+
+    #[test]
+    fn levels_are_ordered_by_verbosity() {
+        assert!(LogLevel::Error < LogLevel::Warn);
+        assert!(LogLevel::Debug < LogLevel::Trace);
+    }
+
+    #[test]
+    fn format_line_has_timestamp_and_level() {
+        let at = DateTime::parse_from_rfc3339("2026-03-01T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            format_line(at, LogLevel::Warn, "rsync stderr"),
+            "2026-03-01T09:30:00.000Z [warn] rsync stderr"
+        );
+    }
+
+    #[test]
+    fn log_file_name_is_dated() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(log_file_name(date), "shrike-2026-03-01.log");
+    }
+
+    #[test]
+    fn prune_keeps_newest_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for day in 1..=4 {
+            let date = NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+            std::fs::write(dir.path().join(log_file_name(date)), "").unwrap();
+        }
+        std::fs::write(dir.path().join("other.txt"), "").unwrap();
+
+        prune_log_files(dir.path(), 2).unwrap();
+
+        let mut names: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "other.txt",
+                "shrike-2026-03-03.log",
+                "shrike-2026-03-04.log"
+            ]
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Signed backup manifest.
+//!
+//! Walks the backup destination, records a SHA-256 checksum for every file,
+//! and writes the result to `manifest.json` together with an HMAC-SHA256
+//! signature. A later `verify` detects both a tampered manifest (signature
+//! mismatch) and tampered or missing backup files (checksum mismatch).
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Result, ShrikeError};
+
+/// File name of the manifest inside the destination directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest format version, bumped on incompatible layout changes.
+const MANIFEST_VERSION: u32 = 1;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Checksum of a single backed-up file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the destination directory, `/`-separated
+    pub path: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// Checksums of every file under a backup destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    /// Entries sorted by path
+    pub files: Vec<ManifestEntry>,
+}
+
+/// On-disk form of `manifest.json`: the manifest plus its signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub manifest: Manifest,
+    /// Lowercase hex HMAC-SHA256 over the serialized manifest
+    pub hmac_sha256: String,
+}
+
+/// Outcome of verifying a signed manifest against the destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestVerification {
+    /// Whether the manifest itself is untampered
+    pub signature_valid: bool,
+    /// Files whose current checksum differs from the manifest
+    pub mismatched: Vec<String>,
+    /// Files listed in the manifest that no longer exist
+    pub missing: Vec<String>,
+}
+
+impl ManifestVerification {
+    /// Returns true if the signature is valid and every file matches.
+    pub fn is_ok(&self) -> bool {
+        self.signature_valid && self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub(crate) fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Compute the SHA-256 of a file by streaming its contents.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Recursively collect regular files under `dir`. Symlinks are not followed.
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if file_type.is_file() && !(dir == root && entry.file_name() == MANIFEST_FILE) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Build a manifest of every file under `destination` (excluding the
+/// manifest file itself).
+pub fn build_manifest(destination: &Path) -> Result<Manifest> {
+    let mut paths = Vec::new();
+    collect_files(destination, destination, &mut paths)?;
+
+    let mut files = paths
+        .iter()
+        .map(|path| {
+            Ok(ManifestEntry {
+                path: relative_path(destination, path),
+                size: fs::metadata(path)?.len(),
+                sha256: sha256_file(path)?,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Manifest {
+        version: MANIFEST_VERSION,
+        generated_at: Utc::now(),
+        files,
+    })
+}
+
+fn manifest_mac(manifest: &Manifest, key: &str) -> Result<HmacSha256> {
+    if key.is_empty() {
+        return Err(ShrikeError::ManifestError(
+            "signing key is empty".to_string(),
+        ));
+    }
+    let payload =
+        serde_json::to_vec(manifest).map_err(|e| ShrikeError::ManifestError(e.to_string()))?;
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes())
+        .map_err(|e| ShrikeError::ManifestError(e.to_string()))?;
+    mac.update(&payload);
+    Ok(mac)
+}
+
+/// Sign a manifest with HMAC-SHA256, returning the hex signature.
+pub fn sign_manifest(manifest: &Manifest, key: &str) -> Result<String> {
+    Ok(to_hex(
+        &manifest_mac(manifest, key)?.finalize().into_bytes(),
+    ))
+}
+
+/// Check a manifest signature in constant time.
+pub fn verify_signature(signed: &SignedManifest, key: &str) -> Result<bool> {
+    let Some(expected) = from_hex(&signed.hmac_sha256) else {
+        return Ok(false);
+    };
+    Ok(manifest_mac(&signed.manifest, key)?
+        .verify_slice(&expected)
+        .is_ok())
+}
+
+/// Generate a manifest for `destination`, sign it, and write it to
+/// `<destination>/manifest.json`. Returns the manifest file path.
+pub fn write_signed_manifest(destination: &Path, key: &str) -> Result<PathBuf> {
+    let manifest = build_manifest(destination)?;
+    let hmac_sha256 = sign_manifest(&manifest, key)?;
+    let signed = SignedManifest {
+        manifest,
+        hmac_sha256,
+    };
+
+    let json = serde_json::to_string_pretty(&signed)
+        .map_err(|e| ShrikeError::ManifestError(e.to_string()))?;
+    let path = destination.join(MANIFEST_FILE);
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// Verify `<destination>/manifest.json`: check its signature, then
+/// recompute every listed checksum.
+///
+/// A manifest with a bad signature is not trusted, so no file is read on
+/// its behalf; a signed one listing a path that would leave the destination
+/// (absolute, or with `..`) is rejected with `ShrikeError::ManifestError`.
+pub fn verify_manifest(destination: &Path, key: &str) -> Result<ManifestVerification> {
+    let path = destination.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Err(ShrikeError::PathNotFound(
+            path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let signed: SignedManifest = serde_json::from_str(&content)
+        .map_err(|e| ShrikeError::ManifestError(format!("invalid manifest: {e}")))?;
+
+    if !verify_signature(&signed, key)? {
+        return Ok(ManifestVerification {
+            signature_valid: false,
+            mismatched: vec![],
+            missing: vec![],
+        });
+    }
+
+    let mut mismatched = Vec::new();
+    let mut missing = Vec::new();
+    for entry in &signed.manifest.files {
+        let relative = Path::new(&entry.path);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(ShrikeError::ManifestError(format!(
+                "path outside the destination: {}",
+                entry.path
+            )));
+        }
+        let file = destination.join(relative);
+        if !file.is_file() {
+            missing.push(entry.path.clone());
+        } else if sha256_file(&file)? != entry.sha256 {
+            mismatched.push(entry.path.clone());
+        }
+    }
+
+    Ok(ManifestVerification {
+        signature_valid: true,
+        mismatched,
+        missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Users/me/.claude")).unwrap();
+        fs::write(dir.path().join("Users/me/.zshrc"), "export A=1").unwrap();
+        fs::write(dir.path().join("Users/me/.claude/settings.json"), "{}").unwrap();
+        dir
+    }
+
+    #[test]
+    fn sha256_file_known_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn build_manifest_lists_files_sorted() {
+        let dir = backup_tree();
+        let manifest = build_manifest(dir.path()).unwrap();
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["Users/me/.claude/settings.json", "Users/me/.zshrc"]
+        );
+        assert_eq!(manifest.files[1].size, 10);
+    }
+
+    #[test]
+    fn generate_then_verify_succeeds() {
+        let dir = backup_tree();
+        let path = write_signed_manifest(dir.path(), "secret-key").unwrap();
+        assert!(path.ends_with(MANIFEST_FILE));
+
+        let report = verify_manifest(dir.path(), "secret-key").unwrap();
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn manifest_excludes_itself_on_regeneration() {
+        let dir = backup_tree();
+        write_signed_manifest(dir.path(), "k").unwrap();
+        let manifest = build_manifest(dir.path()).unwrap();
+        assert!(manifest.files.iter().all(|f| f.path != MANIFEST_FILE));
+    }
+
+    #[test]
+    fn verify_detects_modified_manifest() {
+        let dir = backup_tree();
+        let path = write_signed_manifest(dir.path(), "secret-key").unwrap();
+
+        let mut signed: SignedManifest =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        signed.manifest.files[0].sha256 = "0".repeat(64);
+        fs::write(&path, serde_json::to_string(&signed).unwrap()).unwrap();
+
+        let report = verify_manifest(dir.path(), "secret-key").unwrap();
+        assert!(!report.signature_valid);
+        assert!(!report.is_ok());
+        // An untrusted manifest is not checked against the files
+        assert!(report.mismatched.is_empty());
+    }
+
+    #[test]
+    fn verify_rejects_paths_outside_destination() {
+        let dir = backup_tree();
+        for path in ["../outside.txt", "/etc/hosts", "Users/../../outside.txt"] {
+            let manifest = Manifest {
+                version: MANIFEST_VERSION,
+                generated_at: Utc::now(),
+                files: vec![ManifestEntry {
+                    path: path.to_string(),
+                    size: 0,
+                    sha256: String::new(),
+                }],
+            };
+            let signed = SignedManifest {
+                hmac_sha256: sign_manifest(&manifest, "k").unwrap(),
+                manifest,
+            };
+            fs::write(
+                dir.path().join(MANIFEST_FILE),
+                serde_json::to_string(&signed).unwrap(),
+            )
+            .unwrap();
+
+            let err = verify_manifest(dir.path(), "k").unwrap_err();
+            assert!(
+                matches!(err, ShrikeError::ManifestError(_)),
+                "{path}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn verify_detects_modified_backup_file() {
+        let dir = backup_tree();
+        write_signed_manifest(dir.path(), "secret-key").unwrap();
+        fs::write(dir.path().join("Users/me/.zshrc"), "tampered").unwrap();
+
+        let report = verify_manifest(dir.path(), "secret-key").unwrap();
+        assert!(report.signature_valid);
+        assert_eq!(report.mismatched, vec!["Users/me/.zshrc"]);
+    }
+
+    #[test]
+    fn verify_detects_missing_backup_file() {
+        let dir = backup_tree();
+        write_signed_manifest(dir.path(), "secret-key").unwrap();
+        fs::remove_file(dir.path().join("Users/me/.zshrc")).unwrap();
+
+        let report = verify_manifest(dir.path(), "secret-key").unwrap();
+        assert_eq!(report.missing, vec!["Users/me/.zshrc"]);
+    }
+
+    #[test]
+    fn verify_with_wrong_key_fails_signature() {
+        let dir = backup_tree();
+        write_signed_manifest(dir.path(), "secret-key").unwrap();
+        let report = verify_manifest(dir.path(), "other-key").unwrap();
+        assert!(!report.signature_valid);
+    }
+
+    #[test]
+    fn verify_without_manifest_errors() {
+        let dir = backup_tree();
+        let err = verify_manifest(dir.path(), "k").unwrap_err();
+        assert!(matches!(err, ShrikeError::PathNotFound(_)));
+    }
+
+    #[test]
+    fn sign_rejects_empty_key() {
+        let manifest = Manifest {
+            version: MANIFEST_VERSION,
+            generated_at: Utc::now(),
+            files: vec![],
+        };
+        assert!(sign_manifest(&manifest, "").is_err());
+    }
+}
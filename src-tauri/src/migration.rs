@@ -0,0 +1,205 @@
+//! One-time normalization of entries saved by older versions.
+//!
+//! Early versions stored entry paths exactly as entered, before `add_entry`
+//! canonicalized them, and lacked fields added since. Migration canonicalizes
+//! paths, merges entries that turn out to be duplicates, and backfills
+//! defaulted fields. The store's `schema_version` records that it has run.
+
+use std::collections::HashMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Result, ShrikeError};
+use crate::types::BackupEntry;
+
+/// Current store schema version. Stores without a version are version 0.
+///
+/// Version 2 runs the migration again for stores that picked up
+/// non-canonical or duplicate paths after their first one.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A stored path that was rewritten to its canonical form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// What a migration changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Paths rewritten to their canonical form
+    pub canonicalized: Vec<PathChange>,
+    /// Canonical paths whose duplicate entries were merged into one
+    pub merged_duplicates: Vec<String>,
+    /// Number of entries that were missing fields and got defaults
+    pub backfilled: usize,
+}
+
+impl MigrationReport {
+    /// Returns true if any entry was modified or removed.
+    pub fn changed(&self) -> bool {
+        !self.canonicalized.is_empty() || !self.merged_duplicates.is_empty() || self.backfilled > 0
+    }
+}
+
+/// Returns true if `raw` lacks any field that `entry` serializes.
+fn was_backfilled(raw: &Value, entry: &BackupEntry) -> bool {
+    let (Some(raw), Ok(Value::Object(full))) = (raw.as_object(), serde_json::to_value(entry))
+    else {
+        return false;
+    };
+    full.keys().any(|key| !raw.contains_key(key))
+}
+
+/// Migrate raw stored entries to the current schema.
+///
+/// Paths that exist are canonicalized; paths that no longer exist are kept
+/// as-is. When several entries resolve to the same path, the first is kept
+/// and takes the most recent `last_synced` of the group.
+pub fn migrate_items(
+    raw: &[Value],
+    from_version: u32,
+) -> Result<(Vec<BackupEntry>, MigrationReport)> {
+    let mut report = MigrationReport {
+        from_version,
+        to_version: SCHEMA_VERSION,
+        canonicalized: Vec::new(),
+        merged_duplicates: Vec::new(),
+        backfilled: 0,
+    };
+    let mut items: Vec<BackupEntry> = Vec::new();
+    let mut index_by_path: HashMap<String, usize> = HashMap::new();
+
+    for value in raw {
+        let mut entry: BackupEntry = serde_json::from_value(value.clone())
+            .map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+        if was_backfilled(value, &entry) {
+            report.backfilled += 1;
+        }
+
+        if let Ok(canonical) = fs::canonicalize(&entry.path) {
+            let canonical = canonical.to_string_lossy().to_string();
+            if canonical != entry.path {
+                report.canonicalized.push(PathChange {
+                    from: entry.path.clone(),
+                    to: canonical.clone(),
+                });
+                entry.path = canonical;
+            }
+        }
+
+        match index_by_path.get(&entry.path) {
+            Some(&i) => {
+                let kept = &mut items[i];
+                kept.last_synced = kept.last_synced.max(entry.last_synced);
+                if !report.merged_duplicates.contains(&entry.path) {
+                    report.merged_duplicates.push(entry.path.clone());
+                }
+            }
+            None => {
+                index_by_path.insert(entry.path.clone(), items.len());
+                items.push(entry);
+            }
+        }
+    }
+
+    Ok((items, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ItemType;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn raw_entry(path: &str) -> Value {
+        serde_json::to_value(BackupEntry::new(path.to_string(), ItemType::File)).unwrap()
+    }
+
+    #[test]
+    fn migrate_canonicalizes_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let canonical = fs::canonicalize(dir.path().join("a.txt")).unwrap();
+        let legacy = format!("{}/./a.txt", dir.path().display());
+
+        let (items, report) = migrate_items(&[raw_entry(&legacy)], 0).unwrap();
+        assert_eq!(items[0].path, canonical.to_string_lossy());
+        assert_eq!(report.canonicalized.len(), 1);
+        assert_eq!(report.canonicalized[0].from, legacy);
+        assert!(report.changed());
+    }
+
+    #[test]
+    fn migrate_merges_duplicates_after_canonicalization() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let canonical = fs::canonicalize(dir.path().join("a.txt")).unwrap();
+        let canonical = canonical.to_string_lossy().to_string();
+
+        let mut newer = raw_entry(&format!("{}/./a.txt", dir.path().display()));
+        newer["last_synced"] = json!(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap());
+        let first = raw_entry(&canonical);
+
+        let (items, report) = migrate_items(&[first.clone(), newer], 0).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id.to_string(), first["id"].as_str().unwrap());
+        assert_eq!(
+            items[0].last_synced,
+            Some(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(report.merged_duplicates, vec![canonical]);
+    }
+
+    #[test]
+    fn migrate_merges_symlinked_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let (items, report) = migrate_items(
+            &[
+                raw_entry(&real.to_string_lossy()),
+                raw_entry(&link.to_string_lossy()),
+            ],
+            1,
+        )
+        .unwrap();
+        let canonical = fs::canonicalize(&real).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].path, canonical.to_string_lossy());
+        assert_eq!(report.merged_duplicates.len(), 1);
+    }
+
+    #[test]
+    fn migrate_backfills_missing_fields() {
+        let mut legacy = raw_entry("/nonexistent/legacy");
+        legacy.as_object_mut().unwrap().remove("scheduled");
+
+        let (items, report) = migrate_items(&[legacy, raw_entry("/nonexistent/new")], 0).unwrap();
+        assert!(items[0].scheduled);
+        assert_eq!(report.backfilled, 1);
+    }
+
+    #[test]
+    fn migrate_keeps_missing_paths_unchanged() {
+        let (items, report) = migrate_items(&[raw_entry("/nonexistent/shrike-abc")], 0).unwrap();
+        assert_eq!(items[0].path, "/nonexistent/shrike-abc");
+        assert!(!report.changed());
+        assert_eq!(report.to_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn migrate_rejects_malformed_entry() {
+        let err = migrate_items(&[json!({"path": 42})], 0).unwrap_err();
+        assert!(matches!(err, ShrikeError::StoreError(_)));
+    }
+}
@@ -0,0 +1,174 @@
+//! `--sync-once`: run a single sync from the stored config and exit.
+//!
+//! Lets `launchd` or cron drive end-of-day backups without the tray, window,
+//! or webhook server. The store file is read directly, since no Tauri app
+//! (and therefore no store plugin) is running.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::{Result, ShrikeError};
+use crate::sync;
+use crate::types::{AppSettings, BackupEntry, SyncResult};
+
+/// Command-line flag that selects one-shot mode.
+pub const SYNC_ONCE_FLAG: &str = "--sync-once";
+
+/// Bundle identifier from `tauri.conf.json`, used to locate the store.
+const APP_IDENTIFIER: &str = "com.shrike.app";
+
+const STORE_FILE: &str = "shrike_data.json";
+
+/// Exit code for failures that happen before rsync runs.
+const EXIT_FAILURE: i32 = 1;
+
+/// Returns true if the process arguments request one-shot mode.
+pub fn wants_sync_once<I, S>(args: I) -> bool
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    args.into_iter().any(|arg| arg.as_ref() == SYNC_ONCE_FLAG)
+}
+
+/// Path of the store file written by the Tauri store plugin.
+pub fn default_store_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER).join(STORE_FILE))
+}
+
+/// Load entries and settings from a store file.
+fn load_store(store_path: &Path) -> Result<(Vec<BackupEntry>, AppSettings)> {
+    let content = fs::read_to_string(store_path)
+        .map_err(|e| ShrikeError::StoreError(format!("{}: {e}", store_path.display())))?;
+    let mut store: Value =
+        serde_json::from_str(&content).map_err(|e| ShrikeError::StoreError(e.to_string()))?;
+
+    let items = match store.get_mut("items").map(Value::take) {
+        Some(val) => {
+            serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?
+        }
+        None => Vec::new(),
+    };
+    let settings = match store.get_mut("settings").map(Value::take) {
+        Some(val) => {
+            serde_json::from_value(val).map_err(|e| ShrikeError::StoreError(e.to_string()))?
+        }
+        None => AppSettings::default(),
+    };
+    Ok((items, settings))
+}
+
+/// Map a sync outcome to a process exit code, passing rsync's through.
+fn exit_code_for(result: &Result<SyncResult>) -> i32 {
+    match result {
+        Ok(result) => result.exit_code,
+        Err(ShrikeError::RsyncError { code, .. }) => *code,
+        Err(_) => EXIT_FAILURE,
+    }
+}
+
+/// Run one sync using the store at `store_path` and return the exit code.
+pub fn sync_once(store_path: &Path) -> i32 {
+    let result = load_store(store_path).and_then(|(entries, settings)| {
+        sync::merge_destination_results(sync::execute_sync_all(&entries, &settings, false))
+    });
+
+    match &result {
+        Ok(r) => println!(
+            "shrike: synced {} files, {} directories",
+            r.files_transferred, r.dirs_transferred
+        ),
+        Err(e) => eprintln!("shrike: {e}"),
+    }
+    exit_code_for(&result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn wants_sync_once_detects_flag() {
+        assert!(wants_sync_once(["shrike", "--sync-once"]));
+        assert!(!wants_sync_once(["shrike", "--minimized"]));
+        assert!(!wants_sync_once(Vec::<String>::new()));
+    }
+
+    #[test]
+    fn exit_code_passes_rsync_code_through() {
+        let err = Err(ShrikeError::RsyncError {
+            code: 23,
+            message: "partial transfer".into(),
+        });
+        assert_eq!(exit_code_for(&err), 23);
+    }
+
+    #[test]
+    fn exit_code_for_success_and_pipeline_errors() {
+        let ok = Ok(SyncResult {
+            files_transferred: 1,
+            dirs_transferred: 0,
+            bytes_transferred: 0,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
+        });
+        assert_eq!(exit_code_for(&ok), 0);
+        assert_eq!(
+            exit_code_for(&Err(ShrikeError::SyncFailed("no entries".into()))),
+            EXIT_FAILURE
+        );
+    }
+
+    #[test]
+    fn sync_once_missing_store_fails() {
+        assert_eq!(
+            sync_once(Path::new("/nonexistent/shrike_data.json")),
+            EXIT_FAILURE
+        );
+    }
+
+    #[test]
+    fn sync_once_store_without_entries_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STORE_FILE);
+        fs::write(&path, r#"{"settings":{"gdrive_path":"/tmp/g","backup_dir_name":"B","machine_name":"M","webhook_port":0,"webhook_token":"t"}}"#).unwrap();
+        assert_eq!(sync_once(&path), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn load_store_reads_items_and_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STORE_FILE);
+        let entry = BackupEntry::new("/etc/hosts".into(), crate::types::ItemType::File);
+        let store = serde_json::json!({
+            "items": [entry],
+            "settings": {"gdrive_path":"/tmp/g","backup_dir_name":"B","machine_name":"M","webhook_port":0,"webhook_token":"t"},
+            "schema_version": 1,
+        });
+        fs::write(&path, store.to_string()).unwrap();
+
+        let (items, settings) = load_store(&path).unwrap();
+        assert_eq!(items, vec![entry]);
+        assert_eq!(settings.destination_path().unwrap(), "/tmp/g/B/M");
+    }
+
+    #[test]
+    fn load_store_rejects_malformed_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STORE_FILE);
+        fs::write(&path, "{not json").unwrap();
+        assert!(matches!(load_store(&path), Err(ShrikeError::StoreError(_))));
+    }
+}
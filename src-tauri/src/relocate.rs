@@ -0,0 +1,260 @@
+//! Relocation suggestions for entries whose source has moved.
+//!
+//! When a tracked path disappears, the file or directory has often just
+//! been moved within the home directory. The search here looks for an item
+//! with the same basename and type, bounded by depth, directory count and
+//! wall-clock time so a large home directory can't stall the UI.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::types::{BackupEntry, ItemType, Relocation};
+
+/// Maximum directory depth below the search root.
+pub const MAX_SEARCH_DEPTH: usize = 6;
+
+/// Maximum number of directories read per search.
+pub const MAX_VISITED_DIRS: usize = 20_000;
+
+/// Maximum wall-clock time for one search, or for every search made by one
+/// `suggest_relocations` call.
+pub const SEARCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Directory names that are never descended into: large, generated, or
+/// (for `Library`) mostly app state rather than user files.
+const SKIPPED_DIRS: &[&str] = &["Library", "node_modules", ".git", ".Trash", "target"];
+
+/// Limits applied to a single basename search.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchBounds {
+    pub max_depth: usize,
+    pub max_visited: usize,
+    pub timeout: Duration,
+}
+
+impl Default for SearchBounds {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_SEARCH_DEPTH,
+            max_visited: MAX_VISITED_DIRS,
+            timeout: SEARCH_TIMEOUT,
+        }
+    }
+}
+
+/// Breadth-first search under `root` for an item named `name` of the given
+/// type. Symlinks are not followed.
+///
+/// Breadth-first order means the shallowest match wins; among matches at
+/// the same depth, the alphabetically first one does.
+pub fn find_by_basename(
+    root: &Path,
+    name: &str,
+    item_type: ItemType,
+    bounds: SearchBounds,
+) -> Option<PathBuf> {
+    search_until(
+        root,
+        name,
+        item_type,
+        bounds,
+        Instant::now() + bounds.timeout,
+    )
+}
+
+/// `find_by_basename`, giving up at `deadline` instead of after
+/// `bounds.timeout`.
+fn search_until(
+    root: &Path,
+    name: &str,
+    item_type: ItemType,
+    bounds: SearchBounds,
+    deadline: Instant,
+) -> Option<PathBuf> {
+    let mut queue = VecDeque::from([(root.to_path_buf(), 0usize)]);
+    let mut visited = 0usize;
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        if visited >= bounds.max_visited || Instant::now() >= deadline {
+            return None;
+        }
+        visited += 1;
+
+        let Ok(read) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut children: Vec<_> = read
+            .filter_map(|e| e.ok())
+            .filter_map(|e| Some((e.file_name(), e.file_type().ok()?, e.path())))
+            .collect();
+        children.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (file_name, file_type, path) in children {
+            let is_dir = file_type.is_dir();
+            if file_name == name && is_dir == (item_type == ItemType::Directory) {
+                return Some(path);
+            }
+            if is_dir
+                && depth + 1 < bounds.max_depth
+                && !SKIPPED_DIRS.iter().any(|s| file_name == *s)
+            {
+                queue.push_back((path, depth + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Suggest a new location for every entry whose path no longer exists.
+///
+/// Entries that still exist, or whose basename can't be found under `home`,
+/// get no suggestion. Paths already tracked by another entry are never
+/// suggested. All the searches share one `bounds.timeout`, so a long list
+/// of missing entries can't multiply the wait.
+pub fn suggest_relocations(
+    entries: &[BackupEntry],
+    home: &Path,
+    bounds: SearchBounds,
+) -> Vec<Relocation> {
+    let deadline = Instant::now() + bounds.timeout;
+    entries
+        .iter()
+        .filter(|e| !Path::new(&e.path).exists())
+        .filter_map(|entry| {
+            let name = Path::new(&entry.path).file_name()?.to_str()?;
+            let found = search_until(home, name, entry.item_type, bounds, deadline)?;
+            let suggested_path = found.to_string_lossy().to_string();
+            if entries.iter().any(|e| e.path == suggested_path) {
+                return None;
+            }
+            Some(Relocation {
+                entry_id: entry.id,
+                old_path: entry.path.clone(),
+                suggested_path,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Documents/archive")).unwrap();
+        fs::write(dir.path().join("Documents/archive/notes.md"), "moved").unwrap();
+        dir
+    }
+
+    #[test]
+    fn suggests_moved_file_by_basename() {
+        let home = home_tree();
+        let old_path = home.path().join("notes.md").to_string_lossy().to_string();
+        let entry = BackupEntry::new(old_path.clone(), ItemType::File);
+
+        let suggestions = suggest_relocations(
+            std::slice::from_ref(&entry),
+            home.path(),
+            SearchBounds::default(),
+        );
+        assert_eq!(
+            suggestions,
+            vec![Relocation {
+                entry_id: entry.id,
+                old_path,
+                suggested_path: home
+                    .path()
+                    .join("Documents/archive/notes.md")
+                    .to_string_lossy()
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn suggestions_stop_at_the_shared_deadline() {
+        let home = home_tree();
+        let entry = BackupEntry::new(
+            home.path().join("notes.md").to_string_lossy().to_string(),
+            ItemType::File,
+        );
+        let expired = SearchBounds {
+            timeout: Duration::ZERO,
+            ..Default::default()
+        };
+        assert!(suggest_relocations(&[entry], home.path(), expired).is_empty());
+    }
+
+    #[test]
+    fn existing_entries_get_no_suggestion() {
+        let home = home_tree();
+        let path = home.path().join("Documents/archive/notes.md");
+        let entry = BackupEntry::new(path.to_string_lossy().to_string(), ItemType::File);
+        assert!(suggest_relocations(&[entry], home.path(), SearchBounds::default()).is_empty());
+    }
+
+    #[test]
+    fn search_respects_item_type() {
+        let home = home_tree();
+        assert!(
+            find_by_basename(
+                home.path(),
+                "notes.md",
+                ItemType::Directory,
+                SearchBounds::default()
+            )
+            .is_none()
+        );
+        assert!(
+            find_by_basename(
+                home.path(),
+                "archive",
+                ItemType::Directory,
+                SearchBounds::default()
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn search_is_bounded_by_depth() {
+        let home = home_tree();
+        let shallow = SearchBounds {
+            max_depth: 2,
+            ..Default::default()
+        };
+        assert!(find_by_basename(home.path(), "notes.md", ItemType::File, shallow).is_none());
+    }
+
+    #[test]
+    fn search_prefers_shallowest_match() {
+        let home = home_tree();
+        fs::write(home.path().join("Documents/notes.md"), "closer").unwrap();
+        let found = find_by_basename(
+            home.path(),
+            "notes.md",
+            ItemType::File,
+            SearchBounds::default(),
+        );
+        assert_eq!(found, Some(home.path().join("Documents/notes.md")));
+    }
+
+    #[test]
+    fn search_skips_library() {
+        let home = tempfile::tempdir().unwrap();
+        fs::create_dir_all(home.path().join("Library/Caches")).unwrap();
+        fs::write(home.path().join("Library/Caches/notes.md"), "").unwrap();
+        assert!(
+            find_by_basename(
+                home.path(),
+                "notes.md",
+                ItemType::File,
+                SearchBounds::default()
+            )
+            .is_none()
+        );
+    }
+}
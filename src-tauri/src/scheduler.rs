@@ -0,0 +1,167 @@
+//! Automatic sync on a fixed interval.
+//!
+//! A background task started from `run()` syncs the scheduled entries every
+//! `auto_sync_interval_minutes`. The interval is pushed through a watch
+//! channel, so saving new settings restarts the timer without relaunching
+//! the app. A tick that lands while another sync is running is skipped.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+use tauri::AppHandle;
+use tokio::sync::watch;
+
+use crate::logging::{self, LogLevel};
+use crate::sync;
+
+/// Sender for the current interval; set once the task is running.
+static INTERVAL: OnceLock<watch::Sender<Option<u32>>> = OnceLock::new();
+
+/// The auto-sync interval, or `None` if disabled (unset or zero minutes).
+pub fn interval(minutes: Option<u32>) -> Option<Duration> {
+    minutes
+        .filter(|&m| m > 0)
+        .map(|m| Duration::minutes(i64::from(m)))
+}
+
+/// When the next automatic sync is due, or `None` if disabled.
+pub fn next_run(interval: Option<Duration>, last_run: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    interval.map(|interval| last_run + interval)
+}
+
+/// Decide whether an automatic sync should start at `now`.
+///
+/// A sync is due once a full interval has passed since `last_run`, and is
+/// skipped while another sync is in progress.
+pub fn should_run_now(
+    interval: Option<Duration>,
+    last_run: DateTime<Utc>,
+    now: DateTime<Utc>,
+    sync_running: bool,
+) -> bool {
+    !sync_running && next_run(interval, last_run).is_some_and(|due| now >= due)
+}
+
+/// Start the auto-sync task with the given interval.
+///
+/// Only the first call starts a task; later calls are ignored.
+pub fn start_auto_sync(app: AppHandle, minutes: Option<u32>) {
+    let (tx, mut rx) = watch::channel(minutes);
+    if INTERVAL.set(tx).is_err() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut last_run = Utc::now();
+        loop {
+            let current = interval(*rx.borrow_and_update());
+            let wait = next_run(current, last_run)
+                .and_then(|due| (due - Utc::now()).to_std().ok())
+                .unwrap_or_default();
+
+            tokio::select! {
+                changed = rx.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    // New settings restart the timer from now
+                    last_run = Utc::now();
+                }
+                _ = tokio::time::sleep(wait), if current.is_some() => {
+                    let now = Utc::now();
+                    if should_run_now(current, last_run, now, sync::is_sync_running()) {
+                        run_once(app.clone()).await;
+                    } else {
+                        logging::log(
+                            LogLevel::Info,
+                            format_args!("auto-sync skipped: a sync is already in progress"),
+                        );
+                    }
+                    last_run = now;
+                }
+            }
+        }
+    });
+}
+
+/// Restart the auto-sync timer with a new interval.
+///
+/// Does nothing if the task was never started.
+pub fn reschedule(minutes: Option<u32>) {
+    if let Some(tx) = INTERVAL.get() {
+        tx.send_replace(minutes);
+    }
+}
+
+async fn run_once(app: AppHandle) {
+    let outcome =
+        tauri::async_runtime::spawn_blocking(move || crate::commands::run_scheduled_sync(&app))
+            .await;
+    match outcome {
+        Ok(Ok(Some(result))) => logging::log(
+            LogLevel::Info,
+            format_args!(
+                "auto-sync transferred {} files, {} directories",
+                result.files_transferred, result.dirs_transferred
+            ),
+        ),
+        Ok(Ok(None)) => logging::log(
+            LogLevel::Debug,
+            format_args!("auto-sync skipped: no scheduled entries"),
+        ),
+        Ok(Err(e)) => logging::log(LogLevel::Warn, format_args!("auto-sync failed: {e}")),
+        Err(e) => logging::log(LogLevel::Warn, format_args!("auto-sync task failed: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_or_unset_interval_disables() {
+        assert_eq!(interval(None), None);
+        assert_eq!(interval(Some(0)), None);
+        assert_eq!(interval(Some(15)), Some(Duration::minutes(15)));
+    }
+
+    #[test]
+    fn should_run_once_interval_has_elapsed() {
+        let last_run = Utc::now();
+        let every = interval(Some(30));
+        assert!(!should_run_now(
+            every,
+            last_run,
+            last_run + Duration::minutes(29),
+            false
+        ));
+        assert!(should_run_now(
+            every,
+            last_run,
+            last_run + Duration::minutes(30),
+            false
+        ));
+        assert!(should_run_now(
+            every,
+            last_run,
+            last_run + Duration::hours(5),
+            false
+        ));
+    }
+
+    #[test]
+    fn should_not_run_while_sync_in_progress() {
+        let last_run = Utc::now();
+        let later = last_run + Duration::hours(1);
+        assert!(!should_run_now(interval(Some(30)), last_run, later, true));
+    }
+
+    #[test]
+    fn should_never_run_when_disabled() {
+        let last_run = Utc::now();
+        let later = last_run + Duration::days(365);
+        assert!(!should_run_now(interval(Some(0)), last_run, later, false));
+        assert!(!should_run_now(None, last_run, later, false));
+        assert_eq!(next_run(None, last_run), None);
+    }
+}
@@ -0,0 +1,437 @@
+//! Rsync capability detection.
+//!
+//! macOS ships either Apple's rsync 2.6.9 or `openrsync`, while Homebrew
+//! provides modern GNU rsync 3.x. Optional settings (xattrs, progress2, ...)
+//! only work on some of these, so we parse `rsync --version` once and map
+//! the result to a list of supported features for the settings UI.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{FeatureSupport, VersionInfo};
+
+/// Path of the rsync bundled with macOS.
+pub const SYSTEM_RSYNC: &str = "/usr/bin/rsync";
+
+/// Which rsync implementation was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RsyncFlavor {
+    /// Samba's GNU rsync (including Apple's bundled 2.6.9)
+    Gnu,
+    /// OpenBSD's openrsync (macOS 15+ default)
+    Openrsync,
+    /// Output could not be recognized
+    Unknown,
+}
+
+/// Capabilities parsed from `rsync --version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RsyncCapabilities {
+    pub flavor: RsyncFlavor,
+    /// Version as `(major, minor, patch)`, if reported
+    pub version: Option<(u32, u32, u32)>,
+    /// Wire protocol version, if reported
+    pub protocol: Option<u32>,
+    /// Whether the build lists `xattrs` among its capabilities
+    pub xattrs: bool,
+}
+
+impl RsyncCapabilities {
+    /// Returns true if this is GNU rsync at or above the given version.
+    fn gnu_at_least(&self, min: (u32, u32, u32)) -> bool {
+        self.flavor == RsyncFlavor::Gnu && self.version.is_some_and(|v| v >= min)
+    }
+
+    /// Returns true if rsync understands `--info=progress2`.
+    pub fn supports_progress2(&self) -> bool {
+        self.gnu_at_least((3, 1, 0))
+    }
+
+    /// Human-readable version string, e.g. "rsync 3.2.7 (protocol 31)".
+    pub fn version_string(&self) -> String {
+        let name = match self.flavor {
+            RsyncFlavor::Openrsync => "openrsync",
+            _ => "rsync",
+        };
+        let mut s = name.to_string();
+        if let Some((major, minor, patch)) = self.version {
+            s.push_str(&format!(" {major}.{minor}.{patch}"));
+        }
+        if let Some(protocol) = self.protocol {
+            s.push_str(&format!(" (protocol {protocol})"));
+        }
+        s
+    }
+}
+
+/// Parse a dotted version like "3.2.7" or "2.6.9" (missing parts become 0).
+fn parse_version(s: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = s.split('.').map(|p| {
+        p.chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<u32>()
+            .ok()
+    });
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Return the word following `keyword` in `line`, if any.
+fn word_after<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let mut words = line.split_whitespace();
+    words.find(|w| *w == keyword)?;
+    words.next()
+}
+
+/// Parse the output of `rsync --version` into capabilities.
+///
+/// Handles GNU rsync (`rsync  version 3.2.7  protocol version 31`) and
+/// openrsync (`openrsync: protocol version 29`).
+pub fn parse_rsync_version(output: &str) -> RsyncCapabilities {
+    let first = output.lines().next().unwrap_or_default();
+
+    let flavor = if first.starts_with("openrsync") {
+        RsyncFlavor::Openrsync
+    } else if first.starts_with("rsync") {
+        RsyncFlavor::Gnu
+    } else {
+        RsyncFlavor::Unknown
+    };
+
+    let version = match flavor {
+        RsyncFlavor::Gnu => word_after(first, "version").and_then(parse_version),
+        // openrsync reports the GNU version it is compatible with on a later
+        // line, e.g. "rsync version 2.6.9 compatible"
+        RsyncFlavor::Openrsync => output
+            .lines()
+            .find(|l| l.starts_with("rsync version"))
+            .and_then(|l| word_after(l, "version"))
+            .and_then(parse_version),
+        RsyncFlavor::Unknown => None,
+    };
+
+    let protocol = output.lines().find_map(|l| {
+        let idx = l.find("protocol version")?;
+        l[idx + "protocol version".len()..]
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    });
+
+    // GNU rsync lists capabilities as a comma-separated block, where missing
+    // features are prefixed with "no " (e.g. "no xattrs").
+    let xattrs = flavor == RsyncFlavor::Gnu
+        && output
+            .split([',', '\n'])
+            .map(str::trim)
+            .any(|cap| cap == "xattrs");
+
+    RsyncCapabilities {
+        flavor,
+        version,
+        protocol,
+        xattrs,
+    }
+}
+
+/// Run `<binary> --version` and parse its capabilities.
+///
+/// Returns `None` if the binary cannot be executed.
+pub fn rsync_capabilities(binary: &str) -> Option<RsyncCapabilities> {
+    let output = Command::new(binary).arg("--version").output().ok()?;
+    // openrsync prints its version banner to stderr
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    if text.trim().is_empty() {
+        text = String::from_utf8_lossy(&output.stderr).to_string();
+    }
+    Some(parse_rsync_version(&text))
+}
+
+/// Resolve `binary` to a path, searching `PATH` if it has no separator.
+pub fn resolve_binary(binary: &str) -> Option<PathBuf> {
+    if binary.contains('/') {
+        let path = PathBuf::from(binary);
+        return path.is_file().then_some(path);
+    }
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Check that `binary` resolves to an executable file, for validating a
+/// configured rsync before it is saved.
+pub fn check_executable(binary: &str) -> crate::error::Result<PathBuf> {
+    let path = resolve_binary(binary).ok_or_else(|| {
+        crate::error::ShrikeError::InvalidSettings(format!("rsync binary not found: {binary}"))
+    })?;
+    if !is_executable(&path) {
+        return Err(crate::error::ShrikeError::InvalidSettings(format!(
+            "rsync binary is not executable: {}",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Describe the running OS, e.g. "macOS 14.5" or "Linux 6.8.0".
+fn os_version() -> String {
+    let (program, args, prefix): (&str, &[&str], &str) = if cfg!(target_os = "macos") {
+        ("sw_vers", &["-productVersion"], "macOS ")
+    } else {
+        ("uname", &["-sr"], "")
+    };
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(|v| format!("{prefix}{v}"))
+        .unwrap_or_else(|| std::env::consts::OS.to_string())
+}
+
+/// Collect Shrike, rsync, and OS versions for `binary`.
+pub fn version_info(binary: &str) -> VersionInfo {
+    let shrike_version = env!("CARGO_PKG_VERSION").to_string();
+    let rsync_path = resolve_binary(binary);
+    let rsync_version = rsync_path
+        .as_ref()
+        .and_then(|path| rsync_capabilities(&path.to_string_lossy()))
+        .map(|caps| caps.version_string())
+        .unwrap_or_else(|| "not found".to_string());
+    let rsync_is_system = rsync_path
+        .as_deref()
+        .is_some_and(|path| path == Path::new(SYSTEM_RSYNC));
+    let os_version = os_version();
+
+    let source = match (&rsync_path, rsync_is_system) {
+        (None, _) => "",
+        (Some(_), true) => ", system",
+        (Some(_), false) => ", custom",
+    };
+    let summary = format!("Shrike {shrike_version} / {rsync_version}{source} / {os_version}");
+
+    VersionInfo {
+        shrike_version,
+        rsync_version,
+        rsync_path: rsync_path.map(|p| p.to_string_lossy().to_string()),
+        rsync_is_system,
+        os_version,
+        summary,
+    }
+}
+
+fn feature(name: &str, supported: bool, reason: &str) -> FeatureSupport {
+    FeatureSupport {
+        feature: name.to_string(),
+        supported,
+        reason: reason.to_string(),
+    }
+}
+
+/// Map detected capabilities to the optional features Shrike can use.
+///
+/// `None` means rsync could not be run, so every feature is unsupported.
+pub fn supported_features(caps: Option<&RsyncCapabilities>) -> Vec<FeatureSupport> {
+    let Some(caps) = caps else {
+        return ["xattrs", "compress-level", "progress2", "link-dest"]
+            .iter()
+            .map(|name| feature(name, false, "rsync not found"))
+            .collect();
+    };
+
+    let openrsync = caps.flavor == RsyncFlavor::Openrsync;
+    vec![
+        if caps.xattrs {
+            feature("xattrs", true, "rsync was built with xattr support")
+        } else {
+            feature("xattrs", false, "rsync was built without xattr support")
+        },
+        if caps.gnu_at_least((2, 6, 0)) {
+            feature("compress-level", true, "supported by GNU rsync")
+        } else if openrsync {
+            feature("compress-level", false, "not supported by openrsync")
+        } else {
+            feature("compress-level", false, "requires GNU rsync 2.6.0 or newer")
+        },
+        if caps.supports_progress2() {
+            feature("progress2", true, "supported by rsync 3.1.0+")
+        } else {
+            feature("progress2", false, "requires GNU rsync 3.1.0 or newer")
+        },
+        if caps.gnu_at_least((2, 6, 0)) || openrsync {
+            feature("link-dest", true, "supported")
+        } else {
+            feature("link-dest", false, "requires rsync 2.6.0 or newer")
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GNU_3_2_7: &str = "\
+rsync  version 3.2.7  protocol version 31
+Copyright (C) 1996-2022 by Andrew Tridgell, Wayne Davison, and others.
+Web site: https://rsync.samba.org/
+Capabilities:
+    64-bit files, 64-bit inums, 64-bit timestamps, 64-bit long ints,
+    socketpairs, symlinks, symtimes, hardlinks, hardlink-specials,
+    hardlink-symlinks, IPv6, atimes, batchfiles, inplace, append, ACLs,
+    xattrs, optional secluded-args, iconv, prealloc, stop-at, no crtimes
+";
+
+    const APPLE_2_6_9: &str = "\
+rsync  version 2.6.9  protocol version 29
+Copyright (C) 1996-2006 by Andrew Tridgell, Wayne Davison, and others.
+<http://rsync.samba.org/>
+Capabilities: 64-bit files, socketpairs, hard links, symlinks, batchfiles,
+              inplace, IPv6, 64-bit system inums, 64-bit internal inums
+";
+
+    const OPENRSYNC: &str = "\
+openrsync: protocol version 29
+rsync version 2.6.9 compatible
+";
+
+    #[test]
+    fn parse_gnu_rsync() {
+        let caps = parse_rsync_version(GNU_3_2_7);
+        assert_eq!(caps.flavor, RsyncFlavor::Gnu);
+        assert_eq!(caps.version, Some((3, 2, 7)));
+        assert_eq!(caps.protocol, Some(31));
+        assert!(caps.xattrs);
+    }
+
+    #[test]
+    fn parse_apple_rsync() {
+        let caps = parse_rsync_version(APPLE_2_6_9);
+        assert_eq!(caps.flavor, RsyncFlavor::Gnu);
+        assert_eq!(caps.version, Some((2, 6, 9)));
+        assert_eq!(caps.protocol, Some(29));
+        assert!(!caps.xattrs);
+    }
+
+    #[test]
+    fn parse_openrsync() {
+        let caps = parse_rsync_version(OPENRSYNC);
+        assert_eq!(caps.flavor, RsyncFlavor::Openrsync);
+        assert_eq!(caps.version, Some((2, 6, 9)));
+        assert_eq!(caps.protocol, Some(29));
+        assert!(!caps.xattrs);
+    }
+
+    #[test]
+    fn parse_unknown_output() {
+        let caps = parse_rsync_version("something else");
+        assert_eq!(caps.flavor, RsyncFlavor::Unknown);
+        assert_eq!(caps.version, None);
+    }
+
+    #[test]
+    fn version_string_formats() {
+        let caps = parse_rsync_version(GNU_3_2_7);
+        assert_eq!(caps.version_string(), "rsync 3.2.7 (protocol 31)");
+    }
+
+    #[test]
+    fn supported_features_gnu_modern() {
+        let features = supported_features(Some(&parse_rsync_version(GNU_3_2_7)));
+        let names: Vec<&str> = features.iter().map(|f| f.feature.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["xattrs", "compress-level", "progress2", "link-dest"]
+        );
+        assert!(features.iter().all(|f| f.supported));
+    }
+
+    #[test]
+    fn supported_features_apple_rsync() {
+        let features = supported_features(Some(&parse_rsync_version(APPLE_2_6_9)));
+        let support: Vec<bool> = features.iter().map(|f| f.supported).collect();
+        // xattrs: no, compress-level: yes, progress2: no, link-dest: yes
+        assert_eq!(support, vec![false, true, false, true]);
+        assert!(features[2].reason.contains("3.1.0"));
+    }
+
+    #[test]
+    fn supported_features_openrsync() {
+        let features = supported_features(Some(&parse_rsync_version(OPENRSYNC)));
+        let support: Vec<bool> = features.iter().map(|f| f.supported).collect();
+        assert_eq!(support, vec![false, false, false, true]);
+        assert!(features[1].reason.contains("openrsync"));
+    }
+
+    #[test]
+    fn supported_features_without_rsync() {
+        let features = supported_features(None);
+        assert_eq!(features.len(), 4);
+        assert!(features.iter().all(|f| !f.supported));
+        assert!(features.iter().all(|f| f.reason == "rsync not found"));
+    }
+
+    #[test]
+    fn version_info_includes_crate_version() {
+        let info = version_info("/nonexistent/rsync-abc123");
+        assert_eq!(info.shrike_version, env!("CARGO_PKG_VERSION"));
+        assert!(info.summary.contains(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn version_info_reports_missing_rsync() {
+        let info = version_info("/nonexistent/rsync-abc123");
+        assert_eq!(info.rsync_version, "not found");
+        assert_eq!(info.rsync_path, None);
+        assert!(!info.rsync_is_system);
+        assert!(!info.os_version.is_empty());
+    }
+
+    #[test]
+    fn check_executable_requires_exec_permission() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("rsync");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        let script = script.to_str().unwrap();
+
+        let err = check_executable(script).unwrap_err();
+        assert!(err.to_string().contains("not executable"));
+
+        std::fs::set_permissions(script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(check_executable(script).is_ok());
+
+        let missing = check_executable("/nonexistent/rsync").unwrap_err();
+        assert!(missing.to_string().contains("not found"));
+    }
+
+    #[test]
+    fn resolve_binary_searches_path() {
+        assert!(resolve_binary("sh").is_some());
+        assert!(resolve_binary("shrike-no-such-binary-abc123").is_none());
+    }
+
+    #[test]
+    fn rsync_capabilities_missing_binary() {
+        assert!(rsync_capabilities("/nonexistent/rsync-abc123").is_none());
+    }
+}
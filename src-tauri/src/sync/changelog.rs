@@ -0,0 +1,142 @@
+//! Human-readable sync log kept next to the backup.
+//!
+//! Every sync appends one line to `<destination>/shrike-backup.log`, so the
+//! backup carries its own plain-text record that can be read in any editor.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::error::Result;
+use crate::types::SyncResult;
+
+/// File name of the sync log inside the destination directory.
+pub const CHANGELOG_FILE: &str = "shrike-backup.log";
+
+/// Format the log line for one sync (without the trailing newline).
+///
+/// Successful syncs: `<timestamp>  files=<n> dirs=<n> bytes=<n>  ok`;
+/// failed syncs: `<timestamp>  failed: <error>`.
+pub fn format_summary_line(at: DateTime<Utc>, outcome: &Result<SyncResult>) -> String {
+    let timestamp = at.to_rfc3339_opts(SecondsFormat::Secs, true);
+    match outcome {
+        Ok(result) => format!(
+            "{timestamp}  files={} dirs={} bytes={}  ok",
+            result.files_transferred, result.dirs_transferred, result.bytes_transferred
+        ),
+        // Keep multi-line rsync stderr on a single log line
+        Err(e) => format!(
+            "{timestamp}  failed: {}",
+            e.to_string()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+    }
+}
+
+/// Append `line` to the sync log in `destination`, creating the log if needed.
+pub fn append_summary(destination: &Path, line: &str) -> Result<PathBuf> {
+    let path = destination.join(CHANGELOG_FILE);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{line}")?;
+    Ok(path)
+}
+
+/// How much of the end of the log `last_summary` reads.
+const TAIL_BYTES: u64 = 4096;
+
+/// The most recent line of the sync log in `destination`, if any.
+///
+/// Only the tail of the file is read, so this stays cheap as the log grows.
+pub fn last_summary(destination: &Path) -> Option<String> {
+    let mut file = File::open(destination.join(CHANGELOG_FILE)).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ShrikeError;
+    use chrono::TimeZone;
+
+    fn result(files: u64, bytes: u64) -> SyncResult {
+        SyncResult {
+            files_transferred: files,
+            dirs_transferred: 1,
+            bytes_transferred: bytes,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
+        }
+    }
+
+    fn at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn format_summary_line_success() {
+        assert_eq!(
+            format_summary_line(at(), &Ok(result(3, 4096))),
+            "2026-03-01T09:30:00Z  files=3 dirs=1 bytes=4096  ok"
+        );
+    }
+
+    #[test]
+    fn format_summary_line_failure_is_single_line() {
+        let err = ShrikeError::RsyncError {
+            code: 23,
+            message: "rsync: link_stat failed\nrsync error: some files".to_string(),
+        };
+        let line = format_summary_line(at(), &Err(err));
+        assert!(line.starts_with("2026-03-01T09:30:00Z  failed: "));
+        assert!(line.contains("link_stat failed rsync error"));
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn append_summary_creates_then_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = format_summary_line(at(), &Ok(result(3, 4096)));
+        let second = format_summary_line(at(), &Ok(result(0, 0)));
+
+        let path = append_summary(dir.path(), &first).unwrap();
+        append_summary(dir.path(), &second).unwrap();
+
+        assert_eq!(path, dir.path().join(CHANGELOG_FILE));
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines, vec![first.as_str(), second.as_str()]);
+        assert_eq!(lines[1], "2026-03-01T09:30:00Z  files=0 dirs=1 bytes=0  ok");
+    }
+
+    #[test]
+    fn last_summary_returns_newest_line() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(last_summary(dir.path()), None);
+
+        append_summary(dir.path(), "first").unwrap();
+        append_summary(dir.path(), "second").unwrap();
+        assert_eq!(last_summary(dir.path()).as_deref(), Some("second"));
+    }
+}
@@ -0,0 +1,590 @@
+//! Encrypted archive backend, used instead of rsync when `encrypt` is set.
+//!
+//! The entries are written as a single tar stream encrypted with
+//! [age](https://age-encryption.org) to the configured X25519 recipient,
+//! so the destination only ever holds ciphertext. Archive members are
+//! filtered and named as rsync would write them under the destination: with
+//! the default `Full` layout that is the absolute source path without the
+//! leading `/`. A restore unpacks them into a staging directory, which
+//! then restores exactly like a plain backup at the destination.
+//!
+//! Only the public recipient key is stored in settings; the identity
+//! (secret key) is supplied by the user at restore time.
+
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::Utc;
+
+use super::{SyncPass, filter, layout};
+use crate::error::{Result, ShrikeError};
+use crate::types::{AppSettings, BackupEntry, PathLayout, SyncResult};
+
+/// File name of the encrypted archive inside the destination directory.
+pub const ARCHIVE_FILE: &str = "shrike-backup.tar.age";
+
+/// Parse an age X25519 recipient (`age1...`).
+pub fn parse_recipient(recipient: &str) -> Result<age::x25519::Recipient> {
+    age::x25519::Recipient::from_str(recipient.trim())
+        .map_err(|e| ShrikeError::InvalidSettings(format!("invalid encryption recipient: {e}")))
+}
+
+/// Parse an age X25519 identity (`AGE-SECRET-KEY-1...`).
+pub fn parse_identity(identity: &str) -> Result<age::x25519::Identity> {
+    age::x25519::Identity::from_str(identity.trim())
+        .map_err(|e| ShrikeError::SyncFailed(format!("invalid decryption identity: {e}")))
+}
+
+/// Counts of items added to an archive.
+#[derive(Debug, Default)]
+struct ArchiveCounts {
+    files: u64,
+    dirs: u64,
+}
+
+/// The filter rules rsync applies to one pass, restated for the tar walker.
+struct PassFilter<'a> {
+    /// Directory the pass is transferred from (`None` is `/`); anchored
+    /// patterns match paths rebased onto it.
+    root: Option<&'a Path>,
+    /// Protected and per-entry excludes, followed by the global ones.
+    excludes: Vec<String>,
+    /// Per-entry includes. When non-empty only matching files are kept and
+    /// directories left empty are pruned, as with `--prune-empty-dirs`.
+    includes: &'a [String],
+    /// Layout archive members are named by.
+    layout: PathLayout,
+}
+
+impl PassFilter<'_> {
+    /// Whether rsync would leave `path` out of this pass.
+    fn skips(&self, path: &Path, is_dir: bool) -> bool {
+        let rebased = match self.root {
+            Some(root) => PathBuf::from(layout::rebase(&path.to_string_lossy(), root)),
+            None => path.to_path_buf(),
+        };
+        if filter::is_excluded(&rebased, is_dir, &self.excludes) {
+            return true;
+        }
+        // `--include=*/` keeps every directory; files must match an include
+        !is_dir && !self.includes.is_empty() && !filter::is_excluded(&rebased, false, self.includes)
+    }
+
+    /// Archive member name for `path`, laid out as rsync would write it
+    /// under the destination.
+    fn member_name(&self, path: &Path) -> PathBuf {
+        layout::backup_path_for(&path.to_string_lossy(), "", self.layout)
+    }
+}
+
+/// Write the directories in `pending` (outermost first) to the archive.
+fn flush_dirs<W: Write>(
+    builder: &mut tar::Builder<W>,
+    pending: &mut Vec<PathBuf>,
+    filter: &PassFilter<'_>,
+    counts: &mut ArchiveCounts,
+) -> io::Result<()> {
+    for dir in pending.drain(..) {
+        let name = filter.member_name(&dir);
+        // The transfer root itself maps onto the destination
+        if !name.as_os_str().is_empty() {
+            builder.append_dir(name, &dir)?;
+        }
+        counts.dirs += 1;
+    }
+    Ok(())
+}
+
+/// Append `path` (and, for directories, everything below it), skipping
+/// whatever `filter` says rsync would skip. Directories are held in
+/// `pending` until something below them is kept when includes are set.
+/// Symlinks are stored as links, not followed.
+fn append_tree<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &Path,
+    filter: &PassFilter<'_>,
+    pending: &mut Vec<PathBuf>,
+    counts: &mut ArchiveCounts,
+) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(path)?.file_type();
+    if filter.skips(path, file_type.is_dir()) {
+        return Ok(());
+    }
+    if file_type.is_dir() {
+        pending.push(path.to_path_buf());
+        if filter.includes.is_empty() {
+            flush_dirs(builder, pending, filter, counts)?;
+        }
+        let mut children: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<io::Result<_>>()?;
+        children.sort();
+        for child in children {
+            append_tree(builder, &child, filter, pending, counts)?;
+        }
+        // Nothing below it was kept: prune it
+        if pending.last().is_some_and(|dir| dir == path) {
+            pending.pop();
+        }
+    } else {
+        flush_dirs(builder, pending, filter, counts)?;
+        builder.append_path_with_name(path, filter.member_name(path))?;
+        counts.files += 1;
+    }
+    Ok(())
+}
+
+/// The source path of a pass entry that `plan_passes` rebased onto `root`.
+fn source_path(rebased: &str, root: Option<&Path>) -> PathBuf {
+    match root {
+        Some(root) if rebased == "/." => root.to_path_buf(),
+        Some(root) => root.join(rebased.trim_start_matches('/')),
+        None => PathBuf::from(rebased),
+    }
+}
+
+/// Write a tar stream of every existing entry in `passes` to `output`,
+/// filtered exactly as the rsync backend would filter them.
+fn write_tar<W: Write>(
+    passes: &[SyncPass],
+    settings: &AppSettings,
+    output: W,
+) -> io::Result<(W, ArchiveCounts)> {
+    let mut builder = tar::Builder::new(output);
+    builder.follow_symlinks(false);
+
+    let mut counts = ArchiveCounts::default();
+    for pass in passes {
+        let root = pass.options.source_root.as_deref().map(Path::new);
+        let mut excludes = pass.options.excludes.clone();
+        excludes.extend(
+            settings
+                .global_excludes
+                .iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .map(String::from),
+        );
+        let filter = PassFilter {
+            root,
+            excludes,
+            includes: &pass.options.includes,
+            layout: settings.path_layout,
+        };
+        for entry in &pass.entries {
+            let path = source_path(&entry.path, root);
+            if path.symlink_metadata().is_ok() {
+                append_tree(&mut builder, &path, &filter, &mut Vec::new(), &mut counts)?;
+            }
+        }
+    }
+    Ok((builder.into_inner()?, counts))
+}
+
+/// A writer that discards its input and counts the bytes.
+#[derive(Default)]
+struct CountingSink(u64);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Report what `write_encrypted_archive` would archive without writing
+/// anything. `bytes_transferred` is the plaintext tar size.
+pub fn preview_archive(entries: &[BackupEntry], settings: &AppSettings) -> Result<SyncResult> {
+    let passes = super::plan_passes(entries, settings, &settings.destination_path()?);
+    let (sink, counts) = write_tar(&passes, settings, CountingSink::default())?;
+    Ok(SyncResult {
+        files_transferred: counts.files,
+        dirs_transferred: counts.dirs,
+        bytes_transferred: sink.0,
+        created: 0,
+        updated: 0,
+        deleted: 0,
+        unchanged: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        synced_at: Utc::now(),
+        warnings: vec![],
+        skipped_paths: vec![],
+        truncated: false,
+    })
+}
+
+/// Write an encrypted archive of `entries` to `<destination>/shrike-backup.tar.age`.
+///
+/// The archive holds what an rsync sync with `settings` would send: the
+/// same passes from `plan_passes`, with protected, per-entry and global
+/// excludes and per-entry includes applied, and members named by
+/// `settings.path_layout`. Entries whose path no longer exists are skipped,
+/// mirroring rsync's partial-transfer behavior. The archive is written to
+/// a temporary file in the destination and renamed into place, so an
+/// interrupted sync never leaves a truncated archive behind.
+pub fn write_encrypted_archive(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    destination: &Path,
+    recipient: &str,
+) -> Result<SyncResult> {
+    let recipient = parse_recipient(recipient)?;
+    let encryptor =
+        age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))
+            .map_err(|e| ShrikeError::SyncFailed(format!("encryption failed: {e}")))?;
+
+    let passes = super::plan_passes(entries, settings, &destination.to_string_lossy());
+    let temp = tempfile::NamedTempFile::new_in(destination)?;
+    let output = encryptor.wrap_output(BufWriter::new(temp.as_file()))?;
+    let (output, counts) = write_tar(&passes, settings, output)?;
+    output.finish()?.flush()?;
+
+    let archive = destination.join(ARCHIVE_FILE);
+    temp.persist(&archive)
+        .map_err(|e| ShrikeError::IoError(e.error))?;
+
+    Ok(SyncResult {
+        files_transferred: counts.files,
+        dirs_transferred: counts.dirs,
+        bytes_transferred: fs::metadata(&archive)?.len(),
+        created: 0,
+        updated: 0,
+        deleted: 0,
+        unchanged: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        synced_at: Utc::now(),
+        warnings: vec![],
+        skipped_paths: vec![],
+        truncated: false,
+    })
+}
+
+/// Decrypt `archive` with `identity` and unpack the members backing up
+/// `entries` under `target_root`, laid out as under the destination.
+///
+/// Members outside every entry's backup path are skipped: age doesn't
+/// authenticate the sender, so anyone who can write to the destination can
+/// plant an archive. Callers unpack into a staging directory and restore
+/// from there. Returns the number of archive members that were unpacked.
+pub fn restore_encrypted_archive(
+    archive: &Path,
+    identity: &str,
+    entries: &[BackupEntry],
+    layout: PathLayout,
+    target_root: &Path,
+) -> Result<u64> {
+    if !archive.is_file() {
+        return Err(ShrikeError::PathNotFound(
+            archive.to_string_lossy().to_string(),
+        ));
+    }
+    let identity = parse_identity(identity)?;
+    let decrypt_failed =
+        |e: age::DecryptError| ShrikeError::SyncFailed(format!("decryption failed: {e}"));
+    let reader = age::Decryptor::new(io::BufReader::new(fs::File::open(archive)?))
+        .map_err(decrypt_failed)?
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(decrypt_failed)?;
+
+    let tracked: Vec<PathBuf> = entries
+        .iter()
+        .map(|e| layout::backup_path_for(&e.path, "", layout))
+        .collect();
+    let mut unpacked = 0u64;
+    let mut tar = tar::Archive::new(reader);
+    tar.set_preserve_permissions(true);
+    tar.set_overwrite(true);
+    for member in tar.entries()? {
+        let mut member = member?;
+        let name = member.path()?.into_owned();
+        if !tracked.iter().any(|t| name.starts_with(t)) {
+            continue;
+        }
+        // `unpack_in` rejects absolute paths and `..` components
+        if member.unpack_in(target_root)? {
+            unpacked += 1;
+        }
+    }
+    Ok(unpacked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ItemType;
+    use age::secrecy::ExposeSecret;
+
+    /// Names of the members an archive of `entries` would hold.
+    fn member_names(entries: &[BackupEntry], settings: &AppSettings) -> Vec<String> {
+        let destination = settings.destination_path().unwrap();
+        let passes = crate::sync::plan_passes(entries, settings, &destination);
+        let (tar, _) = write_tar(&passes, settings, Vec::new()).unwrap();
+        tar::Archive::new(tar.as_slice())
+            .entries()
+            .unwrap()
+            .map(|m| m.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn keypair() -> (String, String) {
+        let identity = age::x25519::Identity::generate();
+        (
+            identity.to_public().to_string(),
+            identity.to_string().expose_secret().to_string(),
+        )
+    }
+
+    #[test]
+    fn encrypt_then_decrypt_round_trips_content() {
+        let source = tempfile::tempdir().unwrap();
+        let source_root = fs::canonicalize(source.path()).unwrap();
+        fs::create_dir_all(source_root.join("claude/agents")).unwrap();
+        fs::write(source_root.join("claude/settings.json"), "{\"a\":1}").unwrap();
+        fs::write(source_root.join("claude/agents/reviewer.md"), "be strict").unwrap();
+        fs::write(source_root.join(".zshrc"), "export A=1").unwrap();
+
+        let entries = vec![
+            BackupEntry::new(
+                source_root.join("claude").to_string_lossy().into(),
+                ItemType::Directory,
+            ),
+            BackupEntry::new(
+                source_root.join(".zshrc").to_string_lossy().into(),
+                ItemType::File,
+            ),
+        ];
+        let dest = tempfile::tempdir().unwrap();
+        let (recipient, identity) = keypair();
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let result = write_encrypted_archive(&entries, &settings, dest.path(), &recipient).unwrap();
+        assert!(result.is_success());
+        assert_eq!(result.files_transferred, 3);
+        assert_eq!(result.dirs_transferred, 2);
+
+        // Only ciphertext reaches the destination
+        let archive = dest.path().join(ARCHIVE_FILE);
+        let ciphertext = fs::read(&archive).unwrap();
+        assert!(!ciphertext.windows(9).any(|w| w == b"be strict"));
+        assert_eq!(fs::read_dir(dest.path()).unwrap().count(), 1);
+
+        let restored = tempfile::tempdir().unwrap();
+        let unpacked = restore_encrypted_archive(
+            &archive,
+            &identity,
+            &entries,
+            PathLayout::Full,
+            restored.path(),
+        )
+        .unwrap();
+        assert_eq!(unpacked, 5);
+
+        let mirror = restored.path().join(source_root.strip_prefix("/").unwrap());
+        assert_eq!(
+            fs::read_to_string(mirror.join("claude/settings.json")).unwrap(),
+            "{\"a\":1}"
+        );
+        assert_eq!(
+            fs::read_to_string(mirror.join("claude/agents/reviewer.md")).unwrap(),
+            "be strict"
+        );
+        assert_eq!(
+            fs::read_to_string(mirror.join(".zshrc")).unwrap(),
+            "export A=1"
+        );
+    }
+
+    #[test]
+    fn restore_unpacks_only_members_of_the_given_entries() {
+        let source = tempfile::tempdir().unwrap();
+        let source_root = fs::canonicalize(source.path()).unwrap();
+        fs::write(source_root.join(".zshrc"), "export A=1").unwrap();
+        fs::write(source_root.join("authorized_keys"), "ssh-ed25519 AAAA").unwrap();
+        let zshrc = BackupEntry::new(
+            source_root.join(".zshrc").to_string_lossy().into(),
+            ItemType::File,
+        );
+        let keys = BackupEntry::new(
+            source_root.join("authorized_keys").to_string_lossy().into(),
+            ItemType::File,
+        );
+        let dest = tempfile::tempdir().unwrap();
+        let (recipient, identity) = keypair();
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        write_encrypted_archive(&[zshrc.clone(), keys], &settings, dest.path(), &recipient)
+            .unwrap();
+
+        let restored = tempfile::tempdir().unwrap();
+        let unpacked = restore_encrypted_archive(
+            &dest.path().join(ARCHIVE_FILE),
+            &identity,
+            &[zshrc],
+            PathLayout::Full,
+            restored.path(),
+        )
+        .unwrap();
+        assert_eq!(unpacked, 1);
+        let mirror = restored.path().join(source_root.strip_prefix("/").unwrap());
+        assert!(mirror.join(".zshrc").is_file());
+        assert!(!mirror.join("authorized_keys").exists());
+    }
+
+    #[test]
+    fn preview_archive_counts_without_writing() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir(source.path().join("sub")).unwrap();
+        fs::write(source.path().join("sub/a.txt"), "abc").unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_string_lossy().into(),
+            ItemType::Directory,
+        )];
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let result = preview_archive(&entries, &settings).unwrap();
+        assert_eq!(result.files_transferred, 1);
+        assert_eq!(result.dirs_transferred, 2);
+        assert!(result.bytes_transferred > 0);
+    }
+
+    #[test]
+    fn archive_honors_entry_excludes() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir_all(source.path().join("node_modules/pkg")).unwrap();
+        fs::write(source.path().join("node_modules/pkg/index.js"), "").unwrap();
+        fs::write(source.path().join("keep.md"), "keep").unwrap();
+        let mut entry =
+            BackupEntry::new(source.path().to_string_lossy().into(), ItemType::Directory);
+        entry.excludes = vec!["node_modules/".into()];
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let result = preview_archive(&[entry], &settings).unwrap();
+        assert_eq!(result.files_transferred, 1);
+        assert_eq!(result.dirs_transferred, 1);
+    }
+
+    #[test]
+    fn archive_skips_disabled_entries_destination_and_global_excludes() {
+        let source = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(source.path()).unwrap();
+        fs::create_dir_all(root.join("home/notes")).unwrap();
+        fs::write(root.join("home/notes/todo.md"), "").unwrap();
+        fs::write(root.join("home/notes/.DS_Store"), "").unwrap();
+        fs::create_dir(root.join("secret")).unwrap();
+        fs::write(root.join("secret/key"), "").unwrap();
+
+        // The destination lives inside the backed-up directory
+        let mut settings = AppSettings::for_test(root.join("home/gdrive").to_str().unwrap());
+        settings.global_excludes = vec![".DS_Store".into()];
+        let destination = settings.destination_path().unwrap();
+        fs::create_dir_all(&destination).unwrap();
+        fs::write(Path::new(&destination).join("old.txt"), "").unwrap();
+
+        let mut disabled = BackupEntry::new(
+            root.join("secret").to_string_lossy().into(),
+            ItemType::Directory,
+        );
+        disabled.enabled = false;
+        let entries = vec![
+            BackupEntry::new(
+                root.join("home").to_string_lossy().into(),
+                ItemType::Directory,
+            ),
+            disabled,
+        ];
+
+        let names = member_names(&entries, &settings);
+        let home = root.join("home");
+        let home = home.strip_prefix("/").unwrap().to_string_lossy();
+        assert!(
+            names.contains(&format!("{home}/notes/todo.md")),
+            "{names:?}"
+        );
+        assert!(!names.iter().any(|n| n.contains("secret")), "{names:?}");
+        assert!(!names.iter().any(|n| n.contains("TestMac")), "{names:?}");
+        assert!(!names.iter().any(|n| n.ends_with(".DS_Store")), "{names:?}");
+    }
+
+    #[test]
+    fn archive_keeps_only_included_files_and_prunes_empty_dirs() {
+        let source = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(source.path()).unwrap();
+        fs::create_dir_all(root.join("docs/drafts")).unwrap();
+        fs::create_dir(root.join("build")).unwrap();
+        fs::write(root.join("docs/readme.md"), "").unwrap();
+        fs::write(root.join("docs/drafts/idea.md"), "").unwrap();
+        fs::write(root.join("docs/logo.png"), "").unwrap();
+        fs::write(root.join("build/out.o"), "").unwrap();
+        let mut entry = BackupEntry::new(root.to_string_lossy().into(), ItemType::Directory);
+        entry.includes = vec!["*.md".into()];
+
+        let names = member_names(&[entry], &AppSettings::for_test("/tmp/test_gdrive"));
+        assert_eq!(
+            names.iter().filter(|n| n.ends_with(".md")).count(),
+            2,
+            "{names:?}"
+        );
+        assert!(!names.iter().any(|n| n.ends_with(".png")), "{names:?}");
+        assert!(!names.iter().any(|n| n.contains("build")), "{names:?}");
+    }
+
+    #[test]
+    fn decrypt_with_wrong_identity_fails() {
+        let source = tempfile::NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_string_lossy().into(),
+            ItemType::File,
+        )];
+        let dest = tempfile::tempdir().unwrap();
+        let (recipient, _) = keypair();
+        let (_, other_identity) = keypair();
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        write_encrypted_archive(&entries, &settings, dest.path(), &recipient).unwrap();
+        let err = restore_encrypted_archive(
+            &dest.path().join(ARCHIVE_FILE),
+            &other_identity,
+            &entries,
+            PathLayout::Full,
+            dest.path(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("decryption failed"), "{err}");
+    }
+
+    #[test]
+    fn parse_recipient_rejects_garbage() {
+        assert!(matches!(
+            parse_recipient("not-a-key").unwrap_err(),
+            ShrikeError::InvalidSettings(_)
+        ));
+        let (recipient, identity) = keypair();
+        assert!(parse_recipient(&recipient).is_ok());
+        // A secret key is not a recipient
+        assert!(parse_recipient(&identity).is_err());
+    }
+
+    #[test]
+    fn restore_missing_archive_is_path_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, identity) = keypair();
+        let err = restore_encrypted_archive(
+            &dir.path().join(ARCHIVE_FILE),
+            &identity,
+            &[],
+            PathLayout::Full,
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ShrikeError::PathNotFound(_)));
+    }
+}
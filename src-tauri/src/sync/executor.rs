@@ -3,50 +3,311 @@
 //! Builds rsync command arguments, executes the rsync process, and parses
 //! its output into a structured `SyncResult`.
 
-use std::process::Command;
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 use crate::error::{Result, ShrikeError};
-use crate::types::SyncResult;
+use crate::types::{SyncProgress, SyncResult};
+
+/// Optional rsync behavior layered on top of the base `-avrR` invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RsyncOptions {
+    /// Compress file data during transfer (`-z`)
+    pub compress: bool,
+    /// zlib level used when compressing (`--compress-level=N`)
+    pub compress_level: Option<u8>,
+    /// Decide what to transfer by content checksum instead of size and
+    /// mtime (`--checksum`); slower, since every file is read in full
+    pub checksum: bool,
+    /// Copy what symlinks point to (`-L`) instead of the links themselves
+    pub follow_symlinks: bool,
+    /// Delete destination files missing from, or excluded in, the source
+    /// (`--delete --delete-excluded`). With `--files-from`, rsync only
+    /// deletes inside directories named in the filelist, so other entries'
+    /// backups and paths no longer tracked are left alone; a pass whose
+    /// filelist names the source root itself needs `protects` for that.
+    pub delete: bool,
+    /// Destination paths shielded from `delete`, passed as
+    /// `--filter=P <pattern>` before the excludes
+    pub protects: Vec<String>,
+    /// Patterns passed as `--exclude=<pattern>`, in order
+    pub excludes: Vec<String>,
+    /// File of further exclude patterns, passed as `--exclude-from=<file>`
+    pub exclude_from: Option<String>,
+    /// Allowlist patterns, passed as `--include=<pattern>` after all the
+    /// excludes and followed by `--exclude=*`, so only matching files are
+    /// sent. rsync stops at the first matching rule and would otherwise
+    /// exclude directories before descending into them, so `--include=*/`
+    /// goes first; `--prune-empty-dirs` then drops directories left
+    /// without a matching file. Empty means no allowlist.
+    pub includes: Vec<String>,
+    /// Only report what would be transferred (`--dry-run`)
+    pub dry_run: bool,
+    /// Itemize each changed file (`--itemize-changes`) so the result can
+    /// tell created, updated and deleted files apart
+    pub itemize_changes: bool,
+    /// Itemize every file, changed or not (`--itemize-changes` twice)
+    pub itemize: bool,
+    /// Report overall transfer progress (`--info=progress2`, rsync 3.1.0+)
+    pub progress: bool,
+    /// Directory the filelist paths are transferred from, and so the root
+    /// `-R` reproduces them under; `None` is `/`
+    pub source_root: Option<String>,
+}
 
 /// Build the rsync command arguments.
 ///
-/// Command: `rsync -avrR --files-from=<tmpfile> / <destination>/`
+/// Command: `rsync -avrR --files-from=<tmpfile> [options] <source_root>/ <destination>/`,
+/// where the source root is `/` unless `options.source_root` says otherwise.
 ///
 /// The explicit `-r` is required because `--files-from` disables the implicit
 /// recursion that `-a` normally provides. Without it, directory entries in the
 /// filelist are created as empty directories without their contents.
-pub fn build_rsync_args(files_from_path: &str, destination: &str) -> Vec<String> {
-    vec![
-        "-avrR".to_string(),
-        format!("--files-from={files_from_path}"),
-        "/".to_string(),
-        format!("{destination}/"),
-    ]
+///
+/// Options are emitted between `--files-from` and the source so that the
+/// source and destination always remain the last two arguments.
+pub fn build_rsync_args(
+    files_from_path: &str,
+    destination: &str,
+    options: &RsyncOptions,
+) -> Vec<String> {
+    let mut args = vec!["-avrR".to_string()];
+    if options.compress {
+        args.push("-z".to_string());
+        if let Some(level) = options.compress_level {
+            args.push(format!("--compress-level={level}"));
+        }
+    }
+    if options.checksum {
+        args.push("--checksum".to_string());
+    }
+    if options.follow_symlinks {
+        args.push("-L".to_string());
+    }
+    if options.delete {
+        args.push("--delete".to_string());
+        args.push("--delete-excluded".to_string());
+    }
+    if options.dry_run {
+        args.push("--dry-run".to_string());
+    }
+    if options.itemize {
+        args.push("--itemize-changes".to_string());
+        args.push("--itemize-changes".to_string());
+    } else if options.itemize_changes {
+        args.push("--itemize-changes".to_string());
+    }
+    if options.progress {
+        args.push("--info=progress2".to_string());
+    }
+    args.push(format!("--files-from={files_from_path}"));
+    args.extend(options.protects.iter().map(|p| format!("--filter=P {p}")));
+    args.extend(options.excludes.iter().map(|p| format!("--exclude={p}")));
+    if let Some(file) = &options.exclude_from {
+        args.push(format!("--exclude-from={file}"));
+    }
+    if !options.includes.is_empty() {
+        args.push("--prune-empty-dirs".to_string());
+        args.push("--include=*/".to_string());
+        args.extend(options.includes.iter().map(|p| format!("--include={p}")));
+        args.push("--exclude=*".to_string());
+    }
+    let source_root = options.source_root.as_deref().unwrap_or("/");
+    args.push(format!("{}/", source_root.trim_end_matches('/')));
+    args.push(format!("{destination}/"));
+    args
+}
+
+/// Build the rsync command arguments for restoring from a backup.
+///
+/// Command: `rsync -avrR --no-implied-dirs [--dry-run] --files-from=<tmpfile> <backup_root>/ <target_root>/`
+///
+/// The filelist holds the paths as synced from `target_root` (the original
+/// absolute paths for `/`); rsync strips the leading `/` and resolves them
+/// relative to `backup_root`, the mirror of that layout. `--no-implied-dirs`
+/// keeps rsync from resetting the attributes of existing parent directories
+/// such as `/Users`.
+pub fn build_restore_args(
+    files_from_path: &str,
+    backup_root: &str,
+    target_root: &str,
+    dry_run: bool,
+) -> Vec<String> {
+    let mut args = vec!["-avrR".to_string(), "--no-implied-dirs".to_string()];
+    if dry_run {
+        args.push("--dry-run".to_string());
+    }
+    args.push(format!("--files-from={files_from_path}"));
+    args.push(format!("{backup_root}/"));
+    args.push(format!("{}/", target_root.trim_end_matches('/')));
+    args
+}
+
+/// How a backed-up file compares to its source, per a `--checksum` dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    /// Source files whose backup matches
+    pub in_sync: Vec<String>,
+    /// Source files whose backup differs in content or attributes
+    pub differing: Vec<String>,
+    /// Source files with no copy in the backup
+    pub missing_in_backup: Vec<String>,
+}
+
+impl VerifyReport {
+    /// Returns true if every file's backup matches its source.
+    pub fn is_in_sync(&self) -> bool {
+        self.differing.is_empty() && self.missing_in_backup.is_empty()
+    }
+
+    /// Append another pass's report.
+    pub fn extend(&mut self, other: VerifyReport) {
+        self.in_sync.extend(other.in_sync);
+        self.differing.extend(other.differing);
+        self.missing_in_backup.extend(other.missing_in_backup);
+    }
+}
+
+/// Where a file stands, from one line of `--itemize-changes` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemizedState {
+    InSync,
+    Differing,
+    Missing,
+}
+
+/// Parse one line of `--itemize-changes` output (`YXcstpoguax <path>`).
+///
+/// Returns the file's state and path, made absolute again since rsync
+/// prints `-R` paths relative to `/`. Directories, headers, summaries and
+/// `*deleting` messages return `None`.
+pub fn parse_itemized_line(line: &str) -> Option<(ItemizedState, String)> {
+    let (flags, path) = line.split_once(' ')?;
+    let mut chars = flags.chars();
+    let update = chars.next()?;
+    let file_type = chars.next()?;
+    if !matches!(update, '<' | '>' | 'c' | 'h' | '.') || !matches!(file_type, 'f' | 'L' | 'D' | 'S')
+    {
+        return None;
+    }
+
+    // Unchanged files print their attributes as spaces, so they end up in
+    // the padding before the path rather than in `flags`
+    let attributes = chars.as_str();
+    let mut path = path.trim_start();
+    if file_type == 'L' {
+        path = path.split_once(" -> ").map_or(path, |(link, _)| link);
+    }
+    if path.is_empty() {
+        return None;
+    }
+    let state = if !attributes.is_empty() && attributes.chars().all(|c| c == '+') {
+        ItemizedState::Missing
+    } else if update == '.' && attributes.chars().all(|c| c == ' ' || c == '.') {
+        ItemizedState::InSync
+    } else {
+        ItemizedState::Differing
+    };
+    Some((state, format!("/{}", path.trim_start_matches('/'))))
+}
+
+/// Classify every file in `--itemize-changes` output.
+pub fn parse_itemized_output(stdout: &str) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    for (state, path) in stdout.lines().filter_map(parse_itemized_line) {
+        match state {
+            ItemizedState::InSync => report.in_sync.push(path),
+            ItemizedState::Differing => report.differing.push(path),
+            ItemizedState::Missing => report.missing_in_backup.push(path),
+        }
+    }
+    report
+}
+
+/// Counts of changed files in one sync, from `--itemize-changes` output.
+///
+/// Directories are not counted, matching `files_transferred`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ItemizedChanges {
+    pub created: u64,
+    pub updated: u64,
+    pub deleted: u64,
+    pub unchanged: u64,
+}
+
+/// Count created, updated, deleted and unchanged files in
+/// `--itemize-changes` output. Plain `-v` output counts as no changes.
+pub fn parse_itemized_changes(stdout: &str) -> ItemizedChanges {
+    let mut changes = ItemizedChanges::default();
+    for line in stdout.lines() {
+        if let Some(path) = line.strip_prefix("*deleting") {
+            let path = path.trim();
+            if !path.is_empty() && !path.ends_with('/') {
+                changes.deleted += 1;
+            }
+            continue;
+        }
+        match parse_itemized_line(line) {
+            Some((ItemizedState::Missing, _)) => changes.created += 1,
+            Some((ItemizedState::Differing, _)) => changes.updated += 1,
+            Some((ItemizedState::InSync, _)) => changes.unchanged += 1,
+            None => {}
+        }
+    }
+    changes
+}
+
+/// Returns true if `flags` is the `YXcstpoguax` prefix of an itemized line.
+fn is_itemize_flags(flags: &str) -> bool {
+    let mut chars = flags.chars();
+    matches!(chars.next(), Some('<' | '>' | 'c' | 'h' | '.'))
+        && matches!(chars.next(), Some('f' | 'd' | 'L' | 'D' | 'S'))
+        && flags.len() <= 11
+        && chars.all(|c| c == '+' || c == '.' || c == '?' || c.is_ascii_lowercase() || c == 'T')
+}
+
+/// Returns the path named by a trimmed line of rsync `-v` or
+/// `--itemize-changes` output, or `None` for headers, summaries,
+/// `*deleting` messages and itemized items that were not transferred.
+fn item_path(trimmed: &str) -> Option<&str> {
+    if trimmed.is_empty()
+        || trimmed.starts_with("sending")
+        || trimmed.starts_with("sent ")
+        || trimmed.starts_with("total ")
+        || trimmed.starts_with("building ")
+        || trimmed.starts_with("*deleting")
+    {
+        return None;
+    }
+    let path = match trimmed.split_once(' ') {
+        // A `.` update means only attributes changed
+        Some((flags, _)) if is_itemize_flags(flags) && flags.starts_with('.') => return None,
+        Some((flags, path)) if is_itemize_flags(flags) => path.trim_start(),
+        _ => trimmed,
+    };
+    (path != "." && path != "./").then_some(path)
 }
 
 /// Count transferred files and directories from rsync verbose output.
 ///
 /// In rsync `-v` output, transferred items are listed one per line before the
-/// summary block. Directories end with `/` (e.g. `dir1/`), files do not.
+/// summary block, behind their `YXcstpoguax` codes with `--itemize-changes`.
+/// Directories end with `/` (e.g. `dir1/`), files do not.
 /// Returns `(files, dirs)` counts.
 pub fn count_transferred_items(stdout: &str) -> (u64, u64) {
     let mut files = 0u64;
     let mut dirs = 0u64;
     for line in stdout.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty()
-            || trimmed.starts_with("sending")
-            || trimmed.starts_with("sent ")
-            || trimmed.starts_with("total ")
-            || trimmed.starts_with("building ")
-            || trimmed == "."
-            || trimmed == "./"
-        {
+        let Some(path) = item_path(line.trim()) else {
             continue;
-        }
-        if trimmed.ends_with('/') {
+        };
+        if path.ends_with('/') {
             dirs += 1;
         } else {
             files += 1;
@@ -55,37 +316,477 @@ pub fn count_transferred_items(stdout: &str) -> (u64, u64) {
     (files, dirs)
 }
 
+/// Parse a byte count from rsync's summary, e.g. `1234`, `1,234`, or
+/// `1.23K` (human-readable units).
+fn parse_byte_count(token: &str) -> Option<u64> {
+    let token = token.trim_end_matches(',');
+    let (number, multiplier) = match token.chars().last()? {
+        'K' | 'k' => (&token[..token.len() - 1], 1u64 << 10),
+        'M' | 'm' => (&token[..token.len() - 1], 1 << 20),
+        'G' | 'g' => (&token[..token.len() - 1], 1 << 30),
+        'T' | 't' => (&token[..token.len() - 1], 1 << 40),
+        _ => (token, 1),
+    };
+    if multiplier > 1 {
+        let value: f64 = number.replace(',', ".").parse().ok()?;
+        return Some((value * multiplier as f64).round() as u64);
+    }
+    let digits: String = number
+        .chars()
+        .filter(|c| !matches!(c, ',' | '.' | '\''))
+        .collect();
+    digits.parse().ok()
+}
+
+/// Extract the number of bytes sent from rsync's summary block.
+///
+/// Uses `sent N bytes` when present (GNU rsync and openrsync, which
+/// capitalizes it and separates fields with commas) and falls back to
+/// `total size is N` otherwise. Returns 0 when there is no summary.
+pub fn parse_bytes_transferred(stdout: &str) -> u64 {
+    let mut total_size = None;
+    for line in stdout.lines() {
+        let lowered = line.trim().to_lowercase();
+        let mut words = lowered.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("sent"), Some(n), _) => {
+                if let Some(bytes) = parse_byte_count(n) {
+                    return bytes;
+                }
+            }
+            (Some("total"), Some("size"), Some("is")) => {
+                total_size = words.next().and_then(parse_byte_count);
+            }
+            _ => {}
+        }
+    }
+    total_size.unwrap_or(0)
+}
+
+/// Parse an `--info=progress2` update such as
+/// `  1,234,567  45%   12.34MB/s    0:00:05 (xfr#3, to-chk=10/20)`.
+///
+/// Returns `(bytes, percent)`, or `None` for any other line.
+pub fn parse_progress_line(line: &str) -> Option<(u64, u8)> {
+    let mut words = line.split_whitespace();
+    let bytes = parse_byte_count(words.next()?)?;
+    let percent = words.next()?.strip_suffix('%')?.parse::<u8>().ok()?;
+    Some((bytes, percent.min(100)))
+}
+
+/// Read rsync stdout incrementally, calling `on_progress` as it goes, and
+/// return the output with progress updates removed.
+///
+/// rsync rewrites progress updates in place with `\r`, so both `\r` and
+/// `\n` end a segment. A progress update refreshes `percent`/`bytes`; every
+/// transferred item refreshes `current_file`. Either one triggers a call.
+/// The returned text is exactly what rsync printed minus the progress
+/// segments and their bare `\r` separators, so counting and summary
+/// parsing are unaffected.
+pub fn read_output<R: Read>(
+    reader: R,
+    on_progress: &mut dyn FnMut(&SyncProgress),
+) -> io::Result<String> {
+    let mut reader = BufReader::new(reader);
+    let mut output = Vec::new();
+    let mut segment = Vec::new();
+    let mut progress = SyncProgress {
+        percent: 0,
+        bytes: 0,
+        current_file: String::new(),
+    };
+    loop {
+        segment.clear();
+        let read = read_segment(&mut reader, &mut segment)?;
+        if read == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&segment);
+        let line = text.trim_end_matches(['\r', '\n']);
+        if segment == b"\r" {
+            continue;
+        }
+        if let Some((bytes, percent)) = parse_progress_line(line) {
+            progress.bytes = bytes;
+            progress.percent = percent;
+            on_progress(&progress);
+            continue;
+        }
+        output.extend_from_slice(&segment);
+        if let Some(path) = item_path(line.trim()).filter(|p| !p.ends_with('/')) {
+            progress.current_file = path.to_string();
+            on_progress(&progress);
+        }
+    }
+    Ok(String::from_utf8_lossy(&output).to_string())
+}
+
+/// Read up to and including the next `\r` or `\n` into `buf`.
+fn read_segment<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let mut read = 0;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(read);
+        }
+        match available.iter().position(|&b| b == b'\r' || b == b'\n') {
+            Some(i) => {
+                buf.extend_from_slice(&available[..=i]);
+                reader.consume(i + 1);
+                return Ok(read + i + 1);
+            }
+            None => {
+                let len = available.len();
+                buf.extend_from_slice(available);
+                reader.consume(len);
+                read += len;
+            }
+        }
+    }
+}
+
+/// Broad category of an rsync exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RsyncExitClass {
+    Success,
+    /// Bad command line (1)
+    Usage,
+    /// Protocol incompatibility or a corrupted data stream (2, 5, 12)
+    Protocol,
+    /// Source or destination could not be selected (3)
+    FileSelection,
+    /// Requested action not supported by this rsync (4)
+    Unsupported,
+    /// Socket I/O failed (10)
+    SocketIo,
+    /// Reading or writing a file failed (11)
+    FileIo,
+    /// rsync's own IPC, diagnostics, or memory allocation failed (13, 14, 21, 22)
+    Internal,
+    /// rsync was interrupted by a signal (20)
+    Interrupted,
+    /// Some files could not be transferred (23)
+    PartialTransfer,
+    /// Source files vanished during the transfer (24)
+    VanishedFiles,
+    /// `--max-delete` stopped deletions (25)
+    DeleteLimit,
+    /// Timed out sending, receiving, or connecting (30, 35)
+    Timeout,
+    /// Any other code
+    Unknown,
+}
+
+impl RsyncExitClass {
+    /// Returns true if the sync otherwise completed and the UI may show the
+    /// failure as a warning: files that vanished mid-transfer (such as
+    /// caches and lock files) simply no longer need backing up.
+    pub fn is_soft(&self) -> bool {
+        matches!(self, RsyncExitClass::VanishedFiles)
+    }
+}
+
+/// Non-zero rsync exit codes that still count as a successful sync.
+///
+/// 24 means source files vanished mid-transfer (editors rewriting temp
+/// files, caches being pruned); everything that still exists was copied.
+/// 23 stays a failure, since it means an existing file could not be read.
+pub const SOFT_EXIT_CODES: &[i32] = &[24];
+
+/// Returns true if `code` is listed in `SOFT_EXIT_CODES`.
+pub fn is_soft_exit_code(code: i32) -> bool {
+    SOFT_EXIT_CODES.contains(&code)
+}
+
+/// Classify an rsync exit code.
+pub fn classify_exit_code(code: i32) -> RsyncExitClass {
+    match code {
+        0 => RsyncExitClass::Success,
+        1 => RsyncExitClass::Usage,
+        2 | 5 | 12 => RsyncExitClass::Protocol,
+        3 => RsyncExitClass::FileSelection,
+        4 => RsyncExitClass::Unsupported,
+        10 => RsyncExitClass::SocketIo,
+        11 => RsyncExitClass::FileIo,
+        13 | 14 | 21 | 22 => RsyncExitClass::Internal,
+        20 => RsyncExitClass::Interrupted,
+        23 => RsyncExitClass::PartialTransfer,
+        24 => RsyncExitClass::VanishedFiles,
+        25 => RsyncExitClass::DeleteLimit,
+        30 | 35 => RsyncExitClass::Timeout,
+        _ => RsyncExitClass::Unknown,
+    }
+}
+
+/// Human-readable explanation of an rsync exit code, following the
+/// EXIT VALUES section of the rsync man page.
+pub fn explain_exit_code(code: i32) -> &'static str {
+    match code {
+        0 => "success",
+        1 => "syntax or usage error",
+        2 => "protocol incompatibility",
+        3 => "errors selecting input/output files or directories",
+        4 => "requested action not supported",
+        5 => "error starting client-server protocol",
+        10 => "error in socket I/O",
+        11 => "error in file I/O",
+        12 => "error in rsync protocol data stream",
+        13 => "errors with program diagnostics",
+        14 => "error in IPC code",
+        20 => "interrupted by a signal",
+        21 => "error returned by waitpid()",
+        22 => "error allocating memory buffers",
+        23 => "partial transfer due to errors",
+        24 => "partial transfer because source files vanished",
+        25 => "--max-delete limit stopped deletions",
+        30 => "timeout in data send/receive",
+        35 => "timeout waiting for daemon connection",
+        _ => "unknown error",
+    }
+}
+
+/// Substrings of rsync stderr lines that report a non-fatal problem.
+const WARNING_PATTERNS: &[&str] = &[
+    "skipping non-regular file",
+    "some files vanished",
+    "file has vanished",
+    "symlink has no referent",
+    "cannot delete non-empty directory",
+    "some files/attrs were not transferred",
+];
+
+/// Extract known non-fatal warnings from rsync stderr, one per line, in order.
+pub fn parse_warnings(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| WARNING_PATTERNS.iter().any(|p| line.contains(p)))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Process-level options for running rsync.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Umask for the rsync process; `None` inherits the current one
+    pub umask: Option<u32>,
+    /// rsync executable to run; `None` uses `rsync` from `PATH`
+    pub program: Option<String>,
+    /// Kill rsync once it has run this long; `None` waits indefinitely
+    pub timeout: Option<Duration>,
+    /// Keep only the last this many lines of stdout and stderr in the
+    /// result; `None` keeps all of it
+    pub max_output_lines: Option<usize>,
+}
+
+/// How often a running rsync is polled for exit while a timeout is armed.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Kill `child` if it is still running once `timeout` has passed, and
+/// record that in `timed_out`. Returns as soon as the child exits.
+fn watch_child(child: &Mutex<Child>, timeout: Duration, timed_out: &AtomicBool) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        {
+            let mut child = child.lock().unwrap_or_else(|e| e.into_inner());
+            if !matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                timed_out.store(true, Ordering::SeqCst);
+                return;
+            }
+        }
+        std::thread::sleep(
+            TIMEOUT_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+        );
+    }
+}
+
+/// Wait for `child` without holding its lock, so `watch_child` can still
+/// kill it.
+fn poll_wait(child: &Mutex<Child>) -> io::Result<ExitStatus> {
+    loop {
+        if let Some(status) = child.lock().unwrap_or_else(|e| e.into_inner()).try_wait()? {
+            return Ok(status);
+        }
+        std::thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
+/// Spawn `command` with its umask set to `mask`.
+///
+/// The mask is set in the forked child just before exec, so the umask of
+/// this process (shared by all its threads) never changes.
+#[cfg(unix)]
+fn spawn_with_umask(command: &mut Command, mask: u32) -> std::io::Result<Child> {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: umask is async-signal-safe and touches only the child's mask
+    unsafe {
+        command.pre_exec(move || {
+            libc::umask(mask as libc::mode_t);
+            Ok(())
+        });
+    }
+    command.spawn()
+}
+
+#[cfg(not(unix))]
+fn spawn_with_umask(command: &mut Command, _mask: u32) -> std::io::Result<Child> {
+    command.spawn()
+}
+
 /// Execute rsync with the given arguments and return a `SyncResult`.
 ///
 /// This function runs the actual rsync process. It is separated from argument
 /// building so that argument construction can be tested independently.
-pub fn run_rsync(args: &[String]) -> Result<SyncResult> {
-    let output = Command::new("rsync").args(args).output()?;
+pub fn run_rsync(args: &[String], options: &RunOptions) -> Result<SyncResult> {
+    run_rsync_with_progress(args, options, &mut |_| {})
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let exit_code = output.status.code().unwrap_or(-1);
+/// Like `run_rsync`, but streams stdout and calls `on_progress` for every
+/// progress update and transferred file while rsync is still running.
+///
+/// The returned `SyncResult` is the same as `run_rsync` would produce.
+pub fn run_rsync_with_progress(
+    args: &[String],
+    options: &RunOptions,
+    on_progress: &mut dyn FnMut(&SyncProgress),
+) -> Result<SyncResult> {
+    let program = options
+        .program
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .unwrap_or("rsync");
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = match options.umask {
+        Some(mask) => spawn_with_umask(&mut command, mask)?,
+        None => command.spawn()?,
+    };
 
-    let (files_transferred, dirs_transferred) = count_transferred_items(&stdout);
+    // Drain stderr on its own thread so a chatty rsync can't block on a
+    // full pipe while stdout is being streamed
+    let mut stderr_pipe = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let stdout_pipe = child.stdout.take();
 
-    let result = SyncResult {
-        files_transferred,
-        dirs_transferred,
-        bytes_transferred: 0,
-        stdout,
-        stderr,
-        exit_code,
-        synced_at: Utc::now(),
+    // Killing rsync closes its pipes, which unblocks the reads below
+    let child = Arc::new(Mutex::new(child));
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let watchdog = options.timeout.map(|timeout| {
+        let child = Arc::clone(&child);
+        let timed_out = Arc::clone(&timed_out);
+        std::thread::spawn(move || watch_child(&child, timeout, &timed_out))
+    });
+
+    let stdout = match stdout_pipe {
+        Some(pipe) => read_output(pipe, on_progress),
+        None => Ok(String::new()),
+    };
+    let status = match watchdog {
+        Some(watchdog) => {
+            let status = poll_wait(&child);
+            let _ = watchdog.join();
+            status?
+        }
+        None => child.lock().unwrap_or_else(|e| e.into_inner()).wait()?,
+    };
+    let stderr_bytes = stderr_reader.join().unwrap_or_default();
+    if timed_out.load(Ordering::SeqCst) {
+        return Err(ShrikeError::SyncFailed("sync timed out".to_string()));
+    }
+    let stdout = stdout?;
+
+    let stderr = String::from_utf8_lossy(&stderr_bytes).to_string();
+    let exit_code = status.code().unwrap_or(-1);
+
+    build_result(stdout, stderr, exit_code, options.max_output_lines)
+}
+
+/// Cut `output` down to its last `max_lines` lines, returning whether any
+/// were dropped.
+pub fn keep_last_lines(output: &mut String, max_lines: usize) -> bool {
+    let Some(newlines) = max_lines.checked_sub(1) else {
+        let dropped = !output.is_empty();
+        output.clear();
+        return dropped;
+    };
+    let body = output.strip_suffix('\n').unwrap_or(output);
+    let Some((cut, _)) = body.rmatch_indices('\n').nth(newlines) else {
+        return false;
     };
+    output.drain(..=cut);
+    true
+}
 
-    if !result.is_success() {
+/// Turn captured rsync output into a `SyncResult`, or an `RsyncError` for
+/// exit codes that are neither 0 nor in `SOFT_EXIT_CODES`.
+///
+/// A soft exit returns `Ok` with its explanation among the warnings, unless
+/// rsync's stderr already produced one.
+///
+/// With `max_output_lines`, the output is parsed in full first and only then
+/// cut down to its tail for storing.
+fn build_result(
+    mut stdout: String,
+    mut stderr: String,
+    exit_code: i32,
+    max_output_lines: Option<usize>,
+) -> Result<SyncResult> {
+    if exit_code != 0 && !is_soft_exit_code(exit_code) {
+        if let Some(max_lines) = max_output_lines {
+            keep_last_lines(&mut stderr, max_lines);
+        }
         return Err(ShrikeError::RsyncError {
             code: exit_code,
-            message: result.stderr.clone(),
+            message: stderr,
         });
     }
 
-    Ok(result)
+    let (files_transferred, dirs_transferred) = count_transferred_items(&stdout);
+    let bytes_transferred = parse_bytes_transferred(&stdout);
+    let changes = parse_itemized_changes(&stdout);
+    let mut warnings = parse_warnings(&stderr);
+    if exit_code != 0 && warnings.is_empty() {
+        warnings.push(format!(
+            "rsync exit code {exit_code}: {}",
+            explain_exit_code(exit_code)
+        ));
+    }
+    let truncated = max_output_lines.is_some_and(|max_lines| {
+        let stdout_cut = keep_last_lines(&mut stdout, max_lines);
+        keep_last_lines(&mut stderr, max_lines) || stdout_cut
+    });
+
+    Ok(SyncResult {
+        files_transferred,
+        dirs_transferred,
+        bytes_transferred,
+        created: changes.created,
+        updated: changes.updated,
+        deleted: changes.deleted,
+        unchanged: changes.unchanged,
+        stdout,
+        stderr,
+        exit_code,
+        synced_at: Utc::now(),
+        warnings,
+        skipped_paths: vec![],
+        truncated,
+    })
 }
 
 #[cfg(test)]
@@ -96,7 +797,7 @@ mod tests {
 
     #[test]
     fn build_rsync_args_correct_format() {
-        let args = build_rsync_args("/tmp/filelist.txt", "/mnt/backup");
+        let args = build_rsync_args("/tmp/filelist.txt", "/mnt/backup", &RsyncOptions::default());
         assert_eq!(args.len(), 4);
         assert_eq!(args[0], "-avrR");
         assert_eq!(args[1], "--files-from=/tmp/filelist.txt");
@@ -106,29 +807,436 @@ mod tests {
 
     #[test]
     fn build_rsync_args_handles_unicode_destination() {
-        let args = build_rsync_args("/tmp/list.txt", "/mnt/我的云端硬盘/ShrikeBackup");
+        let args = build_rsync_args(
+            "/tmp/list.txt",
+            "/mnt/我的云端硬盘/ShrikeBackup",
+            &RsyncOptions::default(),
+        );
         assert_eq!(args[3], "/mnt/我的云端硬盘/ShrikeBackup/");
     }
 
     #[test]
     fn build_rsync_args_trailing_slash_on_destination() {
-        let args = build_rsync_args("/tmp/f.txt", "/dest");
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default());
         assert!(args[3].ends_with('/'));
     }
 
     #[test]
     fn build_rsync_args_root_source() {
-        let args = build_rsync_args("/tmp/f.txt", "/dest");
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default());
         assert_eq!(args[2], "/", "source must always be root /");
     }
 
+    #[test]
+    fn build_rsync_args_uses_source_root() {
+        let options = RsyncOptions {
+            source_root: Some("/Users/me".into()),
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[args.len() - 2], "/Users/me/");
+        assert_eq!(args[args.len() - 1], "/dest/");
+    }
+
     #[test]
     fn build_rsync_args_spaces_in_paths() {
-        let args = build_rsync_args("/tmp/my list.txt", "/mnt/My Backup");
+        let args = build_rsync_args(
+            "/tmp/my list.txt",
+            "/mnt/My Backup",
+            &RsyncOptions::default(),
+        );
         assert_eq!(args[1], "--files-from=/tmp/my list.txt");
         assert_eq!(args[3], "/mnt/My Backup/");
     }
 
+    #[test]
+    fn build_rsync_args_emits_excludes_before_source() {
+        let options = RsyncOptions {
+            excludes: vec!["/Users/me/Library/CloudStorage".into(), "*.log".into()],
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args.len(), 6);
+        assert_eq!(args[2], "--exclude=/Users/me/Library/CloudStorage");
+        assert_eq!(args[3], "--exclude=*.log");
+        assert_eq!(args[4], "/");
+        assert_eq!(args[5], "/dest/");
+    }
+
+    #[test]
+    fn build_rsync_args_includes_follow_all_excludes() {
+        let options = RsyncOptions {
+            excludes: vec!["node_modules/".into()],
+            exclude_from: Some("/tmp/excludes.txt".into()),
+            includes: vec!["*.md".into(), "*.txt".into()],
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(
+            args[2..args.len() - 2],
+            [
+                "--exclude=node_modules/",
+                "--exclude-from=/tmp/excludes.txt",
+                "--prune-empty-dirs",
+                "--include=*/",
+                "--include=*.md",
+                "--include=*.txt",
+                "--exclude=*",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_without_includes_has_no_catch_all_exclude() {
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default());
+        for flag in ["--exclude=*", "--prune-empty-dirs"] {
+            assert!(!args.iter().any(|a| a == flag), "unexpected {flag}");
+        }
+    }
+
+    #[test]
+    fn build_rsync_args_protects_precede_excludes() {
+        let options = RsyncOptions {
+            delete: true,
+            protects: vec!["/shrike-backup.log".into()],
+            excludes: vec!["*.log".into()],
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        let protect = args
+            .iter()
+            .position(|a| a == "--filter=P /shrike-backup.log")
+            .unwrap();
+        let exclude = args.iter().position(|a| a == "--exclude=*.log").unwrap();
+        assert!(protect < exclude, "{args:?}");
+    }
+
+    #[test]
+    fn build_rsync_args_exclude_from_follows_excludes() {
+        let options = RsyncOptions {
+            excludes: vec!["*.log".into()],
+            exclude_from: Some("/tmp/excludes.txt".into()),
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[2], "--exclude=*.log");
+        assert_eq!(args[3], "--exclude-from=/tmp/excludes.txt");
+        assert_eq!(args[4], "/");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .iter()
+                .any(|a| a.starts_with("--exclude-from"))
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_compress_level_only_with_compress() {
+        let mut options = RsyncOptions {
+            compress: true,
+            compress_level: Some(6),
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "-z");
+        assert_eq!(args[2], "--compress-level=6");
+
+        options.compress = false;
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &options)
+                .iter()
+                .any(|a| a.starts_with("--compress-level"))
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_compress_adds_z() {
+        let options = RsyncOptions {
+            compress: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "-z");
+        assert_eq!(args[args.len() - 2], "/");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .contains(&"-z".to_string())
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_dry_run() {
+        let options = RsyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "--dry-run");
+        assert_eq!(args[args.len() - 1], "/dest/");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .contains(&"--dry-run".to_string())
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_progress_adds_info_flag() {
+        let options = RsyncOptions {
+            progress: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "--info=progress2");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .contains(&"--info=progress2".to_string())
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_checksum_only_when_enabled() {
+        let options = RsyncOptions {
+            checksum: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "--checksum");
+        assert_eq!(args[args.len() - 2], "/");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .contains(&"--checksum".to_string())
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_follow_symlinks_only_when_enabled() {
+        let options = RsyncOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "-L");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .contains(&"-L".to_string())
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_delete_only_when_enabled() {
+        let options = RsyncOptions {
+            delete: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(args[1], "--delete");
+        assert_eq!(args[2], "--delete-excluded");
+        assert_eq!(args[args.len() - 2], "/");
+        assert!(
+            !build_rsync_args("/tmp/f.txt", "/dest", &RsyncOptions::default())
+                .iter()
+                .any(|a| a.starts_with("--delete"))
+        );
+    }
+
+    #[test]
+    fn build_rsync_args_itemize_lists_unchanged_files_too() {
+        let options = RsyncOptions {
+            dry_run: true,
+            checksum: true,
+            itemize: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(
+            &args[..5],
+            [
+                "-avrR",
+                "--checksum",
+                "--dry-run",
+                "--itemize-changes",
+                "--itemize-changes"
+            ]
+        );
+        assert_eq!(args[args.len() - 1], "/dest/");
+    }
+
+    #[test]
+    fn build_rsync_args_itemize_changes_adds_single_flag() {
+        let options = RsyncOptions {
+            itemize_changes: true,
+            ..Default::default()
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &options);
+        assert_eq!(&args[..2], ["-avrR", "--itemize-changes"]);
+        assert_eq!(args.iter().filter(|a| *a == "--itemize-changes").count(), 1);
+
+        let both = RsyncOptions {
+            itemize: true,
+            ..options
+        };
+        let args = build_rsync_args("/tmp/f.txt", "/dest", &both);
+        assert_eq!(args.iter().filter(|a| *a == "--itemize-changes").count(), 2);
+    }
+
+    // --- parse_itemized_output ---
+
+    #[test]
+    fn parse_itemized_line_classifies_file_states() {
+        assert_eq!(
+            parse_itemized_line(".f          Users/me/.zshrc"),
+            Some((ItemizedState::InSync, "/Users/me/.zshrc".to_string()))
+        );
+        assert_eq!(
+            parse_itemized_line(">f+++++++++ Users/me/new.txt"),
+            Some((ItemizedState::Missing, "/Users/me/new.txt".to_string()))
+        );
+        assert_eq!(
+            parse_itemized_line(">fcs.t...... Users/me/edited.txt"),
+            Some((ItemizedState::Differing, "/Users/me/edited.txt".to_string()))
+        );
+        // Same content, but permissions changed
+        assert_eq!(
+            parse_itemized_line(".f...p..... Users/me/script.sh"),
+            Some((ItemizedState::Differing, "/Users/me/script.sh".to_string()))
+        );
+        assert_eq!(
+            parse_itemized_line("cL+++++++++ Users/me/link -> target"),
+            Some((ItemizedState::Missing, "/Users/me/link".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_itemized_line_keeps_spaces_in_paths() {
+        assert_eq!(
+            parse_itemized_line(".f          Users/me/My Notes/a b.md"),
+            Some((
+                ItemizedState::InSync,
+                "/Users/me/My Notes/a b.md".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_itemized_line_skips_directories_and_noise() {
+        for line in [
+            "cd+++++++++ Users/",
+            ".d          Users/me/",
+            ".d..t...... Users/me/.config/",
+            "sending incremental file list",
+            "sent 1,234 bytes  received 56 bytes  2,580.00 bytes/sec",
+            "total size is 9,876  speedup is 7.66 (DRY RUN)",
+            "*deleting   Users/me/old.txt",
+            "",
+        ] {
+            assert_eq!(parse_itemized_line(line), None, "line {line:?}");
+        }
+    }
+
+    #[test]
+    fn parse_itemized_output_groups_files() {
+        let stdout = "\
+sending incremental file list
+.d          Users/
+.d          Users/me/
+.f          Users/me/.zshrc
+>f.st...... Users/me/.gitconfig
+cd+++++++++ Users/me/.config/
+>f+++++++++ Users/me/.config/app.toml
+.f          Users/me/.vimrc
+
+sent 321 bytes  received 43 bytes  728.00 bytes/sec
+total size is 4,096  speedup is 11.25 (DRY RUN)
+";
+        let report = parse_itemized_output(stdout);
+        assert_eq!(report.in_sync, vec!["/Users/me/.zshrc", "/Users/me/.vimrc"]);
+        assert_eq!(report.differing, vec!["/Users/me/.gitconfig"]);
+        assert_eq!(report.missing_in_backup, vec!["/Users/me/.config/app.toml"]);
+        assert!(!report.is_in_sync());
+    }
+
+    // --- parse_itemized_changes ---
+
+    #[test]
+    fn parse_itemized_changes_classifies_files() {
+        let stdout = "\
+sending incremental file list
+*deleting   Users/me/old.txt
+*deleting   Users/me/gone/
+.d..t...... Users/me/
+>f+++++++++ Users/me/new.txt
+cd+++++++++ Users/me/dir/
+>f+++++++++ Users/me/dir/a.txt
+>f.st...... Users/me/edited.txt
+.f...p..... Users/me/script.sh
+
+sent 321 bytes  received 43 bytes  728.00 bytes/sec
+total size is 4,096  speedup is 11.25
+";
+        assert_eq!(
+            parse_itemized_changes(stdout),
+            ItemizedChanges {
+                created: 2,
+                updated: 2,
+                deleted: 1,
+                unchanged: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_itemized_changes_counts_unchanged_files() {
+        let stdout = ".f          Users/me/.zshrc\n.f          Users/me/.vimrc\n";
+        assert_eq!(parse_itemized_changes(stdout).unchanged, 2);
+    }
+
+    #[test]
+    fn parse_itemized_changes_ignores_plain_verbose_output() {
+        let stdout = "sending incremental file list\nUsers/me/a.txt\nUsers/me/dir/\n";
+        assert_eq!(parse_itemized_changes(stdout), ItemizedChanges::default());
+    }
+
+    // --- build_restore_args ---
+
+    #[test]
+    fn build_restore_args_swaps_source_and_destination() {
+        let args = build_restore_args("/tmp/f.txt", "/mnt/backup", "/", false);
+        assert_eq!(args[0], "-avrR");
+        assert_eq!(args[2], "--files-from=/tmp/f.txt");
+        assert_eq!(args[args.len() - 2], "/mnt/backup/");
+        assert_eq!(args[args.len() - 1], "/");
+        assert!(args.contains(&"--no-implied-dirs".to_string()));
+        assert!(!args.contains(&"--dry-run".to_string()));
+    }
+
+    #[test]
+    fn build_restore_args_dry_run() {
+        let args = build_restore_args("/tmp/f.txt", "/mnt/backup", "/", true);
+        assert_eq!(args[2], "--dry-run");
+        assert_eq!(args[args.len() - 1], "/");
+    }
+
+    #[test]
+    fn build_restore_args_restores_into_target_root() {
+        let args = build_restore_args("/tmp/f.txt", "/mnt/backup", "/Users/me/", false);
+        assert_eq!(args[args.len() - 2], "/mnt/backup/");
+        assert_eq!(args[args.len() - 1], "/Users/me/");
+    }
+
+    // --- spawn_with_umask ---
+
+    #[cfg(unix)]
+    #[test]
+    fn spawn_with_umask_applies_mask_to_child() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "umask"]).stdout(Stdio::piped());
+        let output = spawn_with_umask(&mut command, 0o077)
+            .unwrap()
+            .wait_with_output()
+            .unwrap();
+        let reported = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(u32::from_str_radix(reported.trim(), 8).unwrap(), 0o077);
+    }
+
     // --- count_transferred_items ---
 
     #[test]
@@ -221,18 +1329,388 @@ total size is 400  speedup is 0.75
         assert_eq!(count_transferred_items(output), (2, 0));
     }
 
+    #[test]
+    fn count_transferred_items_strips_itemize_codes() {
+        let output = "\
+sending incremental file list
+*deleting   Users/me/old.txt
+.d..t...... Users/me/
+cd+++++++++ Users/me/dir/
+>f+++++++++ Users/me/dir/a.txt
+>f.st...... Users/me/My Notes.md
+.f...p..... Users/me/script.sh
+
+sent 500 bytes  received 30 bytes  1060.00 bytes/sec
+total size is 400  speedup is 0.75
+";
+        assert_eq!(count_transferred_items(output), (2, 1));
+    }
+
     #[test]
     fn count_transferred_items_whitespace_only_lines_skipped() {
         let output = "sending incremental file list\n  \n\t\nfile.txt\n\nsent 100 bytes  received 20 bytes  240.00 bytes/sec\n";
         assert_eq!(count_transferred_items(output), (1, 0));
     }
 
+    // --- parse_bytes_transferred ---
+
+    #[test]
+    fn parse_bytes_transferred_reads_sent_line() {
+        let output = "\
+sending incremental file list
+file.txt
+
+sent 1234 bytes  received 56 bytes  2580.00 bytes/sec
+total size is 1000  speedup is 0.78
+";
+        assert_eq!(parse_bytes_transferred(output), 1234);
+    }
+
+    #[test]
+    fn parse_bytes_transferred_handles_thousands_separators() {
+        let output = "\
+sent 1,234,567 bytes  received 1,024 bytes  823,060.67 bytes/sec
+total size is 9,876,543  speedup is 8.00
+";
+        assert_eq!(parse_bytes_transferred(output), 1_234_567);
+    }
+
+    #[test]
+    fn parse_bytes_transferred_handles_human_readable_units() {
+        assert_eq!(
+            parse_bytes_transferred("sent 1.50K bytes  received 35 bytes  3.07K bytes/sec\n"),
+            1536
+        );
+        assert_eq!(
+            parse_bytes_transferred("sent 2M bytes  received 35 bytes\n"),
+            2 * 1024 * 1024
+        );
+    }
+
+    #[test]
+    fn parse_bytes_transferred_openrsync_wording() {
+        // macOS openrsync capitalizes the summary and uses comma separators
+        let output = "\
+Transfer starting: 2 files
+Users/nocoo/.zshrc
+Sent 4096 bytes, received 42 bytes, 8276 bytes/sec
+";
+        assert_eq!(parse_bytes_transferred(output), 4096);
+    }
+
+    #[test]
+    fn parse_bytes_transferred_falls_back_to_total_size() {
+        let output = "file.txt\ntotal size is 2,048  speedup is 1.00\n";
+        assert_eq!(parse_bytes_transferred(output), 2048);
+    }
+
+    #[test]
+    fn parse_bytes_transferred_without_summary_is_zero() {
+        assert_eq!(parse_bytes_transferred(""), 0);
+        assert_eq!(
+            parse_bytes_transferred("sending incremental file list\nfile.txt\n"),
+            0
+        );
+        // A file literally named "sent" is not a summary line
+        assert_eq!(parse_bytes_transferred("sent\n"), 0);
+    }
+
+    // --- parse_warnings ---
+
+    #[test]
+    fn parse_warnings_extracts_known_patterns() {
+        let stderr = "\
+skipping non-regular file \"Users/nocoo/.docker/run/docker.sock\"
+file has vanished: \"/Users/nocoo/.cache/tmp.lock\"
+rsync warning: some files vanished before they could be transferred (code 24) at main.c(1338) [sender=3.2.7]
+";
+        let warnings = parse_warnings(stderr);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("skipping non-regular file"));
+        assert!(warnings[1].starts_with("file has vanished"));
+        assert!(warnings[2].contains("some files vanished"));
+    }
+
+    #[test]
+    fn parse_warnings_ignores_unrelated_lines() {
+        let stderr = "\
+rsync: change_dir \"/nonexistent\" failed: No such file or directory (2)
+
+  skipping non-regular file \"a.sock\"  
+";
+        assert_eq!(
+            parse_warnings(stderr),
+            vec!["skipping non-regular file \"a.sock\""]
+        );
+        assert!(parse_warnings("").is_empty());
+    }
+
+    // --- parse_progress_line / read_output ---
+
+    #[test]
+    fn parse_progress_line_reads_bytes_and_percent() {
+        assert_eq!(
+            parse_progress_line(
+                "      1,234,567  45%   12.34MB/s    0:00:05 (xfr#3, to-chk=10/20)"
+            ),
+            Some((1_234_567, 45))
+        );
+        assert_eq!(
+            parse_progress_line("  32.77K 100%  1.2MB/s  0:00:00"),
+            Some((33_556, 100))
+        );
+        assert_eq!(parse_progress_line("Users/nocoo/.zshrc"), None);
+        assert_eq!(
+            parse_progress_line("sent 1234 bytes  received 56 bytes"),
+            None
+        );
+    }
+
+    #[test]
+    fn read_output_reports_progress_and_strips_it_from_stdout() {
+        let raw = "\
+sending incremental file list
+Users/nocoo/a.txt
+\r          1,024  50%    0.00kB/s    0:00:00\r          2,048 100%    1.95MB/s    0:00:00 (xfr#1, to-chk=1/3)
+Users/nocoo/b.txt
+\r          4,096 100%    3.91MB/s    0:00:00 (xfr#2, to-chk=0/3)
+
+sent 4,321 bytes  received 56 bytes  8,754.00 bytes/sec
+total size is 4,096  speedup is 0.94
+";
+        let mut updates = Vec::new();
+        let stdout = read_output(raw.as_bytes(), &mut |p| updates.push(p.clone())).unwrap();
+
+        assert_eq!(
+            stdout,
+            "\
+sending incremental file list
+Users/nocoo/a.txt
+Users/nocoo/b.txt
+
+sent 4,321 bytes  received 56 bytes  8,754.00 bytes/sec
+total size is 4,096  speedup is 0.94
+"
+        );
+        assert_eq!(count_transferred_items(&stdout), (2, 0));
+        assert_eq!(parse_bytes_transferred(&stdout), 4321);
+
+        assert_eq!(updates.len(), 5);
+        assert_eq!(updates[0].current_file, "Users/nocoo/a.txt");
+        assert_eq!(updates[1].percent, 50);
+        assert_eq!(updates[2].bytes, 2048);
+        assert_eq!(updates[3].current_file, "Users/nocoo/b.txt");
+        let last = updates.last().unwrap();
+        assert_eq!((last.percent, last.bytes), (100, 4096));
+    }
+
+    #[test]
+    fn read_output_without_progress_is_unchanged() {
+        let raw = "sending incremental file list\nfile.txt\n\nsent 100 bytes  received 20 bytes\n";
+        let mut calls = 0;
+        assert_eq!(
+            read_output(raw.as_bytes(), &mut |_| calls += 1).unwrap(),
+            raw
+        );
+        assert_eq!(calls, 1);
+    }
+
+    // --- classify_exit_code / explain_exit_code ---
+
+    #[test]
+    fn classify_known_exit_codes() {
+        assert_eq!(classify_exit_code(0), RsyncExitClass::Success);
+        assert_eq!(classify_exit_code(1), RsyncExitClass::Usage);
+        assert_eq!(classify_exit_code(11), RsyncExitClass::FileIo);
+        assert_eq!(classify_exit_code(12), RsyncExitClass::Protocol);
+        assert_eq!(classify_exit_code(23), RsyncExitClass::PartialTransfer);
+        assert_eq!(classify_exit_code(24), RsyncExitClass::VanishedFiles);
+        assert_eq!(classify_exit_code(35), RsyncExitClass::Timeout);
+    }
+
+    #[test]
+    fn classify_unknown_exit_code_falls_back() {
+        for code in [-1, 6, 99] {
+            assert_eq!(classify_exit_code(code), RsyncExitClass::Unknown);
+            assert_eq!(explain_exit_code(code), "unknown error");
+        }
+    }
+
+    #[test]
+    fn explain_exit_codes() {
+        assert_eq!(explain_exit_code(23), "partial transfer due to errors");
+        assert_eq!(
+            explain_exit_code(24),
+            "partial transfer because source files vanished"
+        );
+        assert_eq!(explain_exit_code(11), "error in file I/O");
+    }
+
+    #[test]
+    fn only_vanished_files_is_soft() {
+        assert!(classify_exit_code(24).is_soft());
+        assert!(!classify_exit_code(23).is_soft());
+        assert!(!classify_exit_code(12).is_soft());
+        assert_eq!(
+            serde_json::to_string(&RsyncExitClass::VanishedFiles).unwrap(),
+            "\"vanished_files\""
+        );
+    }
+
+    // --- build_result ---
+
+    #[test]
+    fn build_result_vanished_files_is_ok_with_warning() {
+        let stdout = "\
+sending incremental file list
+Users/nocoo/.zshrc
+
+sent 200 bytes  received 35 bytes  470.00 bytes/sec
+total size is 120  speedup is 0.51
+";
+        let stderr = "\
+file has vanished: \"/Users/nocoo/.vim/swap/.zshrc.swp\"
+rsync warning: some files vanished before they could be transferred (code 24) at main.c(1338) [sender=3.2.7]
+";
+        let result = build_result(stdout.into(), stderr.into(), 24, None).unwrap();
+        assert_eq!(result.exit_code, 24);
+        assert!(result.is_success());
+        assert_eq!(result.files_transferred, 1);
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result.warnings[0].starts_with("file has vanished"));
+    }
+
+    #[test]
+    fn build_result_soft_exit_without_stderr_still_warns() {
+        let result = build_result(String::new(), String::new(), 24, None).unwrap();
+        assert_eq!(
+            result.warnings,
+            vec!["rsync exit code 24: partial transfer because source files vanished"]
+        );
+    }
+
+    #[test]
+    fn build_result_partial_transfer_is_an_error() {
+        let err = build_result(String::new(), "permission denied".into(), 23, None).unwrap_err();
+        assert!(matches!(err, ShrikeError::RsyncError { code: 23, .. }));
+        assert!(
+            build_result(String::new(), String::new(), 0, None)
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn keep_last_lines_keeps_tail() {
+        let mut output = "a\nb\nc\n".to_string();
+        assert!(!keep_last_lines(&mut output, 3));
+        assert_eq!(output, "a\nb\nc\n");
+        assert!(keep_last_lines(&mut output, 2));
+        assert_eq!(output, "b\nc\n");
+
+        let mut unterminated = "a\nb\nc".to_string();
+        assert!(keep_last_lines(&mut unterminated, 1));
+        assert_eq!(unterminated, "c");
+        assert!(keep_last_lines(&mut unterminated, 0));
+        assert!(unterminated.is_empty());
+    }
+
+    #[test]
+    fn build_result_caps_output_after_counting() {
+        let mut stdout = String::from("sending incremental file list\n");
+        for i in 0..600 {
+            stdout.push_str(&format!("Users/me/notes/{i}.md\n"));
+        }
+        stdout.push_str("\nsent 4096 bytes  received 35 bytes  470.00 bytes/sec\n");
+        let stdout_lines = stdout.lines().count();
+
+        let result = build_result(stdout, String::new(), 0, Some(500)).unwrap();
+        assert!(result.truncated);
+        assert_eq!(result.files_transferred, 600);
+        assert_eq!(result.bytes_transferred, 4096);
+        assert_eq!(result.stdout.lines().count(), 500);
+        assert!(
+            result
+                .stdout
+                .starts_with(&format!("Users/me/notes/{}.md\n", stdout_lines - 500 - 1))
+        );
+        assert!(result.stdout.ends_with("470.00 bytes/sec\n"));
+
+        let short = build_result("Users/me/a.txt\n".into(), String::new(), 0, Some(500)).unwrap();
+        assert!(!short.truncated);
+        assert_eq!(short.stdout, "Users/me/a.txt\n");
+    }
+
     // --- run_rsync ---
 
+    /// Write an executable shell script standing in for rsync.
+    #[cfg(unix)]
+    fn fake_rsync(dir: &std::path::Path, script: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("rsync");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_rsync_honors_program_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = fake_rsync(
+            dir.path(),
+            "echo \"$1\"; echo; echo 'sent 1234 bytes  received 56 bytes'",
+        );
+        let options = RunOptions {
+            program: Some(program),
+            ..Default::default()
+        };
+        let result = run_rsync(&["Users/me/notes.txt".to_string()], &options).unwrap();
+        assert_eq!(result.bytes_transferred, 1234);
+        assert_eq!(result.files_transferred, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_rsync_kills_process_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = RunOptions {
+            program: Some(fake_rsync(dir.path(), "exec sleep 30")),
+            timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let err = run_rsync(&[], &options).unwrap_err();
+        assert!(matches!(&err, ShrikeError::SyncFailed(m) if m == "sync timed out"));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_rsync_finishing_within_timeout_succeeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = RunOptions {
+            program: Some(fake_rsync(
+                dir.path(),
+                "echo 'sent 7 bytes  received 1 bytes'",
+            )),
+            timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        assert_eq!(run_rsync(&[], &options).unwrap().bytes_transferred, 7);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
     #[test]
     fn run_rsync_with_nonexistent_source_fails() {
-        let args = build_rsync_args("/nonexistent/filelist.txt", "/tmp");
-        let result = run_rsync(&args);
+        let args = build_rsync_args(
+            "/nonexistent/filelist.txt",
+            "/tmp",
+            &RsyncOptions::default(),
+        );
+        let result = run_rsync(&args, &RunOptions::default());
         assert!(result.is_err());
     }
 
@@ -241,8 +1719,12 @@ total size is 400  speedup is 0.75
         // Create an empty filelist
         let file = tempfile::NamedTempFile::new().unwrap();
         let dest = tempfile::tempdir().unwrap();
-        let args = build_rsync_args(file.path().to_str().unwrap(), dest.path().to_str().unwrap());
-        let result = run_rsync(&args).unwrap();
+        let args = build_rsync_args(
+            file.path().to_str().unwrap(),
+            dest.path().to_str().unwrap(),
+            &RsyncOptions::default(),
+        );
+        let result = run_rsync(&args, &RunOptions::default()).unwrap();
         assert!(result.is_success());
         // macOS openrsync may still output directory entries even with an
         // empty filelist, so we just check it succeeds without error
@@ -275,8 +1757,9 @@ total size is 400  speedup is 0.75
         let args = build_rsync_args(
             filelist.path().to_str().unwrap(),
             dest_dir.path().to_str().unwrap(),
+            &RsyncOptions::default(),
         );
-        let result = run_rsync(&args).unwrap();
+        let result = run_rsync(&args, &RunOptions::default()).unwrap();
 
         assert!(result.is_success());
         assert!(result.files_transferred >= 1);
@@ -289,4 +1772,32 @@ total size is 400  speedup is 0.75
             "rsync test content"
         );
     }
+
+    #[test]
+    fn run_rsync_with_progress_reports_multi_file_transfer() {
+        use std::io::Write;
+
+        let source_dir = tempfile::tempdir().unwrap();
+        let source_root = std::fs::canonicalize(source_dir.path()).unwrap();
+        let mut filelist = tempfile::NamedTempFile::new().unwrap();
+        for name in ["one.txt", "two.txt", "three.txt"] {
+            let path = source_root.join(name);
+            std::fs::write(&path, name.repeat(1000)).unwrap();
+            writeln!(filelist, "{}", path.display()).unwrap();
+        }
+        filelist.flush().unwrap();
+        let dest_dir = tempfile::tempdir().unwrap();
+
+        let args = build_rsync_args(
+            filelist.path().to_str().unwrap(),
+            dest_dir.path().to_str().unwrap(),
+            &RsyncOptions::default(),
+        );
+        let mut calls = 0;
+        let result =
+            run_rsync_with_progress(&args, &RunOptions::default(), &mut |_| calls += 1).unwrap();
+
+        assert!(calls >= 1, "progress callback was never invoked");
+        assert_eq!(result.files_transferred, 3);
+    }
 }
@@ -5,7 +5,7 @@
 //! on its own line.
 
 use std::fs;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use tempfile::NamedTempFile;
@@ -13,19 +13,54 @@ use tempfile::NamedTempFile;
 use crate::error::{Result, ShrikeError};
 use crate::types::BackupEntry;
 
-/// Write all entry paths into a temporary file (one path per line).
+/// Write all enabled entry paths into a temporary file (one path per line).
+///
+/// A path containing `\n` or `\r` would be split into two bogus lines, so
+/// it fails the whole filelist with `ShrikeError::PathHasLineBreak` before
+/// anything is written.
 ///
 /// Returns the `NamedTempFile` handle. The caller must keep this handle alive
 /// for as long as rsync needs to read from it; dropping it deletes the file.
+///
+/// Paths are streamed through a buffered writer, so a very long entry list
+/// costs neither a copy in memory nor a write per line.
 pub fn generate_filelist(entries: &[BackupEntry]) -> Result<NamedTempFile> {
+    let enabled = || entries.iter().filter(|e| e.enabled);
+    if let Some(entry) = enabled().find(|e| e.path.contains(['\n', '\r'])) {
+        return Err(ShrikeError::PathHasLineBreak(entry.path.clone()));
+    }
+
     let mut file = NamedTempFile::new()?;
-    for entry in entries {
-        writeln!(file, "{}", entry.path)?;
+    let mut writer = BufWriter::new(file.as_file_mut());
+    for entry in enabled() {
+        writeln!(writer, "{}", entry.path)?;
     }
-    file.flush()?;
+    writer.flush()?;
+    drop(writer);
     Ok(file)
 }
 
+/// Write exclude patterns into a temporary file for `--exclude-from`, one
+/// per line. Blank patterns are skipped; returns `None` if none remain.
+///
+/// As with `generate_filelist`, the file is deleted when the handle drops.
+pub fn generate_exclude_file(patterns: &[String]) -> Result<Option<NamedTempFile>> {
+    let patterns: Vec<&str> = patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut file = NamedTempFile::new()?;
+    for pattern in patterns {
+        writeln!(file, "{pattern}")?;
+    }
+    file.flush()?;
+    Ok(Some(file))
+}
+
 /// Read a filelist file back into a vector of path strings.
 ///
 /// This is the inverse of `generate_filelist` and is used by the validation
@@ -40,6 +75,58 @@ pub fn read_filelist(path: &Path) -> Result<Vec<String>> {
     Ok(lines)
 }
 
+/// Escape rsync wildcard characters so a path matches only itself.
+///
+/// rsync reads `\` as an escape only in patterns that contain a wildcard,
+/// so a backslash is escaped only when the path also holds one of `*?[`.
+fn escape_pattern(path: &str) -> String {
+    let has_wildcard = path.contains(['*', '?', '[']);
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[') || (has_wildcard && c == '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Compute exclude patterns for protected directories that would otherwise
+/// be swept up by a broad entry.
+///
+/// Backing up `~` with `-R` would also copy `~/Library/CloudStorage` (the
+/// Google Drive mount, including the backup itself) and the destination,
+/// recursing into itself. For every protected path that lies strictly inside
+/// some entry, an anchored exclude pattern is returned. Entries that do not
+/// contain a protected path produce no excludes.
+pub fn protected_excludes(
+    entries: &[BackupEntry],
+    destination: &str,
+    cloud_storage_dir: Option<&Path>,
+) -> Vec<String> {
+    let mut protected: Vec<&Path> = Vec::new();
+    if let Some(dir) = cloud_storage_dir {
+        protected.push(dir);
+    }
+    protected.push(Path::new(destination));
+
+    let mut excludes: Vec<String> = Vec::new();
+    for target in protected {
+        let inside_entry = entries.iter().any(|entry| {
+            let entry_path = Path::new(&entry.path);
+            target != entry_path && target.starts_with(entry_path)
+        });
+        if !inside_entry {
+            continue;
+        }
+        let pattern = escape_pattern(target.to_string_lossy().trim_end_matches('/'));
+        if !excludes.contains(&pattern) {
+            excludes.push(pattern);
+        }
+    }
+    excludes
+}
+
 /// Return the filelist path as a UTF-8 string, or error.
 pub fn filelist_path_str(file: &NamedTempFile) -> Result<String> {
     file.path()
@@ -53,6 +140,69 @@ mod tests {
     use super::*;
     use crate::types::ItemType;
 
+    const CLOUD: &str = "/Users/me/Library/CloudStorage";
+    const DEST: &str = "/Users/me/Library/CloudStorage/GoogleDrive-me/My Drive/ShrikeBackup/mbp";
+
+    #[test]
+    fn protected_excludes_home_entry_excludes_cloud_and_destination() {
+        let entries = vec![BackupEntry::new("/Users/me".into(), ItemType::Directory)];
+        let excludes = protected_excludes(&entries, DEST, Some(Path::new(CLOUD)));
+        assert_eq!(excludes, vec![CLOUD.to_string(), DEST.to_string()]);
+    }
+
+    #[test]
+    fn protected_excludes_unrelated_entries_produce_none() {
+        let entries = vec![
+            BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+            BackupEntry::new("/Users/me/.claude".into(), ItemType::Directory),
+        ];
+        let excludes = protected_excludes(&entries, DEST, Some(Path::new(CLOUD)));
+        assert!(excludes.is_empty());
+    }
+
+    #[test]
+    fn protected_excludes_respects_component_boundaries() {
+        let entries = vec![BackupEntry::new("/a/b".into(), ItemType::Directory)];
+        assert!(protected_excludes(&entries, "/a/bc/backup", None).is_empty());
+        assert_eq!(
+            protected_excludes(&entries, "/a/b/c/backup", None),
+            vec!["/a/b/c/backup"]
+        );
+    }
+
+    #[test]
+    fn protected_excludes_entry_inside_cloud_dir_only_excludes_destination() {
+        // An entry inside CloudStorage does not contain CloudStorage itself
+        let entries = vec![BackupEntry::new(
+            "/Users/me/Library/CloudStorage/GoogleDrive-me".into(),
+            ItemType::Directory,
+        )];
+        let excludes = protected_excludes(&entries, DEST, Some(Path::new(CLOUD)));
+        assert_eq!(excludes, vec![DEST.to_string()]);
+    }
+
+    #[test]
+    fn protected_excludes_escapes_wildcards() {
+        let entries = vec![BackupEntry::new("/data".into(), ItemType::Directory)];
+        assert_eq!(
+            protected_excludes(&entries, "/data/backup[1]*", None),
+            vec!["/data/backup\\[1]\\*"]
+        );
+    }
+
+    #[test]
+    fn protected_excludes_escapes_backslashes_only_with_wildcards() {
+        let entries = vec![BackupEntry::new("/data".into(), ItemType::Directory)];
+        assert_eq!(
+            protected_excludes(&entries, "/data/back\\up", None),
+            vec!["/data/back\\up"]
+        );
+        assert_eq!(
+            protected_excludes(&entries, "/data/back\\up*", None),
+            vec!["/data/back\\\\up\\*"]
+        );
+    }
+
     #[test]
     fn generate_filelist_writes_all_paths() {
         let entries = vec![
@@ -69,6 +219,43 @@ mod tests {
         assert_eq!(contents.lines().count(), 3);
     }
 
+    #[test]
+    fn generate_filelist_skips_disabled_entries() {
+        let mut disabled = BackupEntry::new("/Users/me/Movies".into(), ItemType::Directory);
+        disabled.enabled = false;
+        let entries = vec![
+            disabled,
+            BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+        ];
+        let file = generate_filelist(&entries).unwrap();
+        let contents = fs::read_to_string(file.path()).unwrap();
+        assert_eq!(contents, "/Users/me/.zshrc\n");
+    }
+
+    #[test]
+    fn generate_exclude_file_writes_one_pattern_per_line() {
+        let patterns = vec![".DS_Store".into(), "  ".into(), "__pycache__/".into()];
+        let file = generate_exclude_file(&patterns).unwrap().unwrap();
+        let contents = fs::read_to_string(file.path()).unwrap();
+        assert_eq!(contents, ".DS_Store\n__pycache__/\n");
+        assert!(generate_exclude_file(&["".into()]).unwrap().is_none());
+    }
+
+    #[test]
+    fn generate_filelist_rejects_paths_with_line_breaks() {
+        for path in ["/Users/me/evil\n/etc/passwd", "/Users/me/evil\r.txt"] {
+            let entries = vec![
+                BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+                BackupEntry::new(path.into(), ItemType::File),
+            ];
+            let err = generate_filelist(&entries).unwrap_err();
+            assert!(
+                matches!(&err, ShrikeError::PathHasLineBreak(p) if p == path),
+                "{err}"
+            );
+        }
+    }
+
     #[test]
     fn generate_filelist_empty_entries_produces_empty_file() {
         let entries: Vec<BackupEntry> = vec![];
@@ -0,0 +1,269 @@
+//! Rsync-style exclude matching, used to preview what a sync will skip.
+//!
+//! Implements the subset of rsync's filter rules that Shrike emits:
+//! - `*` matches within one path component, `**` across components,
+//!   `?` one non-`/` character, `[...]` a character class, `\` escapes
+//!   (only in patterns holding a wildcard; others match literally)
+//! - a pattern without `/` matches the final path component at any depth
+//! - a pattern starting with `/` is anchored at the transfer root (`/`,
+//!   since Shrike syncs absolute paths with `-R`)
+//! - any other pattern with a `/` matches a trailing run of components
+//! - a trailing `/` restricts the pattern to directories
+
+use std::fs;
+use std::path::Path;
+
+/// Maximum number of paths returned by an exclude preview.
+pub const MAX_PREVIEW_PATHS: usize = 1000;
+
+/// Match a character class starting after `[`. Returns the match result and
+/// the remaining pattern, or `None` if the class is unterminated.
+fn match_class(p: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, mut i) = match p.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+    let mut matched = false;
+    let mut first = true;
+    while i < p.len() {
+        if p[i] == ']' && !first {
+            return Some((matched != negated, &p[i + 1..]));
+        }
+        first = false;
+        if i + 2 < p.len() && p[i + 1] == '-' && p[i + 2] != ']' {
+            matched |= p[i] <= c && c <= p[i + 2];
+            i += 3;
+        } else {
+            matched |= p[i] == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+fn glob_chars(p: &[char], t: &[char]) -> bool {
+    match p.first() {
+        None => t.is_empty(),
+        Some('*') if p.get(1) == Some(&'*') => (0..=t.len()).any(|i| glob_chars(&p[2..], &t[i..])),
+        Some('*') => {
+            for i in 0..=t.len() {
+                if glob_chars(&p[1..], &t[i..]) {
+                    return true;
+                }
+                if i < t.len() && t[i] == '/' {
+                    return false;
+                }
+            }
+            false
+        }
+        Some('?') => t.first().is_some_and(|&c| c != '/') && glob_chars(&p[1..], &t[1..]),
+        Some('[') => match t.first() {
+            Some(&c) if c != '/' => match match_class(&p[1..], c) {
+                Some((true, rest)) => glob_chars(rest, &t[1..]),
+                Some((false, _)) => false,
+                // Unterminated class: treat `[` literally
+                None => c == '[' && glob_chars(&p[1..], &t[1..]),
+            },
+            _ => false,
+        },
+        Some('\\') if p.len() > 1 => t.first() == Some(&p[1]) && glob_chars(&p[2..], &t[1..]),
+        Some(&c) => t.first() == Some(&c) && glob_chars(&p[1..], &t[1..]),
+    }
+}
+
+/// Match `text` against a glob `pattern`.
+///
+/// As in rsync, a pattern without any of `*?[` is matched literally, so a
+/// `\` in it is not an escape.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    if !pattern.contains(['*', '?', '[']) {
+        return pattern == text;
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    glob_chars(&p, &t)
+}
+
+/// Returns true if the absolute `path` would be excluded by any pattern.
+pub fn is_excluded(path: &Path, is_dir: bool, patterns: &[String]) -> bool {
+    let full = path.to_string_lossy();
+    let components: Vec<String> = path
+        .components()
+        .skip(1)
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    patterns.iter().any(|pattern| {
+        let (pattern, dir_only) = match pattern.strip_suffix('/') {
+            Some(p) => (p, true),
+            None => (pattern.as_str(), false),
+        };
+        if dir_only && !is_dir {
+            return false;
+        }
+        if pattern.starts_with('/') {
+            glob_match(pattern, &full)
+        } else if pattern.contains('/') {
+            (0..components.len()).any(|i| glob_match(pattern, &components[i..].join("/")))
+        } else {
+            components
+                .last()
+                .is_some_and(|name| glob_match(pattern, name))
+        }
+    })
+}
+
+fn walk(dir: &Path, patterns: &[String], limit: usize, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    children.sort_by_key(|e| e.file_name());
+
+    for child in children {
+        if out.len() >= limit {
+            return;
+        }
+        let path = child.path();
+        let is_dir = child.file_type().is_ok_and(|t| t.is_dir());
+        if is_excluded(&path, is_dir, patterns) {
+            // rsync skips an excluded directory's whole subtree
+            let suffix = if is_dir { "/" } else { "" };
+            out.push(format!("{}{suffix}", path.to_string_lossy()));
+        } else if is_dir {
+            walk(&path, patterns, limit, out);
+        }
+    }
+}
+
+/// List paths under `root` that `patterns` exclude, in sorted walk order.
+///
+/// Excluded directories are listed once with a trailing `/` and not
+/// descended into. At most `limit` paths are returned. Symlinks are not
+/// followed.
+pub fn excluded_paths(root: &Path, patterns: &[String], limit: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    if !patterns.is_empty() {
+        walk(root, patterns, limit, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("*.log", "debug.log"));
+        assert!(!glob_match("*.log", "debug.log.gz"));
+        assert!(!glob_match("*.log", "dir/debug.log"));
+        assert!(glob_match("**/*.log", "a/b/debug.log"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file/.txt"));
+        assert!(glob_match("[a-c]*.rs", "build.rs"));
+        assert!(!glob_match("[!a-c]*.rs", "build.rs"));
+        assert!(glob_match("backup\\[1\\]", "backup[1]"));
+        assert!(!glob_match("backup\\*", "backup1"));
+        // Without wildcards a backslash is literal
+        assert!(glob_match("back\\up", "back\\up"));
+        assert!(!glob_match("back\\up", "backup"));
+    }
+
+    #[test]
+    fn is_excluded_basename_pattern_matches_at_any_depth() {
+        let p = patterns(&["*.log"]);
+        assert!(is_excluded(Path::new("/data/x.log"), false, &p));
+        assert!(is_excluded(Path::new("/data/deep/er/x.log"), false, &p));
+        assert!(!is_excluded(Path::new("/data/x.txt"), false, &p));
+    }
+
+    #[test]
+    fn is_excluded_anchored_and_dir_only_patterns() {
+        let anchored = patterns(&["/Users/me/Library/CloudStorage"]);
+        assert!(is_excluded(
+            Path::new("/Users/me/Library/CloudStorage"),
+            true,
+            &anchored
+        ));
+        assert!(!is_excluded(
+            Path::new("/other/Users/me/Library/CloudStorage"),
+            true,
+            &anchored
+        ));
+
+        let dir_only = patterns(&["node_modules/"]);
+        assert!(is_excluded(Path::new("/p/node_modules"), true, &dir_only));
+        assert!(!is_excluded(Path::new("/p/node_modules"), false, &dir_only));
+    }
+
+    #[test]
+    fn is_excluded_multi_component_pattern_matches_tail() {
+        let p = patterns(&["projects/*/node_modules"]);
+        assert!(is_excluded(
+            Path::new("/Users/me/.claude/projects/app/node_modules"),
+            true,
+            &p
+        ));
+        assert!(!is_excluded(
+            Path::new("/Users/me/.claude/projects/app/src"),
+            true,
+            &p
+        ));
+    }
+
+    #[test]
+    fn excluded_paths_lists_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir(root.join("logs")).unwrap();
+        fs::write(root.join("app.log"), "").unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+        fs::write(root.join("logs/old.log"), "").unwrap();
+        fs::write(root.join("logs/keep.txt"), "").unwrap();
+
+        let excluded = excluded_paths(&root, &patterns(&["*.log"]), MAX_PREVIEW_PATHS);
+        let root = root.to_string_lossy();
+        assert_eq!(
+            excluded,
+            vec![format!("{root}/app.log"), format!("{root}/logs/old.log")]
+        );
+    }
+
+    #[test]
+    fn excluded_paths_reports_directory_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+
+        let excluded = excluded_paths(&root, &patterns(&["node_modules/"]), 10);
+        assert_eq!(
+            excluded,
+            vec![format!("{}/node_modules/", root.to_string_lossy())]
+        );
+    }
+
+    #[test]
+    fn excluded_paths_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            fs::write(dir.path().join(format!("{i}.log")), "").unwrap();
+        }
+        assert_eq!(
+            excluded_paths(dir.path(), &patterns(&["*.log"]), 5).len(),
+            5
+        );
+    }
+
+    #[test]
+    fn excluded_paths_without_patterns_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.log"), "").unwrap();
+        assert!(excluded_paths(dir.path(), &[], 10).is_empty());
+    }
+}
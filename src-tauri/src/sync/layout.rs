@@ -0,0 +1,267 @@
+//! Destination layout: where each entry ends up inside the backup directory.
+//!
+//! With the default `Full` layout, rsync's `-R` flag reproduces each entry's
+//! absolute source path under the destination, so distinct entries can never
+//! collide. `RelativeToHome` roots entries inside the home directory at home
+//! instead of `/`, so a path in home and one outside it (`~/etc/hosts` and
+//! `/etc/hosts`) can map to the same destination and silently overwrite each
+//! other.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::types::{AppSettings, BackupEntry, PathLayout};
+
+/// Compute where `source` is written inside `destination` under `layout`.
+///
+/// `Full` mirrors rsync `-R` (`<dest>/<abs_path>`), and `RelativeToHome`
+/// strips the home directory from paths inside it (`<dest>/<path_in_home>`).
+pub fn backup_path_for(source: &str, destination: &str, layout: PathLayout) -> PathBuf {
+    let source_path = Path::new(source);
+    let relative = match layout {
+        PathLayout::Full => source_path.strip_prefix("/").unwrap_or(source_path),
+        PathLayout::RelativeToHome => match transfer_root(source, layout, home_dir().as_deref()) {
+            Some(root) => source_path.strip_prefix(root).unwrap_or(source_path),
+            None => source_path.strip_prefix("/").unwrap_or(source_path),
+        },
+    };
+    if relative.as_os_str().is_empty() {
+        return PathBuf::from(destination);
+    }
+    Path::new(destination).join(relative)
+}
+
+/// The home directory `RelativeToHome` roots paths at, canonicalized like
+/// entry paths so the two compare equal.
+pub fn home_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| std::fs::canonicalize(&home).unwrap_or(home))
+}
+
+/// Directory rsync transfers `source` from under `layout`, when it is not `/`.
+///
+/// Only `RelativeToHome` roots anything elsewhere: at `home`, for paths
+/// inside it. Paths outside home are transferred from `/` as with `Full`.
+pub fn transfer_root(source: &str, layout: PathLayout, home: Option<&Path>) -> Option<PathBuf> {
+    let home = home.filter(|_| layout == PathLayout::RelativeToHome)?;
+    Path::new(source)
+        .starts_with(home)
+        .then(|| home.to_path_buf())
+}
+
+/// `path` as written to the filelist of a transfer rooted at `root`.
+///
+/// The path keeps a leading `/`, which rsync strips from filelist lines just
+/// as it does for `/`-rooted transfers; `root` itself becomes `/.`. Paths
+/// outside `root` are returned unchanged.
+pub fn rebase(path: &str, root: &Path) -> String {
+    match Path::new(path).strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => "/.".to_string(),
+        Ok(relative) => format!("/{}", relative.to_string_lossy()),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// Where syncs back `entry` up: `<gdrive>/<backup_dir>/<machine>/<path>`,
+/// laid out by `settings.path_layout` under the primary destination.
+pub fn entry_backup_path(entry: &BackupEntry, settings: &AppSettings) -> Result<PathBuf> {
+    let destination = settings.destination_path()?;
+    Ok(backup_path_for(
+        &entry.path,
+        &destination,
+        settings.path_layout,
+    ))
+}
+
+/// Program and arguments that show `path` in the file manager.
+///
+/// On macOS `open -R` selects it in its enclosing Finder folder; elsewhere
+/// the enclosing folder is opened with `xdg-open`.
+pub fn reveal_command(path: &Path) -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "macos") {
+        (
+            "open",
+            vec!["-R".to_string(), path.to_string_lossy().to_string()],
+        )
+    } else {
+        let folder = path.parent().unwrap_or(path);
+        ("xdg-open", vec![folder.to_string_lossy().to_string()])
+    }
+}
+
+/// Find pairs of entries that map to the same destination path.
+///
+/// Each collision is reported as `(first_id, second_id, shared_path)`, in
+/// entry order. Three entries sharing a path produce two pairs, each against
+/// the first entry that claimed it.
+pub fn detect_destination_collisions(
+    entries: &[BackupEntry],
+    destination: &str,
+    layout: PathLayout,
+) -> Vec<(Uuid, Uuid, String)> {
+    let mut claimed: HashMap<PathBuf, Uuid> = HashMap::new();
+    let mut collisions = Vec::new();
+    for entry in entries {
+        let target = backup_path_for(&entry.path, destination, layout);
+        match claimed.get(&target) {
+            Some(first) => {
+                collisions.push((*first, entry.id, target.to_string_lossy().to_string()));
+            }
+            None => {
+                claimed.insert(target, entry.id);
+            }
+        }
+    }
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ItemType;
+
+    const DEST: &str = "/Volumes/GoogleDrive/My Drive/ShrikeBackup/mbp";
+
+    #[test]
+    fn backup_path_full_layout_mirrors_source() {
+        assert_eq!(
+            backup_path_for("/Users/me/.zshrc", DEST, PathLayout::Full),
+            PathBuf::from(format!("{DEST}/Users/me/.zshrc"))
+        );
+    }
+
+    #[test]
+    fn backup_path_relative_to_home_strips_home() {
+        let home = home_dir().unwrap();
+        let source = home.join(".config/app");
+        assert_eq!(
+            backup_path_for(&source.to_string_lossy(), DEST, PathLayout::RelativeToHome),
+            PathBuf::from(format!("{DEST}/.config/app"))
+        );
+        assert_eq!(
+            backup_path_for(&home.to_string_lossy(), DEST, PathLayout::RelativeToHome),
+            PathBuf::from(DEST)
+        );
+    }
+
+    #[test]
+    fn backup_path_relative_to_home_keeps_paths_outside_home_full() {
+        assert_eq!(
+            backup_path_for("/etc/hosts", DEST, PathLayout::RelativeToHome),
+            PathBuf::from(format!("{DEST}/etc/hosts"))
+        );
+    }
+
+    #[test]
+    fn transfer_root_is_home_only_for_paths_inside_it() {
+        let home = Path::new("/Users/me");
+        let root = |source, layout| transfer_root(source, layout, Some(home));
+        assert_eq!(
+            root("/Users/me/.zshrc", PathLayout::RelativeToHome),
+            Some(PathBuf::from("/Users/me"))
+        );
+        assert_eq!(root("/Users/meow/.zshrc", PathLayout::RelativeToHome), None);
+        assert_eq!(root("/etc/hosts", PathLayout::RelativeToHome), None);
+        assert_eq!(root("/Users/me/.zshrc", PathLayout::Full), None);
+        assert_eq!(
+            transfer_root("/Users/me/.zshrc", PathLayout::RelativeToHome, None),
+            None
+        );
+    }
+
+    #[test]
+    fn rebase_keeps_a_leading_slash() {
+        let home = Path::new("/Users/me");
+        assert_eq!(rebase("/Users/me/.config/app", home), "/.config/app");
+        assert_eq!(rebase("/Users/me", home), "/.");
+        assert_eq!(rebase("/etc/hosts", home), "/etc/hosts");
+    }
+
+    #[test]
+    fn entry_backup_path_nests_source_under_machine_dir() {
+        let settings = AppSettings {
+            gdrive_path: "/Volumes/GoogleDrive/My Drive".into(),
+            backup_dir_name: "ShrikeBackup".into(),
+            machine_name: "mbp".into(),
+            ..AppSettings::default()
+        };
+        let entry = BackupEntry::new("/Users/me/.config/app".into(), ItemType::Directory);
+        assert_eq!(
+            entry_backup_path(&entry, &settings).unwrap(),
+            PathBuf::from(format!("{DEST}/Users/me/.config/app"))
+        );
+    }
+
+    #[test]
+    fn entry_backup_path_requires_destination() {
+        let settings = AppSettings {
+            gdrive_path: String::new(),
+            ..AppSettings::default()
+        };
+        let entry = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        assert!(entry_backup_path(&entry, &settings).is_err());
+    }
+
+    #[test]
+    fn reveal_command_targets_backup() {
+        let path = PathBuf::from(format!("{DEST}/Users/me/.zshrc"));
+        let (program, args) = reveal_command(&path);
+        if cfg!(target_os = "macos") {
+            assert_eq!(program, "open");
+            assert_eq!(
+                args,
+                vec!["-R".to_string(), format!("{DEST}/Users/me/.zshrc")]
+            );
+        } else {
+            assert_eq!(program, "xdg-open");
+            assert_eq!(args, vec![format!("{DEST}/Users/me")]);
+        }
+    }
+
+    #[test]
+    fn relative_to_home_layout_detects_home_and_root_overlap() {
+        let home = home_dir().unwrap();
+        let a = BackupEntry::new(
+            home.join("etc/hosts").to_string_lossy().to_string(),
+            ItemType::File,
+        );
+        let b = BackupEntry::new("/etc/hosts".into(), ItemType::File);
+        let c = BackupEntry::new(
+            home.join(".zshrc").to_string_lossy().to_string(),
+            ItemType::File,
+        );
+        let collisions = detect_destination_collisions(
+            &[a.clone(), b.clone(), c],
+            DEST,
+            PathLayout::RelativeToHome,
+        );
+        assert_eq!(collisions, vec![(a.id, b.id, format!("{DEST}/etc/hosts"))]);
+    }
+
+    #[test]
+    fn full_layout_has_no_collisions() {
+        let entries = vec![
+            BackupEntry::new("/Users/me/.config/foo/config.toml".into(), ItemType::File),
+            BackupEntry::new("/Users/me/.config/bar/config.toml".into(), ItemType::File),
+        ];
+        assert!(detect_destination_collisions(&entries, DEST, PathLayout::Full).is_empty());
+    }
+
+    #[test]
+    fn three_way_collision_reports_against_first() {
+        let in_home = home_dir().unwrap().join("a/x");
+        let entries: Vec<BackupEntry> = [in_home.to_str().unwrap(), "/a/x", "/a/x"]
+            .iter()
+            .map(|p| BackupEntry::new(p.to_string(), ItemType::File))
+            .collect();
+        let collisions = detect_destination_collisions(&entries, DEST, PathLayout::RelativeToHome);
+        assert_eq!(collisions.len(), 2);
+        assert!(
+            collisions
+                .iter()
+                .all(|(first, _, _)| *first == entries[0].id)
+        );
+    }
+}
@@ -4,15 +4,29 @@
 //! 1. **filelist** — Generate a `--files-from` temp file from BackupEntry list
 //! 2. **validation** — Validate paths exist, are readable, no duplicates
 //! 3. **executor** — Build rsync args, run rsync, parse output
+//!
+//! **restore** runs the same layers in reverse, from backup to source.
 
+pub mod capabilities;
+pub mod changelog;
+pub mod encrypted;
 pub mod executor;
 pub mod filelist;
+pub mod filter;
+pub mod layout;
+pub mod restore;
 pub mod validation;
 
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use serde::Serialize;
+use uuid::Uuid;
+
 use crate::error::{Result, ShrikeError};
-use crate::types::{AppSettings, BackupEntry, SyncResult};
+use crate::types::{
+    AppSettings, BackupEntry, DestinationStatus, ItemType, SyncProgress, SyncResult,
+};
 
 /// Global lock to prevent concurrent rsync runs.
 ///
@@ -21,94 +35,1788 @@ use crate::types::{AppSettings, BackupEntry, SyncResult};
 /// to serialize all sync operations.
 static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
 
+/// Set while a coalesced sync waits for the running one to finish.
+static SYNC_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Last sync to each destination path since the app started.
+static DESTINATION_SYNCS: std::sync::Mutex<BTreeMap<String, LastSync>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+/// What `DESTINATION_SYNCS` remembers about one destination.
+#[derive(Debug, Clone, Default)]
+struct LastSync {
+    succeeded_at: Option<chrono::DateTime<chrono::Utc>>,
+    exit_code: Option<i32>,
+}
+
+/// How often a queued sync checks whether the running one has finished.
+const PENDING_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// What `coalesce_sync` did with a request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Coalesced<T> {
+    /// No sync was running, so this one ran right away
+    Ran(T),
+    /// A sync was running; this request runs once it finishes, or was
+    /// folded into the run already queued
+    Queued,
+}
+
 /// Returns true if a sync operation is currently in progress.
 pub fn is_sync_running() -> bool {
     SYNC_RUNNING.load(Ordering::Relaxed)
 }
 
+/// Entries included in manual syncs, i.e. those not disabled by the user.
+pub fn enabled_entries(entries: &[BackupEntry]) -> Vec<BackupEntry> {
+    entries.iter().filter(|e| e.enabled).cloned().collect()
+}
+
+/// Ids of the `entries` a successful sync backed up, leaving out those
+/// whose path is in `result.skipped_paths`, so they are not stamped synced.
+pub fn synced_entry_ids(entries: &[BackupEntry], result: &SyncResult) -> Vec<Uuid> {
+    entries
+        .iter()
+        .filter(|e| !result.skipped_paths.contains(&e.path))
+        .map(|e| e.id)
+        .collect()
+}
+
+/// Entries included in periodic (scheduled) syncs.
+///
+/// Scheduled syncs skip disabled entries, like manual syncs, and also
+/// entries the user marked as on-demand only.
+pub fn scheduled_entries(entries: &[BackupEntry]) -> Vec<BackupEntry> {
+    entries
+        .iter()
+        .filter(|e| e.enabled && e.scheduled)
+        .cloned()
+        .collect()
+}
+
+/// Placeholder shown in previewed argv instead of the temporary filelist path.
+pub const FILELIST_PLACEHOLDER: &str = "<filelist>";
+
+/// Placeholder shown in previewed argv instead of the global exclude file path.
+pub const EXCLUDE_FILE_PLACEHOLDER: &str = "<excludes>";
+
+/// What the next sync would do, computed without touching the destination.
+#[derive(Debug, Clone, Serialize)]
+pub struct Preflight {
+    /// Destination directory rsync would write to
+    pub destination: String,
+    /// Rsync command line for each pass, with the filelist shown as `<filelist>`
+    pub argv: Vec<Vec<String>>,
+    /// Validation of the entry paths
+    pub validation: validation::ValidationReport,
+    /// Estimated total source size in bytes
+    pub estimated_bytes: u64,
+}
+
+/// One filelist path in a `SyncPlan`, as it stands on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedEntry {
+    pub path: String,
+    pub item_type: ItemType,
+    pub exists: bool,
+    /// Passes validation: absolute, found, and its metadata is readable
+    pub readable: bool,
+}
+
+/// What the next sync covers: the resolved filelist, where it goes, and
+/// what validation would flag.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPlan {
+    pub destination: String,
+    pub entries: Vec<PlannedEntry>,
+    pub issues: validation::ValidationReport,
+}
+
+/// Rsync options derived from the entries and destination, for a transfer
+/// from `root` (`None` is `/`).
+///
+/// Entries are expected already rebased onto `root`; the protected paths
+/// are rebased here so their excludes stay anchored at the transfer root.
+fn rsync_options(
+    entries: &[BackupEntry],
+    destination: &str,
+    root: Option<&std::path::Path>,
+) -> executor::RsyncOptions {
+    let cloud_storage_dir = crate::types::default_cloud_storage_dir();
+    let (cloud_storage_dir, destination) = match root {
+        Some(root) => (
+            cloud_storage_dir.map(|dir| layout::rebase(&dir.to_string_lossy(), root).into()),
+            layout::rebase(destination, root),
+        ),
+        None => (cloud_storage_dir, destination.to_string()),
+    };
+    executor::RsyncOptions {
+        excludes: filelist::protected_excludes(entries, &destination, cloud_storage_dir.as_deref()),
+        source_root: root.map(|root| root.to_string_lossy().to_string()),
+        ..Default::default()
+    }
+}
+
+/// A single rsync invocation covering entries that share the same options.
+#[derive(Debug, Clone)]
+pub struct SyncPass {
+    pub entries: Vec<BackupEntry>,
+    pub options: executor::RsyncOptions,
+}
+
+/// Split entries into rsync passes by their effective per-entry options.
+///
+/// An entry's `compress` override wins over the global setting, and its
+/// `excludes` and `includes` apply only to its own pass, so one entry's
+/// patterns never filter another entry. Entries that end up with the same options share
+/// one pass. Passes are ordered by the first entry that needs them, so a
+/// uniform list yields a single pass. Disabled entries get no pass.
+///
+/// Under the `RelativeToHome` layout, entries inside the home directory get
+/// passes of their own, transferred from home: their paths are rebased onto
+/// it (see `layout::rebase`) and `source_root` is set. With `mirror_deletes`,
+/// a pass for the whole home directory mirrors onto the destination root,
+/// so it protects the other passes' backups, the sync log and the manifest.
+pub fn plan_passes(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    destination: &str,
+) -> Vec<SyncPass> {
+    plan_passes_from(
+        entries,
+        settings,
+        destination,
+        layout::home_dir().as_deref(),
+    )
+}
+
+/// `plan_passes` with the home directory passed in.
+fn plan_passes_from(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    destination: &str,
+    home: Option<&std::path::Path>,
+) -> Vec<SyncPass> {
+    type Group<'a> = (
+        bool,
+        &'a [String],
+        &'a [String],
+        Option<std::path::PathBuf>,
+        Vec<BackupEntry>,
+    );
+    let mut groups: Vec<Group<'_>> = Vec::new();
+    for entry in entries.iter().filter(|e| e.enabled) {
+        let compress = entry.compress.unwrap_or(settings.compress);
+        let excludes = entry.excludes.as_slice();
+        let includes = entry.includes.as_slice();
+        let root = layout::transfer_root(&entry.path, settings.path_layout, home);
+        let entry = match &root {
+            Some(root) => BackupEntry {
+                path: layout::rebase(&entry.path, root),
+                ..entry.clone()
+            },
+            None => entry.clone(),
+        };
+        match groups.iter_mut().find(|(c, e, i, r, _)| {
+            *c == compress && *e == excludes && *i == includes && *r == root
+        }) {
+            Some((_, _, _, _, group)) => group.push(entry),
+            None => groups.push((compress, excludes, includes, root, vec![entry])),
+        }
+    }
+
+    let mut passes = groups
+        .into_iter()
+        .map(|(compress, entry_excludes, includes, root, entries)| {
+            let mut options = executor::RsyncOptions {
+                compress,
+                compress_level: settings.compress_level,
+                checksum: settings.verify_checksums,
+                follow_symlinks: settings.follow_symlinks,
+                delete: settings.mirror_deletes,
+                itemize_changes: true,
+                includes: includes.to_vec(),
+                ..rsync_options(&entries, destination, root.as_deref())
+            };
+            for pattern in entry_excludes {
+                if !options.excludes.contains(pattern) {
+                    options.excludes.push(pattern.clone());
+                }
+            }
+            SyncPass { entries, options }
+        })
+        .collect::<Vec<_>>();
+    if settings.mirror_deletes {
+        protect_root_passes(&mut passes);
+    }
+    passes
+}
+
+/// Whether `pass` transfers its whole source root (an entry for home under
+/// `RelativeToHome`, or `/`), and so mirrors onto the destination itself.
+fn covers_destination_root(pass: &SyncPass) -> bool {
+    pass.entries.iter().any(|e| e.path == "/." || e.path == "/")
+}
+
+/// Shield what other writers keep at the destination root from the
+/// `--delete` of a pass that covers it: the sync log, the manifest, and the
+/// top-level directory of every other pass's backups.
+fn protect_root_passes(passes: &mut [SyncPass]) {
+    let protects: Vec<Option<Vec<String>>> = passes
+        .iter()
+        .enumerate()
+        .map(|(i, pass)| {
+            covers_destination_root(pass).then(|| {
+                let mut protects = vec![
+                    format!("/{}", changelog::CHANGELOG_FILE),
+                    format!("/{}", crate::manifest::MANIFEST_FILE),
+                ];
+                let others = passes.iter().enumerate().filter(|(j, _)| *j != i);
+                for (_, other) in others {
+                    for entry in &other.entries {
+                        let Some(top) =
+                            std::path::Path::new(&entry.path)
+                                .components()
+                                .find_map(|c| match c {
+                                    std::path::Component::Normal(name) => {
+                                        Some(name.to_string_lossy())
+                                    }
+                                    _ => None,
+                                })
+                        else {
+                            continue;
+                        };
+                        let pattern = format!("/{top}/***");
+                        if !protects.contains(&pattern) {
+                            protects.push(pattern);
+                        }
+                    }
+                }
+                protects
+            })
+        })
+        .collect();
+    for (pass, protects) in passes.iter_mut().zip(protects) {
+        if let Some(protects) = protects {
+            pass.options.protects = protects;
+        }
+    }
+}
+
+/// Run the filelist and validation layers and build the rsync command lines
+/// without executing them.
+///
+/// Unlike `execute_sync`, this neither creates the destination directory nor
+/// takes the sync lock, so it is safe to call with unsaved settings.
+pub fn preflight(entries: &[BackupEntry], settings: &AppSettings) -> Result<Preflight> {
+    let destination = settings.destination_path()?;
+
+    let filelist_file = filelist::generate_filelist(entries)?;
+    let paths = filelist::read_filelist(filelist_file.path())?;
+    let validation = validation::validate_filelist(&paths);
+
+    let exclude_from = settings
+        .global_excludes
+        .iter()
+        .any(|p| !p.trim().is_empty())
+        .then(|| EXCLUDE_FILE_PLACEHOLDER.to_string());
+    let argv = plan_passes(entries, settings, &destination)
+        .into_iter()
+        .map(|pass| {
+            let options = executor::RsyncOptions {
+                exclude_from: exclude_from.clone(),
+                ..pass.options
+            };
+            let mut argv = vec![settings.rsync_program().to_string()];
+            argv.extend(executor::build_rsync_args(
+                FILELIST_PLACEHOLDER,
+                &destination,
+                &options,
+            ));
+            argv
+        })
+        .collect();
+
+    Ok(Preflight {
+        destination,
+        argv,
+        validation,
+        estimated_bytes: validation::estimate_size(&paths),
+    })
+}
+
+/// Run the filelist and validation layers and report every path the next
+/// sync would cover, without running rsync.
+///
+/// Like `preflight`, this never creates the destination, so paths that are
+/// all missing are reported rather than failing the plan.
+pub fn sync_plan(entries: &[BackupEntry], settings: &AppSettings) -> Result<SyncPlan> {
+    let destination = settings.destination_path()?;
+
+    let filelist_file = filelist::generate_filelist(entries)?;
+    let paths = filelist::read_filelist(filelist_file.path())?;
+    let issues = validation::validate_filelist(&paths);
+
+    let planned = paths
+        .into_iter()
+        .map(|path| {
+            let item_type = entries
+                .iter()
+                .find(|e| e.path == path)
+                .map_or(ItemType::File, |e| e.item_type);
+            PlannedEntry {
+                item_type,
+                exists: std::path::Path::new(&path).exists(),
+                readable: validation::validate_path(&path) == validation::PathValidation::Valid,
+                path,
+            }
+        })
+        .collect();
+
+    Ok(SyncPlan {
+        destination,
+        entries: planned,
+        issues,
+    })
+}
+
+/// Combine the results of several rsync passes into one.
+///
+/// Each pass's output was already cut to `max_output_lines`; the combined
+/// output is cut again, so a sync split into passes keeps no more than one.
+fn merge_results(results: Vec<SyncResult>, max_output_lines: Option<usize>) -> Option<SyncResult> {
+    let mut merged = results.into_iter().reduce(|mut acc, next| {
+        acc.files_transferred += next.files_transferred;
+        acc.dirs_transferred += next.dirs_transferred;
+        acc.bytes_transferred += next.bytes_transferred;
+        acc.created += next.created;
+        acc.updated += next.updated;
+        acc.deleted += next.deleted;
+        acc.unchanged += next.unchanged;
+        acc.stdout.push_str(&next.stdout);
+        acc.stderr.push_str(&next.stderr);
+        acc.warnings.extend(next.warnings);
+        acc.truncated |= next.truncated;
+        for path in next.skipped_paths {
+            if !acc.skipped_paths.contains(&path) {
+                acc.skipped_paths.push(path);
+            }
+        }
+        if next.exit_code != 0 {
+            acc.exit_code = next.exit_code;
+        }
+        acc.synced_at = next.synced_at;
+        acc
+    })?;
+    if let Some(max_lines) = max_output_lines {
+        let stdout_cut = executor::keep_last_lines(&mut merged.stdout, max_lines);
+        let stderr_cut = executor::keep_last_lines(&mut merged.stderr, max_lines);
+        merged.truncated |= stdout_cut || stderr_cut;
+    }
+    Some(merged)
+}
+
 /// Execute the full sync pipeline: generate filelist, validate, run rsync.
 ///
-/// This is the main entry point used by commands and webhook handlers.
-/// Only one sync operation can run at a time — concurrent calls are
-/// rejected with `ShrikeError::SyncFailed`.
-pub fn execute_sync(entries: &[BackupEntry], settings: &AppSettings) -> Result<SyncResult> {
-    // Acquire the sync lock (compare-and-swap false → true)
-    if SYNC_RUNNING
+/// Syncs to the primary destination of `settings` only; commands and
+/// webhook handlers go through `execute_sync_all` to cover every
+/// destination. Only one sync operation can run at a time — concurrent
+/// calls are rejected with `ShrikeError::SyncBusy`.
+///
+/// With `dry_run`, rsync runs with `--dry-run`: the result lists what would
+/// be transferred and the destination is neither created nor written to.
+/// A dry run still takes the sync lock, so it never interleaves with a
+/// real sync.
+pub fn execute_sync(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+) -> Result<SyncResult> {
+    with_sync_lock(|| execute_sync_inner(entries, settings, dry_run, None))
+}
+
+/// Sync `entries` to every configured destination, primary first.
+///
+/// Runs the pipeline of `execute_sync` once per destination under a single
+/// sync lock. A failing destination does not stop the others: the results
+/// of those that succeeded are returned, in order, and the run fails only
+/// if every destination failed.
+pub fn execute_sync_all(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+) -> Result<Vec<SyncResult>> {
+    with_sync_lock(|| sync_destinations(entries, settings, dry_run, None))
+}
+
+/// Like `execute_sync_all`, but calls `on_progress` while rsync runs.
+///
+/// Uses `--info=progress2` when the installed rsync supports it; older
+/// rsyncs still report each transferred file. The encrypted backend does not
+/// report progress.
+pub fn execute_sync_all_with_progress(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    on_progress: &mut dyn FnMut(&SyncProgress),
+) -> Result<Vec<SyncResult>> {
+    with_sync_lock(|| sync_destinations(entries, settings, dry_run, Some(on_progress)))
+}
+
+/// Like `execute_sync_all` (never a dry run), but a request made while a
+/// sync is running is queued instead of rejected; see `coalesce_sync`.
+///
+/// `run` is handed the sync to perform and returns whatever the caller
+/// wants from it, so work around the sync (history, notifications) happens
+/// under the same lock whether it runs now or queued. `run` loads the
+/// entries and settings it syncs once it holds the lock, so a queued run
+/// picks up triggers and edits made while it waited.
+pub fn execute_sync_all_coalesced<T>(
+    run: impl FnOnce(&dyn Fn(&[BackupEntry], &AppSettings) -> Result<Vec<SyncResult>>) -> T
+    + Send
+    + 'static,
+) -> Coalesced<T> {
+    coalesce_sync(move || {
+        run(&|entries, settings| sync_destinations(entries, settings, false, None))
+    })
+}
+
+/// Run `f` under the sync lock, or queue it if a sync is already running.
+///
+/// At most one run is queued: requests arriving while one is pending are
+/// folded into it, so a burst of triggers during a sync yields exactly one
+/// more sync. The queued run waits on a background thread and its result
+/// is dropped.
+fn coalesce_sync<T>(f: impl FnOnce() -> T + Send + 'static) -> Coalesced<T> {
+    if let Some(_guard) = try_sync_lock() {
+        return Coalesced::Ran(f());
+    }
+    if SYNC_PENDING
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
         .is_err()
     {
-        return Err(ShrikeError::SyncFailed(
-            "a sync operation is already in progress".to_string(),
+        return Coalesced::Queued;
+    }
+    std::thread::spawn(move || {
+        let _guard = loop {
+            if let Some(guard) = try_sync_lock() {
+                break guard;
+            }
+            std::thread::sleep(PENDING_POLL_INTERVAL);
+        };
+        // Requests from here on queue a run after this one
+        SYNC_PENDING.store(false, Ordering::SeqCst);
+        f();
+    });
+    Coalesced::Queued
+}
+
+/// Combine per-destination results into one summary for callers that
+/// report a single `SyncResult`.
+///
+/// Every destination receives the same files, so the counts and output are
+/// those of the first result (the primary's, unless it failed) rather than
+/// a sum. Warnings, skipped paths, truncation and a non-zero exit code are
+/// carried over from every destination.
+pub fn merge_destination_results(results: Result<Vec<SyncResult>>) -> Result<SyncResult> {
+    let mut results = results?.into_iter();
+    let mut summary = results
+        .next()
+        .ok_or_else(|| ShrikeError::SyncFailed("no destinations configured".to_string()))?;
+    for next in results {
+        summary.warnings.extend(next.warnings);
+        summary.truncated |= next.truncated;
+        for path in next.skipped_paths {
+            if !summary.skipped_paths.contains(&path) {
+                summary.skipped_paths.push(path);
+            }
+        }
+        if summary.exit_code == 0 {
+            summary.exit_code = next.exit_code;
+        }
+        summary.synced_at = next.synced_at;
+    }
+    Ok(summary)
+}
+
+/// Sync to each destination in turn, carrying on past failures.
+///
+/// Returns the results of the destinations that succeeded, with a warning
+/// on the first naming each one that failed, or the first error if none
+/// succeeded.
+fn sync_destinations(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    mut on_progress: Option<&mut dyn FnMut(&SyncProgress)>,
+) -> Result<Vec<SyncResult>> {
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for destination in settings.destinations() {
+        let on_progress = on_progress
+            .as_mut()
+            .map(|f| &mut **f as &mut dyn FnMut(&SyncProgress));
+        match execute_sync_inner(
+            entries,
+            &settings.for_destination(&destination),
+            dry_run,
+            on_progress,
+        ) {
+            Ok(result) => results.push(result),
+            Err(e) => failures.push((destination.gdrive_path, e)),
+        }
+    }
+    let Some(first) = results.first_mut() else {
+        return Err(failures.into_iter().next().map_or_else(
+            || ShrikeError::SyncFailed("no destinations configured".to_string()),
+            |(_, e)| e,
+        ));
+    };
+    first.warnings.extend(
+        failures
+            .iter()
+            .map(|(gdrive_path, e)| format!("sync to {gdrive_path} failed: {e}")),
+    );
+    Ok(results)
+}
+
+/// Sync an ad-hoc list of paths without storing them as entries.
+///
+/// Each path becomes a transient `BackupEntry` whose type is read from disk;
+/// missing paths are typed as files and rejected by validation as usual.
+pub fn execute_sync_with_paths(paths: &[String], settings: &AppSettings) -> Result<SyncResult> {
+    let entries: Vec<BackupEntry> = paths
+        .iter()
+        .map(|path| {
+            let item_type = if std::path::Path::new(path).is_dir() {
+                ItemType::Directory
+            } else {
+                ItemType::File
+            };
+            BackupEntry::new(path.clone(), item_type)
+        })
+        .collect();
+    merge_destination_results(execute_sync_all(&entries, settings, false))
+}
+
+/// Copy the backed-up versions of `paths` back to their original locations.
+///
+/// Paths without a copy in the backup are skipped; if none have one, this
+/// fails with `ShrikeError::SyncFailed`. Shares the sync lock, so a restore
+/// never overlaps a sync.
+pub fn restore_paths(paths: &[String], settings: &AppSettings) -> Result<SyncResult> {
+    with_sync_lock(|| {
+        let destination = settings.destination_path()?;
+        let entries: Vec<BackupEntry> = paths
+            .iter()
+            .filter(|path| {
+                layout::backup_path_for(path, &destination, settings.path_layout).exists()
+            })
+            .map(|path| BackupEntry::new(path.clone(), ItemType::File))
+            .collect();
+        if entries.is_empty() {
+            return Err(ShrikeError::SyncFailed(
+                "no backed-up paths to restore".to_string(),
+            ));
+        }
+
+        restore::run_restore(&entries, &destination, settings, false)
+    })
+}
+
+/// Decrypt this machine's encrypted archive with `identity` and restore
+/// `entries` to their original locations.
+///
+/// Only the archive members backing up `entries` are unpacked, into a
+/// staging directory that is then restored like a plain backup: `dry_run`
+/// only reports what would be restored, and local files newer than their
+/// archived copy are only overwritten with `force`. Shares the sync lock,
+/// so a restore never overlaps a sync.
+pub fn restore_encrypted(
+    identity: &str,
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    force: bool,
+) -> Result<SyncResult> {
+    with_sync_lock(|| {
+        let destination = settings.destination_path()?;
+        let staging = tempfile::tempdir()?;
+        encrypted::restore_encrypted_archive(
+            &std::path::Path::new(&destination).join(encrypted::ARCHIVE_FILE),
+            identity,
+            entries,
+            settings.path_layout,
+            staging.path(),
+        )?;
+
+        let staged = staging.path().to_string_lossy();
+        let entries: Vec<BackupEntry> = entries
+            .iter()
+            .filter(|e| layout::backup_path_for(&e.path, &staged, settings.path_layout).exists())
+            .cloned()
+            .collect();
+        if entries.is_empty() {
+            return Err(ShrikeError::SyncFailed(
+                "no backed-up paths to restore".to_string(),
+            ));
+        }
+        if !dry_run && !force {
+            restore::refuse_newer_local_files(&entries, &staged, settings)?;
+        }
+        restore::run_restore(&entries, &staged, settings, dry_run)
+    })
+}
+
+/// Compare every entry with its backup at the primary destination, using an
+/// itemized `--checksum` dry run so nothing is transferred.
+///
+/// Encrypted backups are a single archive and can't be compared file by
+/// file, so they fail with `ShrikeError::InvalidSettings`. Shares the sync
+/// lock, so the comparison never sees a half-finished sync.
+pub fn verify_sync(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+) -> Result<executor::VerifyReport> {
+    if settings.encrypt {
+        return Err(ShrikeError::InvalidSettings(
+            "encrypted backups cannot be verified file by file".to_string(),
         ));
     }
 
-    // Ensure we always release the lock, even on error/panic
-    let result = execute_sync_inner(entries, settings);
-    SYNC_RUNNING.store(false, Ordering::SeqCst);
-    result
+    with_sync_lock(|| {
+        let destination = settings.destination_path()?;
+        let filelist_file = filelist::generate_filelist(entries)?;
+        validation::dry_run_check_file(filelist_file.path(), &destination)?;
+
+        let run_options = executor::RunOptions {
+            umask: settings.umask()?,
+            program: settings.rsync_binary.clone(),
+            timeout: settings.sync_timeout(),
+            max_output_lines: settings.output_line_cap(),
+        };
+        let exclude_file = filelist::generate_exclude_file(&settings.global_excludes)?;
+        let exclude_from = exclude_file
+            .as_ref()
+            .map(filelist::filelist_path_str)
+            .transpose()?;
+        let mut report = executor::VerifyReport::default();
+        for pass in plan_passes(entries, settings, &destination) {
+            let pass_filelist = filelist::generate_filelist(&pass.entries)?;
+            let filelist_path = filelist::filelist_path_str(&pass_filelist)?;
+            let options = executor::RsyncOptions {
+                checksum: true,
+                dry_run: true,
+                itemize: true,
+                // Deletions would show up as `*deleting` lines, not as drift
+                delete: false,
+                exclude_from: exclude_from.clone(),
+                ..pass.options
+            };
+            let args = executor::build_rsync_args(&filelist_path, &destination, &options);
+            let result = executor::run_rsync(&args, &run_options)?;
+            report.extend(executor::parse_itemized_output(&result.stdout));
+        }
+        Ok(report)
+    })
+}
+
+/// Run `f` while holding the global sync lock.
+///
+/// Concurrent callers are rejected with `ShrikeError::SyncBusy` instead
+/// of waiting.
+fn with_sync_lock<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let Some(_guard) = try_sync_lock() else {
+        return Err(ShrikeError::SyncBusy);
+    };
+    f()
+}
+
+/// Take the sync lock if it is free. The guard releases it even on
+/// error/panic.
+pub fn try_sync_lock() -> Option<SyncLockGuard> {
+    // Acquire the sync lock (compare-and-swap false → true). Only build the
+    // guard on success: dropping it releases the lock.
+    match SYNC_RUNNING.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(_) => Some(SyncLockGuard),
+        Err(_) => None,
+    }
+}
+
+/// Releases the sync lock when dropped, including while unwinding.
+pub struct SyncLockGuard;
+
+impl Drop for SyncLockGuard {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Delay before the first retry; doubled for each one after.
+const RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Upper bound on the delay between retries.
+const RETRY_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Returns true if an rsync exit code is one of `transient_codes`
+/// (normally `AppSettings::transient_exit_codes`).
+pub fn is_transient_exit_code(code: i32, transient_codes: &[i32]) -> bool {
+    transient_codes.contains(&code)
+}
+
+/// Decide whether to retry after `error`, given the retries already made.
+///
+/// Only rsync failures with an exit code in `settings.transient_exit_codes`
+/// are retried, at most `settings.max_sync_retries` times.
+fn should_retry(error: &ShrikeError, retries: u32, settings: &AppSettings) -> bool {
+    retries < settings.max_sync_retries
+        && matches!(error, ShrikeError::RsyncError { code, .. }
+            if is_transient_exit_code(*code, &settings.transient_exit_codes))
+}
+
+/// Exponential backoff: the delay before retry number `retries + 1`.
+fn retry_delay(retries: u32) -> std::time::Duration {
+    RETRY_BASE_DELAY
+        .checked_mul(2u32.saturating_pow(retries))
+        .map_or(RETRY_MAX_DELAY, |delay| delay.min(RETRY_MAX_DELAY))
 }
 
 /// Inner sync logic, separated so the lock guard in `execute_sync` stays clean.
-fn execute_sync_inner(entries: &[BackupEntry], settings: &AppSettings) -> Result<SyncResult> {
+///
+/// Transient rsync failures are retried up to `max_sync_retries` times with
+/// exponential backoff. Every real sync that gets as far as an existing
+/// destination is recorded in the destination's sync log; a log write
+/// failure never fails the sync.
+fn execute_sync_inner(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    mut on_progress: Option<&mut dyn FnMut(&SyncProgress)>,
+) -> Result<SyncResult> {
+    crate::logging::log(
+        crate::logging::LogLevel::Info,
+        format_args!(
+            "sync started: {} entries to {}{}",
+            entries.len(),
+            settings.destination_path().unwrap_or_default(),
+            if dry_run { " (dry run)" } else { "" }
+        ),
+    );
+    let mut retries = 0;
+    let outcome = loop {
+        let progress = on_progress
+            .as_mut()
+            .map(|f| &mut **f as &mut dyn FnMut(&SyncProgress));
+        let outcome = run_backend(entries, settings, dry_run, progress);
+        match &outcome {
+            Err(e) if should_retry(e, retries, settings) => {
+                let delay = retry_delay(retries);
+                retries += 1;
+                crate::logging::log(
+                    crate::logging::LogLevel::Warn,
+                    format_args!(
+                        "{e}; retrying in {}s ({retries}/{})",
+                        delay.as_secs(),
+                        settings.max_sync_retries
+                    ),
+                );
+                std::thread::sleep(delay);
+            }
+            _ => break outcome,
+        }
+    };
+    match &outcome {
+        Ok(result) => crate::logging::log(
+            crate::logging::LogLevel::Info,
+            format_sync_completed(result),
+        ),
+        Err(e) => crate::logging::log(
+            crate::logging::LogLevel::Warn,
+            format_args!("sync failed: {e}"),
+        ),
+    }
+    if dry_run {
+        return outcome;
+    }
+    if let Ok(destination) = settings.destination_path() {
+        record_destination_sync(&destination, &outcome);
+        let destination = std::path::Path::new(&destination);
+        if destination.is_dir() {
+            let line = changelog::format_summary_line(chrono::Utc::now(), &outcome);
+            if let Err(e) = changelog::append_summary(destination, &line) {
+                crate::logging::log(
+                    crate::logging::LogLevel::Warn,
+                    format_args!("failed to append sync log: {e}"),
+                );
+            }
+        }
+    }
+    outcome
+}
+
+/// Remember `outcome` as the last sync to `destination`.
+fn record_destination_sync(destination: &str, outcome: &Result<SyncResult>) {
+    let mut syncs = DESTINATION_SYNCS.lock().unwrap_or_else(|e| e.into_inner());
+    let last = syncs.entry(destination.to_string()).or_default();
+    last.exit_code = Some(match outcome {
+        Ok(result) => {
+            last.succeeded_at = Some(result.synced_at);
+            result.exit_code
+        }
+        Err(ShrikeError::RsyncError { code, .. }) => *code,
+        Err(_) => -1,
+    });
+}
+
+/// Every configured destination, primary first, with its last sync and
+/// whether it is reachable (see `validation::destination_reachable`).
+///
+/// Never creates a destination, so it is safe for status checks.
+pub fn destination_statuses(settings: &AppSettings) -> Result<Vec<DestinationStatus>> {
+    let syncs = DESTINATION_SYNCS.lock().unwrap_or_else(|e| e.into_inner());
+    settings
+        .destinations()
+        .iter()
+        .map(|destination| {
+            let path = settings.for_destination(destination).destination_path()?;
+            let last = syncs.get(&path).cloned().unwrap_or_default();
+            let reachable = validation::destination_reachable(
+                std::path::Path::new(&destination.gdrive_path),
+                std::path::Path::new(&path),
+            );
+            Ok(DestinationStatus {
+                path,
+                last_synced: last.succeeded_at,
+                last_exit_code: last.exit_code,
+                reachable,
+            })
+        })
+        .collect()
+}
+
+/// Delete this machine's backup from the primary destination, e.g. when
+/// decommissioning it, and return the bytes freed.
+///
+/// `confirm` must equal `machine_name`. The path is `destination_path`, whose
+/// component checks (no `..` or `/`) keep it inside
+/// `<gdrive_path>/<backup_dir_name>/`; anything that isn't exactly two levels
+/// below a drive root other than `/` is still refused. Takes the sync lock so
+/// it never races a sync writing to the same folder.
+pub fn purge_machine_backup(settings: &AppSettings, confirm: &str) -> Result<u64> {
+    if confirm != settings.machine_name {
+        return Err(ShrikeError::PurgeRefused(format!(
+            "confirmation does not match machine name {}",
+            settings.machine_name
+        )));
+    }
     let destination = settings.destination_path()?;
+    let path = std::path::Path::new(&destination);
+    let root = std::path::Path::new(settings.gdrive_path.trim_end_matches('/'));
+    if root.parent().is_none() || path.parent().and_then(|p| p.parent()) != Some(root) {
+        return Err(ShrikeError::PurgeRefused(format!(
+            "{destination} is not a machine folder inside {}",
+            settings.gdrive_path
+        )));
+    }
+
+    with_sync_lock(|| {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let freed = validation::dir_size(path)?;
+        std::fs::remove_dir_all(path)?;
+        crate::logging::log(
+            crate::logging::LogLevel::Warn,
+            format_args!("purged machine backup {destination} ({freed} bytes)"),
+        );
+        Ok(freed)
+    })
+}
+
+/// The log line for a finished sync, with its transfer and change counts.
+pub fn format_sync_completed(result: &SyncResult) -> String {
+    format!(
+        "sync completed: exit={} files={} dirs={} bytes={} created={} updated={} deleted={} skipped={}",
+        result.exit_code,
+        result.files_transferred,
+        result.dirs_transferred,
+        result.bytes_transferred,
+        result.created,
+        result.updated,
+        result.deleted,
+        result.skipped_paths.len()
+    )
+}
+
+/// Make one sync attempt with the configured backend.
+fn run_backend(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    on_progress: Option<&mut dyn FnMut(&SyncProgress)>,
+) -> Result<SyncResult> {
+    match on_progress {
+        _ if settings.encrypt => encrypted_pipeline(entries, settings, dry_run),
+        Some(on_progress) => {
+            let progress2 = capabilities::rsync_capabilities(settings.rsync_program())
+                .is_some_and(|caps| caps.supports_progress2());
+            run_pipeline(entries, settings, dry_run, progress2, |args, options| {
+                executor::run_rsync_with_progress(args, options, &mut *on_progress)
+            })
+        }
+        None => run_pipeline(entries, settings, dry_run, false, executor::run_rsync),
+    }
+}
+
+/// The encrypted-archive counterpart of `run_pipeline`: same filelist and
+/// validation layers, then one age-encrypted tar instead of rsync passes.
+fn encrypted_pipeline(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+) -> Result<SyncResult> {
+    let destination = settings.destination_path()?;
+    let recipient = settings.encryption_recipient.as_deref().ok_or_else(|| {
+        ShrikeError::InvalidSettings("encryption requires a recipient key".to_string())
+    })?;
 
-    // Layer 1: Generate filelist
     let filelist_file = filelist::generate_filelist(entries)?;
-    let filelist_path = filelist::filelist_path_str(&filelist_file)?;
+    if dry_run {
+        let report = validation::dry_run_check_file(filelist_file.path(), &destination)?;
+        let entries = entries_to_sync(entries, &report);
+        return with_skipped_paths(encrypted::preview_archive(&entries, settings), &report);
+    }
+    let report = validation::pre_sync_check_file(
+        filelist_file.path(),
+        std::path::Path::new(&settings.gdrive_path),
+        &destination,
+        !settings.skip_space_check,
+    )?;
 
-    // Layer 2: Validate
-    let paths = filelist::read_filelist(filelist_file.path())?;
-    let _report = validation::pre_sync_check(&paths, &destination)?;
+    let entries = entries_to_sync(entries, &report);
+    with_skipped_paths(
+        encrypted::write_encrypted_archive(
+            &entries,
+            settings,
+            std::path::Path::new(&destination),
+            recipient,
+        ),
+        &report,
+    )
+}
 
-    // Layer 3: Execute rsync
-    let args = executor::build_rsync_args(&filelist_path, &destination);
-    let result = executor::run_rsync(&args)?;
+/// The enabled `entries` validation accepted, each path once, so the
+/// transfer holds exactly what `report` says will be synced.
+fn entries_to_sync(
+    entries: &[BackupEntry],
+    report: &validation::ValidationReport,
+) -> Vec<BackupEntry> {
+    let skipped = report.skipped_paths();
+    let mut seen = std::collections::HashSet::new();
+    entries
+        .iter()
+        .filter(|e| e.enabled && !skipped.contains(&e.path) && seen.insert(e.path.as_str()))
+        .cloned()
+        .collect()
+}
 
+/// Attach the paths validation left out to a sync's result, logging them
+/// once so partial syncs are visible.
+fn with_skipped_paths(
+    result: Result<SyncResult>,
+    report: &validation::ValidationReport,
+) -> Result<SyncResult> {
+    let mut result = result?;
+    result.skipped_paths = report.skipped_paths();
+    if !result.skipped_paths.is_empty() {
+        crate::logging::log(
+            crate::logging::LogLevel::Warn,
+            format_args!("skipping paths: {}", report.summary()),
+        );
+    } else {
+        crate::logging::log(
+            crate::logging::LogLevel::Debug,
+            format_args!("validation: {}", report.summary()),
+        );
+    }
     Ok(result)
 }
 
+/// The sync pipeline with the rsync invocation injected, so tests can fail
+/// or panic mid-pipeline without a real rsync.
+///
+/// Filelist temp files are owned by this frame and removed on drop, which
+/// also runs when `run` panics.
+fn run_pipeline(
+    entries: &[BackupEntry],
+    settings: &AppSettings,
+    dry_run: bool,
+    progress: bool,
+    mut run: impl FnMut(&[String], &executor::RunOptions) -> Result<SyncResult>,
+) -> Result<SyncResult> {
+    let destination = settings.destination_path()?;
+
+    // Layer 1: Generate filelist
+    let filelist_file = filelist::generate_filelist(entries)?;
+
+    // Layer 2: Validate (streamed, so huge filelists aren't held in memory)
+    let report = if dry_run {
+        validation::dry_run_check_file(filelist_file.path(), &destination)?
+    } else {
+        validation::pre_sync_check_file(
+            filelist_file.path(),
+            std::path::Path::new(&settings.gdrive_path),
+            &destination,
+            !settings.skip_space_check,
+        )?
+    };
+
+    // Layer 3: Execute rsync, one pass per distinct set of per-entry options
+    let run_options = executor::RunOptions {
+        umask: settings.umask()?,
+        program: settings.rsync_binary.clone(),
+        timeout: settings.sync_timeout(),
+        max_output_lines: settings.output_line_cap(),
+    };
+    let exclude_file = filelist::generate_exclude_file(&settings.global_excludes)?;
+    let exclude_from = exclude_file
+        .as_ref()
+        .map(filelist::filelist_path_str)
+        .transpose()?;
+    if settings.mirror_deletes && !dry_run {
+        crate::logging::log(
+            crate::logging::LogLevel::Warn,
+            format_args!(
+                "mirror_deletes is on: files deleted from sources will be deleted from {destination}"
+            ),
+        );
+    }
+    let entries = entries_to_sync(entries, &report);
+    let mut results = Vec::new();
+    for pass in plan_passes(&entries, settings, &destination) {
+        let pass_filelist = filelist::generate_filelist(&pass.entries)?;
+        let filelist_path = filelist::filelist_path_str(&pass_filelist)?;
+        let options = executor::RsyncOptions {
+            dry_run,
+            progress,
+            exclude_from: exclude_from.clone(),
+            ..pass.options
+        };
+        let args = executor::build_rsync_args(&filelist_path, &destination, &options);
+        crate::logging::log(
+            crate::logging::LogLevel::Debug,
+            format_args!("running {} {}", settings.rsync_program(), args.join(" ")),
+        );
+        let result = run(&args, &run_options)?;
+        log_rsync_output(&result);
+        results.push(result);
+    }
+
+    with_skipped_paths(
+        merge_results(results, run_options.max_output_lines)
+            .ok_or_else(|| ShrikeError::SyncFailed("no entries to sync".to_string())),
+        &report,
+    )
+}
+
+/// Log a finished rsync pass: its exit code, its stdout at trace level and
+/// any stderr as a warning.
+fn log_rsync_output(result: &SyncResult) {
+    crate::logging::log(
+        crate::logging::LogLevel::Info,
+        format_args!("rsync exited with code {}", result.exit_code),
+    );
+    crate::logging::log(
+        crate::logging::LogLevel::Trace,
+        format_args!("rsync stdout:\n{}", result.stdout),
+    );
+    if !result.stderr.is_empty() {
+        crate::logging::log(
+            crate::logging::LogLevel::Warn,
+            format_args!("rsync stderr:\n{}", result.stderr),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::ItemType;
+    use crate::types::{Destination, PathLayout};
     use std::fs;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
-    fn test_settings(dest: &str) -> AppSettings {
-        AppSettings {
-            gdrive_path: dest.to_string(),
-            backup_dir_name: "Backup".to_string(),
-            machine_name: "TestMac".to_string(),
-            webhook_port: 0,
-            webhook_token: "test".to_string(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".to_string(),
-            language: "auto".to_string(),
+    /// Serializes tests that flip the global sync lock by hand.
+    static SYNC_LOCK_TESTS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn lock_sync_tests() -> std::sync::MutexGuard<'static, ()> {
+        SYNC_LOCK_TESTS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn scheduled_entries_skips_on_demand_entries() {
+        let mut heavy = BackupEntry::new("/Users/me/Movies".into(), ItemType::Directory);
+        heavy.scheduled = false;
+        let light = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        let entries = vec![heavy, light.clone()];
+
+        assert_eq!(scheduled_entries(&entries), vec![light]);
+        // Manual syncs still include on-demand entries
+        assert_eq!(enabled_entries(&entries), entries);
+    }
+
+    #[test]
+    fn synced_entry_ids_leave_out_skipped_paths() {
+        let kept = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        let gone = BackupEntry::new("/Users/me/gone".into(), ItemType::Directory);
+        let result = SyncResult {
+            files_transferred: 1,
+            dirs_transferred: 0,
+            bytes_transferred: 0,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![gone.path.clone()],
+            truncated: false,
+        };
+        assert_eq!(
+            synced_entry_ids(&[kept.clone(), gone], &result),
+            vec![kept.id]
+        );
+    }
+
+    #[test]
+    fn disabled_entries_are_skipped_by_every_sync() {
+        let mut disabled = BackupEntry::new("/Users/me/Movies".into(), ItemType::Directory);
+        disabled.enabled = false;
+        let enabled = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        let entries = vec![disabled, enabled.clone()];
+
+        assert_eq!(enabled_entries(&entries), vec![enabled.clone()]);
+        assert_eq!(scheduled_entries(&entries), vec![enabled]);
+        let passes = plan_passes(
+            &entries,
+            &AppSettings::for_test("/tmp/test_gdrive"),
+            "/dest",
+        );
+        assert_eq!(passes.len(), 1);
+        assert_eq!(passes[0].entries.len(), 1);
+    }
+
+    fn entry_with_compress(path: &str, compress: Option<bool>) -> BackupEntry {
+        let mut entry = BackupEntry::new(path.into(), ItemType::Directory);
+        entry.compress = compress;
+        entry
+    }
+
+    #[test]
+    fn plan_passes_applies_per_entry_compress_override() {
+        let entries = vec![
+            entry_with_compress("/Users/me/notes", Some(true)),
+            entry_with_compress("/Users/me/Movies", Some(false)),
+        ];
+        for global in [false, true] {
+            let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+            settings.compress = global;
+            let passes = plan_passes(&entries, &settings, "/dest");
+            assert_eq!(passes.len(), 2);
+
+            let args_for = |path: &str| {
+                let pass = passes
+                    .iter()
+                    .find(|p| p.entries.iter().any(|e| e.path == path))
+                    .unwrap();
+                executor::build_rsync_args("/tmp/f.txt", "/dest", &pass.options)
+            };
+            assert!(args_for("/Users/me/notes").contains(&"-z".to_string()));
+            assert!(!args_for("/Users/me/Movies").contains(&"-z".to_string()));
         }
     }
 
+    #[test]
+    fn plan_passes_inherits_global_compress() {
+        let entries = vec![
+            entry_with_compress("/a", None),
+            entry_with_compress("/b", Some(true)),
+        ];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.compress = true;
+        let passes = plan_passes(&entries, &settings, "/dest");
+        assert_eq!(passes.len(), 1);
+        assert!(passes[0].options.compress);
+        assert_eq!(passes[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn plan_passes_scopes_entry_excludes_to_their_pass() {
+        let mut claude = BackupEntry::new("/Users/me/.claude".into(), ItemType::Directory);
+        claude.excludes = vec!["node_modules/".into()];
+        let mut cursor = BackupEntry::new("/Users/me/.cursor".into(), ItemType::Directory);
+        cursor.excludes = vec!["node_modules/".into()];
+        let plain = BackupEntry::new("/Users/me/notes".into(), ItemType::Directory);
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let passes = plan_passes(&[claude, plain, cursor], &settings, "/dest");
+        assert_eq!(passes.len(), 2);
+        assert_eq!(
+            passes[0].entries.len(),
+            2,
+            "identical excludes share a pass"
+        );
+        assert_eq!(passes[0].options.excludes, vec!["node_modules/"]);
+        assert!(passes[1].options.excludes.is_empty());
+
+        let args = executor::build_rsync_args("/tmp/f.txt", "/dest", &passes[0].options);
+        assert!(args.contains(&"--exclude=node_modules/".to_string()));
+    }
+
+    #[test]
+    fn plan_passes_applies_compress_level_to_compressed_passes() {
+        let entries = vec![
+            entry_with_compress("/Users/me/notes", Some(true)),
+            entry_with_compress("/Users/me/Movies", None),
+        ];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.compress_level = Some(3);
+        let passes = plan_passes(&entries, &settings, "/dest");
+        let args = executor::build_rsync_args("/tmp/f.txt", "/dest", &passes[0].options);
+        assert!(args.contains(&"--compress-level=3".to_string()));
+        let args = executor::build_rsync_args("/tmp/f.txt", "/dest", &passes[1].options);
+        assert!(!args.iter().any(|a| a.starts_with("--compress")));
+    }
+
+    #[test]
+    fn plan_passes_deletes_only_in_mirror_mode() {
+        let entries = vec![BackupEntry::new(
+            "/Users/me/notes".into(),
+            ItemType::Directory,
+        )];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        assert!(!plan_passes(&entries, &settings, "/dest")[0].options.delete);
+
+        settings.mirror_deletes = true;
+        assert!(plan_passes(&entries, &settings, "/dest")[0].options.delete);
+    }
+
+    #[test]
+    fn plan_passes_scopes_entry_includes_to_their_pass() {
+        let mut notes = BackupEntry::new("/Users/me/notes".into(), ItemType::Directory);
+        notes.includes = vec!["*.md".into()];
+        let zshrc = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        let passes = plan_passes(
+            &[notes, zshrc],
+            &AppSettings::for_test("/tmp/test_gdrive"),
+            "/dest",
+        );
+        assert_eq!(passes.len(), 2);
+        assert_eq!(passes[0].options.includes, vec!["*.md"]);
+        assert!(passes[1].options.includes.is_empty());
+    }
+
+    #[test]
+    fn plan_passes_appends_entry_excludes_after_protected_ones() {
+        let mut home = BackupEntry::new("/Users/me".into(), ItemType::Directory);
+        home.excludes = vec!["/Users/me/backup".into(), "*.log".into()];
+        let passes = plan_passes(
+            &[home],
+            &AppSettings::for_test("/tmp/test_gdrive"),
+            "/Users/me/backup",
+        );
+        // The destination is already protected, so it is not repeated
+        assert_eq!(
+            passes[0].options.excludes,
+            vec!["/Users/me/backup", "*.log"]
+        );
+    }
+
+    #[test]
+    fn plan_passes_keeps_cloud_storage_and_destination_out_of_a_home_entry() {
+        let home = dirs::home_dir().unwrap();
+        let cloud = crate::types::default_cloud_storage_dir().unwrap();
+        let entries = vec![BackupEntry::new(
+            home.to_string_lossy().to_string(),
+            ItemType::Directory,
+        )];
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+
+        for destination in [
+            cloud.join("GoogleDrive-me/My Drive/Backup/TestMac"),
+            home.join("Backups/TestMac"),
+        ] {
+            let destination = destination.to_string_lossy().to_string();
+            let passes = plan_passes(&entries, &settings, &destination);
+            let args = executor::build_rsync_args("/tmp/f.txt", &destination, &passes[0].options);
+            assert!(
+                args.contains(&format!("--exclude={}", cloud.display())),
+                "{args:?}"
+            );
+            assert!(
+                args.contains(&format!("--exclude={destination}")),
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn plan_passes_roots_home_entries_at_home_when_relative() {
+        let home = std::path::Path::new("/Users/me");
+        let entries = vec![
+            BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+            BackupEntry::new("/etc/hosts".into(), ItemType::File),
+            BackupEntry::new("/Users/me/notes".into(), ItemType::Directory),
+        ];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.path_layout = PathLayout::RelativeToHome;
+
+        let passes = plan_passes_from(&entries, &settings, "/dest", Some(home));
+
+        assert_eq!(passes.len(), 2);
+        assert_eq!(passes[0].options.source_root.as_deref(), Some("/Users/me"));
+        let paths: Vec<&str> = passes[0].entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/.zshrc", "/notes"]);
+        assert_eq!(passes[1].options.source_root, None);
+        assert_eq!(passes[1].entries[0].path, "/etc/hosts");
+    }
+
+    #[test]
+    fn plan_passes_protects_other_backups_from_a_mirrored_home_pass() {
+        let home = std::path::Path::new("/Users/me");
+        let entries = vec![
+            BackupEntry::new("/Users/me".into(), ItemType::Directory),
+            BackupEntry::new("/etc/hosts".into(), ItemType::File),
+        ];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.path_layout = PathLayout::RelativeToHome;
+
+        let passes = plan_passes_from(&entries, &settings, "/dest", Some(home));
+        assert!(passes.iter().all(|p| p.options.protects.is_empty()));
+
+        settings.mirror_deletes = true;
+        let passes = plan_passes_from(&entries, &settings, "/dest", Some(home));
+        assert_eq!(passes[0].entries[0].path, "/.");
+        assert_eq!(
+            passes[0].options.protects,
+            ["/shrike-backup.log", "/manifest.json", "/etc/***"]
+        );
+        // Passes below the root only delete inside their own entries
+        assert!(passes[1].options.protects.is_empty());
+    }
+
+    #[test]
+    fn plan_passes_full_layout_ignores_home() {
+        let entries = vec![BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File)];
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let passes = plan_passes_from(
+            &entries,
+            &settings,
+            "/dest",
+            Some(std::path::Path::new("/Users/me")),
+        );
+        assert_eq!(passes[0].options.source_root, None);
+        assert_eq!(passes[0].entries[0].path, "/Users/me/.zshrc");
+    }
+
+    #[test]
+    fn plan_passes_anchors_protected_excludes_at_home() {
+        let home = std::path::Path::new("/Users/me");
+        let entries = vec![BackupEntry::new("/Users/me".into(), ItemType::Directory)];
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.path_layout = PathLayout::RelativeToHome;
+
+        let passes = plan_passes_from(&entries, &settings, "/Users/me/backup", Some(home));
+
+        assert_eq!(passes[0].entries[0].path, "/.");
+        assert!(
+            passes[0].options.excludes.contains(&"/backup".to_string()),
+            "{:?}",
+            passes[0].options.excludes
+        );
+    }
+
+    #[test]
+    fn transient_exit_codes() {
+        let defaults = crate::types::DEFAULT_TRANSIENT_EXIT_CODES;
+        assert!(is_transient_exit_code(11, defaults));
+        assert!(is_transient_exit_code(23, defaults));
+        assert!(is_transient_exit_code(30, defaults));
+        for code in [0, 1, 2, 12, 20, 24, -1] {
+            assert!(!is_transient_exit_code(code, defaults), "code {code}");
+        }
+        assert!(is_transient_exit_code(24, &[24]));
+        assert!(!is_transient_exit_code(23, &[]));
+    }
+
+    #[test]
+    fn should_retry_only_transient_rsync_errors_within_budget() {
+        let rsync = |code| ShrikeError::RsyncError {
+            code,
+            message: String::new(),
+        };
+        let mut settings = AppSettings::for_test("/tmp/test_gdrive");
+        settings.max_sync_retries = 3;
+        assert!(should_retry(&rsync(23), 0, &settings));
+        assert!(should_retry(&rsync(11), 2, &settings));
+        // Budget exhausted
+        assert!(!should_retry(&rsync(11), 3, &settings));
+        // Syntax and protocol errors are permanent
+        assert!(!should_retry(&rsync(1), 0, &settings));
+        assert!(!should_retry(&rsync(2), 0, &settings));
+        assert!(!should_retry(
+            &ShrikeError::SyncFailed("x".into()),
+            0,
+            &settings
+        ));
+        // Only the configured codes are transient
+        settings.transient_exit_codes = vec![24];
+        assert!(should_retry(&rsync(24), 0, &settings));
+        assert!(!should_retry(&rsync(23), 0, &settings));
+        // Retries disabled (the default)
+        settings.max_sync_retries = 0;
+        assert!(!should_retry(&rsync(24), 0, &settings));
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially_up_to_cap() {
+        assert_eq!(retry_delay(0), RETRY_BASE_DELAY);
+        assert_eq!(retry_delay(1), RETRY_BASE_DELAY * 2);
+        assert_eq!(retry_delay(2), RETRY_BASE_DELAY * 4);
+        assert_eq!(retry_delay(10), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn destination_statuses_report_the_last_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dir.path().to_str().unwrap());
+        let destination = settings.destination_path().unwrap();
+        let never = destination_statuses(&settings).unwrap();
+        assert_eq!(never[0].path, destination);
+        assert_eq!(never[0].last_synced, None);
+        assert_eq!(never[0].last_exit_code, None);
+        assert!(never[0].reachable);
+
+        let result = SyncResult {
+            files_transferred: 0,
+            dirs_transferred: 0,
+            bytes_transferred: 0,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 24,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
+        };
+        record_destination_sync(&destination, &Ok(result.clone()));
+        record_destination_sync(
+            &destination,
+            &Err(ShrikeError::RsyncError {
+                code: 23,
+                message: String::new(),
+            }),
+        );
+
+        let status = &destination_statuses(&settings).unwrap()[0];
+        assert_eq!(status.last_synced, Some(result.synced_at));
+        assert_eq!(status.last_exit_code, Some(23));
+    }
+
+    #[test]
+    fn purge_machine_backup_refuses_mismatched_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dir.path().to_str().unwrap());
+        let destination = settings.destination_path().unwrap();
+        fs::create_dir_all(&destination).unwrap();
+
+        let err = purge_machine_backup(&settings, "OtherMac").unwrap_err();
+        assert!(matches!(err, ShrikeError::PurgeRefused(_)));
+        assert!(std::path::Path::new(&destination).exists());
+    }
+
+    #[test]
+    fn purge_machine_backup_refuses_paths_outside_a_machine_folder() {
+        let mut settings = AppSettings::for_test("/");
+        let err = purge_machine_backup(&settings, "TestMac").unwrap_err();
+        assert!(matches!(err, ShrikeError::PurgeRefused(_)));
+
+        settings.gdrive_path = "/tmp/test_gdrive".to_string();
+        settings.machine_name = "..".to_string();
+        assert!(purge_machine_backup(&settings, "..").is_err());
+    }
+
+    #[test]
+    fn purge_machine_backup_only_removes_machine_subtree() {
+        let _serial = lock_sync_tests();
+        let dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dir.path().to_str().unwrap());
+        let destination = std::path::PathBuf::from(settings.destination_path().unwrap());
+        fs::create_dir_all(destination.join("Users/me")).unwrap();
+        fs::write(destination.join("Users/me/a.txt"), "12345").unwrap();
+        fs::write(destination.join("Users/me/b.txt"), "123").unwrap();
+        let sibling = dir.path().join("Backup/OtherMac");
+        fs::create_dir_all(&sibling).unwrap();
+        fs::write(sibling.join("keep.txt"), "keep").unwrap();
+
+        assert_eq!(purge_machine_backup(&settings, "TestMac").unwrap(), 8);
+        assert!(!destination.exists());
+        assert!(dir.path().join("Backup").is_dir());
+        assert_eq!(
+            fs::read_to_string(sibling.join("keep.txt")).unwrap(),
+            "keep"
+        );
+
+        // Already gone: nothing to free
+        assert_eq!(purge_machine_backup(&settings, "TestMac").unwrap(), 0);
+    }
+
+    #[test]
+    fn format_sync_completed_includes_counts() {
+        let result = SyncResult {
+            files_transferred: 12,
+            dirs_transferred: 3,
+            bytes_transferred: 4096,
+            created: 5,
+            updated: 7,
+            deleted: 1,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec!["/gone".into()],
+            truncated: false,
+        };
+        assert_eq!(
+            format_sync_completed(&result),
+            "sync completed: exit=0 files=12 dirs=3 bytes=4096 created=5 updated=7 deleted=1 skipped=1"
+        );
+    }
+
+    #[test]
+    fn merge_results_sums_passes() {
+        let pass = |files: u64, stdout: &str| SyncResult {
+            files_transferred: files,
+            dirs_transferred: 1,
+            bytes_transferred: 10,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            exit_code: 0,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![format!("file has vanished: {stdout}")],
+            skipped_paths: vec![],
+            truncated: false,
+        };
+        let merged = merge_results(vec![pass(2, "a\n"), pass(3, "b\n")], None).unwrap();
+        assert_eq!(merged.files_transferred, 5);
+        assert_eq!(merged.dirs_transferred, 2);
+        assert_eq!(merged.bytes_transferred, 20);
+        assert_eq!(merged.stdout, "a\nb\n");
+        assert_eq!(merged.warnings.len(), 2);
+        assert!(!merged.truncated);
+        assert!(merge_results(vec![], None).is_none());
+
+        // Passes each within the cap still get cut together
+        let merged = merge_results(vec![pass(2, "a\n"), pass(3, "b\n")], Some(1)).unwrap();
+        assert_eq!(merged.stdout, "b\n");
+        assert!(merged.truncated);
+    }
+
+    #[test]
+    fn merge_destination_results_reports_primary_counts() {
+        let destination = |files: u64, exit_code: i32, warning: &str| SyncResult {
+            files_transferred: files,
+            dirs_transferred: 1,
+            bytes_transferred: 10,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code,
+            synced_at: chrono::Utc::now(),
+            warnings: vec![warning.to_string()],
+            skipped_paths: vec![],
+            truncated: false,
+        };
+        let merged = merge_destination_results(Ok(vec![
+            destination(2, 0, "primary"),
+            destination(2, 24, "extra"),
+        ]))
+        .unwrap();
+        assert_eq!(merged.files_transferred, 2);
+        assert_eq!(merged.bytes_transferred, 10);
+        assert_eq!(merged.exit_code, 24);
+        assert_eq!(merged.warnings, vec!["primary", "extra"]);
+        assert!(merge_destination_results(Ok(vec![])).is_err());
+    }
+
+    #[test]
+    fn sync_destinations_carries_on_past_a_failing_destination() {
+        let source = NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+        let reachable = tempfile::tempdir().unwrap();
+        let mut settings = AppSettings::for_test("/nonexistent/shrike_gdrive");
+        settings.extra_destinations = vec![Destination {
+            gdrive_path: reachable.path().to_str().unwrap().to_string(),
+            backup_dir_name: "Backup".to_string(),
+        }];
+
+        let results = sync_destinations(&entries, &settings, false, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_success());
+        assert!(
+            results[0]
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("sync to /nonexistent/shrike_gdrive failed")),
+            "{:?}",
+            results[0].warnings
+        );
+        assert!(reachable.path().join("Backup/TestMac").is_dir());
+
+        settings.extra_destinations[0].gdrive_path = "/nonexistent/other_gdrive".to_string();
+        assert!(sync_destinations(&entries, &settings, false, None).is_err());
+    }
+
+    #[test]
+    fn preflight_reflects_unsaved_backup_dir_name() {
+        let mut source = NamedTempFile::new().unwrap();
+        writeln!(source, "hello shrike").unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let before = preflight(&entries, &settings).unwrap();
+        assert_eq!(before.destination, "/tmp/test_gdrive/Backup/TestMac");
+
+        let mut changed = settings.clone();
+        changed.backup_dir_name = "Elsewhere".to_string();
+        let after = preflight(&entries, &changed).unwrap();
+        assert_eq!(after.destination, "/tmp/test_gdrive/Elsewhere/TestMac");
+        assert_eq!(
+            after.argv[0].last().unwrap(),
+            "/tmp/test_gdrive/Elsewhere/TestMac/"
+        );
+    }
+
+    #[test]
+    fn preflight_does_not_create_destination() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        let entries = vec![BackupEntry::new("/etc/hosts".into(), ItemType::File)];
+
+        let result = preflight(&entries, &settings).unwrap();
+        assert!(!std::path::Path::new(&result.destination).exists());
+        assert_eq!(result.argv.len(), 1);
+        assert_eq!(result.argv[0][0], "rsync");
+        assert!(result.argv[0].contains(&format!("--files-from={FILELIST_PLACEHOLDER}")));
+        assert!(result.validation.is_ok());
+    }
+
+    #[test]
+    fn preflight_reports_invalid_paths_and_size() {
+        let mut source = NamedTempFile::new().unwrap();
+        write!(source, "12345").unwrap();
+        let entries = vec![
+            BackupEntry::new(source.path().to_str().unwrap().to_string(), ItemType::File),
+            BackupEntry::new("/nonexistent/shrike-abc".into(), ItemType::File),
+        ];
+
+        let result = preflight(&entries, &AppSettings::for_test("/tmp/test_gdrive")).unwrap();
+        assert_eq!(result.validation.valid_count, 1);
+        assert_eq!(result.validation.errors.len(), 1);
+        assert_eq!(result.estimated_bytes, 5);
+    }
+
+    #[test]
+    fn sync_plan_reports_missing_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().to_str().unwrap().to_string();
+        let missing = "/nonexistent/shrike-plan".to_string();
+        let mut disabled = BackupEntry::new("/etc/hosts".into(), ItemType::File);
+        disabled.enabled = false;
+        let entries = vec![
+            BackupEntry::new(present.clone(), ItemType::Directory),
+            BackupEntry::new(missing.clone(), ItemType::File),
+            disabled,
+        ];
+
+        let plan = sync_plan(&entries, &AppSettings::for_test("/tmp/test_gdrive")).unwrap();
+
+        assert_eq!(plan.destination, "/tmp/test_gdrive/Backup/TestMac");
+        assert_eq!(
+            plan.entries,
+            vec![
+                PlannedEntry {
+                    path: present,
+                    item_type: ItemType::Directory,
+                    exists: true,
+                    readable: true,
+                },
+                PlannedEntry {
+                    path: missing.clone(),
+                    item_type: ItemType::File,
+                    exists: false,
+                    readable: false,
+                },
+            ]
+        );
+        assert_eq!(plan.issues.valid_count, 1);
+        assert_eq!(
+            plan.issues.errors,
+            vec![validation::PathValidation::NotFound(missing)]
+        );
+    }
+
     #[test]
     fn execute_sync_empty_entries_fails() {
-        let settings = test_settings("/tmp/test_gdrive");
-        let result = execute_sync_inner(&[], &settings);
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let result = execute_sync_inner(&[], &settings, false, None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("no entries"));
     }
 
+    #[test]
+    fn execute_sync_all_entries_disabled_fails() {
+        let mut entry = BackupEntry::new("/etc/hosts".into(), ItemType::File);
+        entry.enabled = false;
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        let result = execute_sync_inner(&[entry], &settings, false, None);
+        assert!(result.unwrap_err().to_string().contains("no entries"));
+    }
+
     #[test]
     fn execute_sync_real_file() {
         let dest_dir = tempfile::tempdir().unwrap();
-        let settings = test_settings(dest_dir.path().to_str().unwrap());
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
 
         let mut source = NamedTempFile::new().unwrap();
         writeln!(source, "hello shrike").unwrap();
         let source_path = source.path().to_str().unwrap().to_string();
 
         let entries = vec![BackupEntry::new(source_path.clone(), ItemType::File)];
-        let result = execute_sync_inner(&entries, &settings).unwrap();
+        let result = execute_sync_inner(&entries, &settings, false, None).unwrap();
 
         assert!(result.is_success());
         assert_eq!(result.exit_code, 0);
@@ -124,16 +1832,212 @@ mod tests {
         assert!(content.contains("hello shrike"));
     }
 
+    /// A runner that records the filelist path passed to rsync, then fails
+    /// the way `fail` says.
+    fn recording_runner(
+        seen: &std::sync::Mutex<Vec<String>>,
+        fail: fn() -> Result<SyncResult>,
+    ) -> impl Fn(&[String], &executor::RunOptions) -> Result<SyncResult> + '_ {
+        move |args, _| {
+            let path = args
+                .iter()
+                .find_map(|a| a.strip_prefix("--files-from="))
+                .unwrap();
+            assert!(std::path::Path::new(path).exists());
+            seen.lock().unwrap().push(path.to_string());
+            fail()
+        }
+    }
+
+    #[test]
+    fn run_pipeline_removes_filelist_when_rsync_fails() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        let source = NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let result = run_pipeline(
+            &entries,
+            &settings,
+            false,
+            false,
+            recording_runner(&seen, || Err(ShrikeError::SyncFailed("boom".to_string()))),
+        );
+        assert!(result.is_err());
+
+        let seen = seen.into_inner().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(!std::path::Path::new(&seen[0]).exists());
+    }
+
+    #[test]
+    fn run_pipeline_dry_run_passes_flag_and_leaves_destination_alone() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        let source = NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+
+        let seen_args = std::sync::Mutex::new(Vec::new());
+        let result = run_pipeline(&entries, &settings, true, false, |args, _| {
+            seen_args.lock().unwrap().extend_from_slice(args);
+            Ok(SyncResult {
+                files_transferred: 1,
+                dirs_transferred: 0,
+                bytes_transferred: 0,
+                created: 0,
+                updated: 0,
+                deleted: 0,
+                unchanged: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                synced_at: chrono::Utc::now(),
+                warnings: vec![],
+                skipped_paths: vec![],
+                truncated: false,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(result.files_transferred, 1);
+        assert!(seen_args.lock().unwrap().contains(&"--dry-run".to_string()));
+        assert!(!dest_dir.path().join("Backup").exists());
+    }
+
+    #[test]
+    fn run_pipeline_passes_global_excludes_as_exclude_file() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let mut settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        settings.global_excludes = vec![".DS_Store".into(), "*.pyc".into()];
+        let source = NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+
+        let excludes = std::sync::Mutex::new(None);
+        run_pipeline(&entries, &settings, true, false, |args, _| {
+            let file = args
+                .iter()
+                .find_map(|a| a.strip_prefix("--exclude-from="))
+                .unwrap();
+            *excludes.lock().unwrap() = Some(fs::read_to_string(file).unwrap());
+            Ok(SyncResult {
+                files_transferred: 0,
+                dirs_transferred: 0,
+                bytes_transferred: 0,
+                created: 0,
+                updated: 0,
+                deleted: 0,
+                unchanged: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                synced_at: chrono::Utc::now(),
+                warnings: vec![],
+                skipped_paths: vec![],
+                truncated: false,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(
+            excludes.lock().unwrap().as_deref(),
+            Some(".DS_Store\n*.pyc\n")
+        );
+    }
+
+    #[test]
+    fn run_pipeline_reports_skipped_paths() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        let source = NamedTempFile::new().unwrap();
+        let missing = "/nonexistent/shrike/missing.txt".to_string();
+        let entries = vec![
+            BackupEntry::new(source.path().to_str().unwrap().to_string(), ItemType::File),
+            BackupEntry::new(missing.clone(), ItemType::File),
+        ];
+
+        let filelist = std::sync::Mutex::new(String::new());
+        let result = run_pipeline(&entries, &settings, true, false, |args, _| {
+            let path = args
+                .iter()
+                .find_map(|a| a.strip_prefix("--files-from="))
+                .unwrap();
+            *filelist.lock().unwrap() = fs::read_to_string(path).unwrap();
+            Ok(SyncResult {
+                files_transferred: 1,
+                dirs_transferred: 0,
+                bytes_transferred: 0,
+                created: 0,
+                updated: 0,
+                deleted: 0,
+                unchanged: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: 0,
+                synced_at: chrono::Utc::now(),
+                warnings: vec![],
+                skipped_paths: vec![],
+                truncated: false,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(result.files_transferred, 1);
+        assert_eq!(result.skipped_paths, vec![missing.clone()]);
+        let filelist = filelist.into_inner().unwrap();
+        assert!(!filelist.contains(&missing), "{filelist}");
+        assert!(
+            filelist.contains(source.path().to_str().unwrap()),
+            "{filelist}"
+        );
+    }
+
+    #[test]
+    fn run_pipeline_removes_filelist_when_rsync_panics() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+        let source = NamedTempFile::new().unwrap();
+        let entries = vec![BackupEntry::new(
+            source.path().to_str().unwrap().to_string(),
+            ItemType::File,
+        )];
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            run_pipeline(
+                &entries,
+                &settings,
+                false,
+                false,
+                recording_runner(&seen, || panic!("rsync exploded")),
+            )
+        }));
+        assert!(outcome.is_err());
+
+        let seen = seen.into_inner().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(!std::path::Path::new(&seen[0]).exists());
+    }
+
     #[test]
     fn execute_sync_nonexistent_file_fails() {
         let dest_dir = tempfile::tempdir().unwrap();
-        let settings = test_settings(dest_dir.path().to_str().unwrap());
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
 
         let entries = vec![BackupEntry::new(
             "/nonexistent/file_abc123.txt".into(),
             ItemType::File,
         )];
-        let result = execute_sync_inner(&entries, &settings);
+        let result = execute_sync_inner(&entries, &settings, false, None);
         assert!(result.is_err());
     }
 
@@ -158,13 +2062,13 @@ mod tests {
             .to_string_lossy()
             .to_string();
 
-        let settings = test_settings(dest_dir.path().to_str().unwrap());
+        let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
         let entries = vec![
             BackupEntry::new(file1_path.clone(), ItemType::File),
             BackupEntry::new(file2_path.clone(), ItemType::File),
         ];
 
-        let result = execute_sync_inner(&entries, &settings).unwrap();
+        let result = execute_sync_inner(&entries, &settings, false, None).unwrap();
         assert!(result.is_success());
 
         // Verify both files exist in backup
@@ -181,25 +2085,58 @@ mod tests {
 
     #[test]
     fn execute_sync_rejects_concurrent_runs() {
+        let _serial = lock_sync_tests();
         // Simulate a lock being held by setting the flag manually
         SYNC_RUNNING.store(true, Ordering::SeqCst);
 
-        let settings = test_settings("/tmp/test_gdrive");
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
         let entries = vec![BackupEntry::new("/etc/hosts".into(), ItemType::File)];
-        let result = execute_sync(&entries, &settings);
+        let result = execute_sync(&entries, &settings, false);
 
         // Must release the lock before asserting, so other tests aren't affected
         SYNC_RUNNING.store(false, Ordering::SeqCst);
 
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("already in progress"));
+        assert!(matches!(result, Err(ShrikeError::SyncBusy)));
+    }
+
+    #[test]
+    fn coalesce_sync_queues_one_more_run() {
+        let _serial = lock_sync_tests();
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        SYNC_RUNNING.store(true, Ordering::SeqCst);
+
+        // Three triggers while a sync runs fold into one queued run
+        for _ in 0..3 {
+            let runs = runs.clone();
+            let outcome = coalesce_sync(move || {
+                runs.fetch_add(1, Ordering::SeqCst);
+            });
+            assert_eq!(outcome, Coalesced::Queued);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        SYNC_RUNNING.store(false, Ordering::SeqCst);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while runs.load(Ordering::SeqCst) == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        std::thread::sleep(PENDING_POLL_INTERVAL * 4);
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(!SYNC_PENDING.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn coalesce_sync_runs_immediately_when_idle() {
+        let _serial = lock_sync_tests();
+        let outcome = coalesce_sync(|| 42);
+        assert_eq!(outcome, Coalesced::Ran(42));
+        assert!(!is_sync_running());
     }
 
     #[test]
     fn is_sync_running_reflects_state() {
+        let _serial = lock_sync_tests();
         assert!(!is_sync_running());
         SYNC_RUNNING.store(true, Ordering::SeqCst);
         assert!(is_sync_running());
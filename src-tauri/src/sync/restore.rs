@@ -0,0 +1,256 @@
+//! Restore: copy an entry's backup back to its original location.
+//!
+//! The backup mirrors each entry's path under the destination (rsync `-R`),
+//! so a restore runs rsync in reverse with `-R` again: the filelist holds
+//! the paths as they were synced, resolved relative to the destination, and
+//! rsync rebuilds them under the transfer root they were synced from (`/`,
+//! or home under the `RelativeToHome` layout).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{executor, filelist, layout, with_sync_lock};
+use crate::error::{Result, ShrikeError};
+use crate::types::{AppSettings, BackupEntry, SyncResult};
+
+/// How many conflicting paths are listed in the overwrite guard's error.
+const MAX_LISTED_CONFLICTS: usize = 5;
+
+/// Local files under `original` that were modified after their copy under
+/// `backup`, and would lose changes if the backup were restored over them.
+///
+/// Walks the backup rather than the local tree, since only files with a
+/// backed-up copy are overwritten. Symlinks are not followed.
+pub fn newer_local_files(original: &Path, backup: &Path) -> Vec<String> {
+    let mut newer = Vec::new();
+    collect_newer(original, backup, &mut newer);
+    newer
+}
+
+fn collect_newer(local: &Path, backup: &Path, newer: &mut Vec<String>) {
+    let Ok(backup_meta) = fs::symlink_metadata(backup) else {
+        return;
+    };
+    if backup_meta.is_dir() {
+        if let Ok(children) = fs::read_dir(backup) {
+            for child in children.filter_map(|e| e.ok()) {
+                collect_newer(&local.join(child.file_name()), &child.path(), newer);
+            }
+        }
+        return;
+    }
+
+    let Ok(local_meta) = fs::symlink_metadata(local) else {
+        return;
+    };
+    if let (Ok(local_mtime), Ok(backup_mtime)) = (local_meta.modified(), backup_meta.modified())
+        && local_mtime > backup_mtime
+    {
+        newer.push(local.to_string_lossy().to_string());
+    }
+}
+
+/// Copy `entry`'s backup back to its original absolute path.
+///
+/// With `dry_run`, rsync only reports what would be restored. Otherwise the
+/// restore is refused while any local file is newer than its backup, unless
+/// `force` is set. Shares the sync lock, so a restore never overlaps a sync.
+pub fn restore_entry(
+    entry: &BackupEntry,
+    settings: &AppSettings,
+    dry_run: bool,
+    force: bool,
+) -> Result<SyncResult> {
+    with_sync_lock(|| restore_entry_inner(entry, settings, dry_run, force))
+}
+
+/// Restore logic, separated so tests can run it without the sync lock.
+fn restore_entry_inner(
+    entry: &BackupEntry,
+    settings: &AppSettings,
+    dry_run: bool,
+    force: bool,
+) -> Result<SyncResult> {
+    let destination = settings.destination_path()?;
+    let backup = layout::backup_path_for(&entry.path, &destination, settings.path_layout);
+    if !backup.exists() {
+        return Err(ShrikeError::PathNotFound(
+            backup.to_string_lossy().to_string(),
+        ));
+    }
+
+    if !dry_run && !force {
+        refuse_newer_local_files(std::slice::from_ref(entry), &destination, settings)?;
+    }
+
+    run_restore(std::slice::from_ref(entry), &destination, settings, dry_run)
+}
+
+/// Fail if any of `entries` has a local file newer than its backup under
+/// `destination`, since restoring would lose that change.
+pub(super) fn refuse_newer_local_files(
+    entries: &[BackupEntry],
+    destination: &str,
+    settings: &AppSettings,
+) -> Result<()> {
+    let newer: Vec<String> = entries
+        .iter()
+        .flat_map(|entry| {
+            let backup = layout::backup_path_for(&entry.path, destination, settings.path_layout);
+            newer_local_files(Path::new(&entry.path), &backup)
+        })
+        .collect();
+    if newer.is_empty() {
+        return Ok(());
+    }
+    Err(ShrikeError::SyncFailed(format!(
+        "{} local file(s) are newer than the backup, restore with force to overwrite: {}",
+        newer.len(),
+        newer[..newer.len().min(MAX_LISTED_CONFLICTS)].join(", ")
+    )))
+}
+
+/// Copy `entries`' backups under `destination` back into place, with one
+/// rsync run per transfer root of `settings.path_layout`.
+pub(super) fn run_restore(
+    entries: &[BackupEntry],
+    destination: &str,
+    settings: &AppSettings,
+    dry_run: bool,
+) -> Result<SyncResult> {
+    let home = layout::home_dir();
+    let mut groups: Vec<(Option<PathBuf>, Vec<BackupEntry>)> = Vec::new();
+    for entry in entries {
+        let root = layout::transfer_root(&entry.path, settings.path_layout, home.as_deref());
+        // Disabled entries are skipped by syncs, not restores
+        let entry = BackupEntry {
+            path: match &root {
+                Some(root) => layout::rebase(&entry.path, root),
+                None => entry.path.clone(),
+            },
+            enabled: true,
+            ..entry.clone()
+        };
+        match groups.iter_mut().find(|(r, _)| *r == root) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((root, vec![entry])),
+        }
+    }
+
+    let run_options = executor::RunOptions {
+        umask: settings.umask()?,
+        program: settings.rsync_binary.clone(),
+        timeout: settings.sync_timeout(),
+        max_output_lines: settings.output_line_cap(),
+    };
+    let mut results = Vec::new();
+    for (root, entries) in groups {
+        let filelist_file = filelist::generate_filelist(&entries)?;
+        let filelist_path = filelist::filelist_path_str(&filelist_file)?;
+        let target_root = root.map_or_else(|| "/".to_string(), |r| r.to_string_lossy().to_string());
+        let args = executor::build_restore_args(&filelist_path, destination, &target_root, dry_run);
+        results.push(executor::run_rsync(&args, &run_options)?);
+    }
+    super::merge_results(results, run_options.max_output_lines)
+        .ok_or_else(|| ShrikeError::SyncFailed("nothing to restore".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ItemType;
+    use std::time::{Duration, SystemTime};
+
+    fn set_mtime(path: &Path, mtime: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    }
+
+    #[test]
+    fn newer_local_files_flags_only_files_modified_after_backup() {
+        let local = tempfile::tempdir().unwrap();
+        let backup = tempfile::tempdir().unwrap();
+        let past = SystemTime::now() - Duration::from_secs(3600);
+        for name in ["edited.txt", "same.txt", "sub/older.txt"] {
+            fs::create_dir_all(local.path().join(name).parent().unwrap()).unwrap();
+            fs::create_dir_all(backup.path().join(name).parent().unwrap()).unwrap();
+            fs::write(local.path().join(name), "local").unwrap();
+            fs::write(backup.path().join(name), "backup").unwrap();
+        }
+        set_mtime(&backup.path().join("edited.txt"), past);
+        set_mtime(&local.path().join("same.txt"), past);
+        set_mtime(&backup.path().join("same.txt"), past);
+        set_mtime(&local.path().join("sub/older.txt"), past);
+        // Only in the backup: restoring it overwrites nothing
+        fs::write(backup.path().join("deleted.txt"), "backup").unwrap();
+
+        let newer = newer_local_files(local.path(), backup.path());
+        assert_eq!(
+            newer,
+            vec![
+                local
+                    .path()
+                    .join("edited.txt")
+                    .to_string_lossy()
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn newer_local_files_single_file_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.txt");
+        let backup = dir.path().join("backup.txt");
+        fs::write(&local, "local").unwrap();
+        fs::write(&backup, "backup").unwrap();
+        set_mtime(&backup, SystemTime::now() - Duration::from_secs(60));
+
+        assert_eq!(newer_local_files(&local, &backup).len(), 1);
+        assert!(newer_local_files(&backup, &dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn restore_entry_refuses_to_overwrite_newer_local_file() {
+        let source = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let file = source.path().join("notes.txt");
+        fs::write(&file, "edited locally").unwrap();
+
+        let settings = AppSettings {
+            gdrive_path: dest.path().to_string_lossy().to_string(),
+            backup_dir_name: "Backup".into(),
+            machine_name: "Mac".into(),
+            ..AppSettings::default()
+        };
+        let backup = layout::backup_path_for(
+            &file.to_string_lossy(),
+            &settings.destination_path().unwrap(),
+            settings.path_layout,
+        );
+        fs::create_dir_all(backup.parent().unwrap()).unwrap();
+        fs::write(&backup, "backed up").unwrap();
+        set_mtime(&backup, SystemTime::now() - Duration::from_secs(3600));
+
+        let entry = BackupEntry::new(file.to_string_lossy().to_string(), ItemType::File);
+        let err = restore_entry_inner(&entry, &settings, false, false).unwrap_err();
+        assert!(err.to_string().contains("newer than the backup"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "edited locally");
+    }
+
+    #[test]
+    fn restore_entry_without_backup_is_path_not_found() {
+        let dest = tempfile::tempdir().unwrap();
+        let settings = AppSettings {
+            gdrive_path: dest.path().to_string_lossy().to_string(),
+            ..AppSettings::default()
+        };
+        let entry = BackupEntry::new("/nonexistent/notes.txt".into(), ItemType::File);
+        let err = restore_entry_inner(&entry, &settings, true, false).unwrap_err();
+        assert!(matches!(err, ShrikeError::PathNotFound(_)));
+    }
+}
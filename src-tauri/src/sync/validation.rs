@@ -2,16 +2,26 @@
 //!
 //! Validates the generated filelist before handing it off to rsync.
 //! Checks include: path existence, readability, duplicate detection,
-//! absolute path requirement, and destination availability.
+//! absolute path requirement, destination availability (including whether
+//! the Drive mount is live), and free space.
 
 use std::collections::HashSet;
 use std::fs;
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 
 use crate::error::{Result, ShrikeError};
 
 /// Result of validating a single path entry.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Serialized as `{"status": "not_found", "path": "/x"}`; `Valid` carries no
+/// path and is just `{"status": "valid"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "path", rename_all = "snake_case")]
 pub enum PathValidation {
     /// Path is valid and ready for sync.
     Valid,
@@ -21,10 +31,25 @@ pub enum PathValidation {
     NotReadable(String),
     /// Path is not an absolute path.
     NotAbsolute(String),
+    /// Path is a symlink whose target does not exist.
+    BrokenSymlink(String),
+}
+
+impl PathValidation {
+    /// The offending path, or `None` for `Valid`.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            PathValidation::Valid => None,
+            PathValidation::NotFound(p)
+            | PathValidation::NotReadable(p)
+            | PathValidation::NotAbsolute(p)
+            | PathValidation::BrokenSymlink(p) => Some(p),
+        }
+    }
 }
 
 /// Result of validating an entire filelist.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidationReport {
     /// Total number of paths checked.
     pub total: usize,
@@ -47,6 +72,17 @@ impl ValidationReport {
         !self.is_ok()
     }
 
+    /// Paths the sync leaves out because they failed validation.
+    ///
+    /// Duplicates are not listed: the sync still transfers them, once.
+    pub fn skipped_paths(&self) -> Vec<String> {
+        self.errors
+            .iter()
+            .filter_map(PathValidation::path)
+            .map(str::to_string)
+            .collect()
+    }
+
     /// Format a human-readable summary of validation issues.
     pub fn summary(&self) -> String {
         if self.is_ok() {
@@ -71,6 +107,11 @@ impl ValidationReport {
                 .iter()
                 .filter(|e| matches!(e, PathValidation::NotAbsolute(_)))
                 .count();
+            let broken_symlinks = self
+                .errors
+                .iter()
+                .filter(|e| matches!(e, PathValidation::BrokenSymlink(_)))
+                .count();
 
             if not_found > 0 {
                 parts.push(format!("{not_found} not found"));
@@ -81,6 +122,9 @@ impl ValidationReport {
             if not_absolute > 0 {
                 parts.push(format!("{not_absolute} not absolute"));
             }
+            if broken_symlinks > 0 {
+                parts.push(format!("{broken_symlinks} broken symlinks"));
+            }
         }
 
         if !self.duplicates.is_empty() {
@@ -97,12 +141,18 @@ impl ValidationReport {
 }
 
 /// Validate a single path: must be absolute, must exist, must be readable.
+/// A symlink must also resolve to an existing target.
 pub fn validate_path(path: &str) -> PathValidation {
     if !path.starts_with('/') {
         return PathValidation::NotAbsolute(path.to_string());
     }
 
     let p = Path::new(path);
+    // `exists()` follows links, so a dangling one would look merely missing
+    let is_symlink = fs::symlink_metadata(p).is_ok_and(|m| m.file_type().is_symlink());
+    if is_symlink && fs::metadata(p).is_err() {
+        return PathValidation::BrokenSymlink(path.to_string());
+    }
     if !p.exists() {
         return PathValidation::NotFound(path.to_string());
     }
@@ -114,33 +164,185 @@ pub fn validate_path(path: &str) -> PathValidation {
     }
 }
 
+/// Accumulates a `ValidationReport` one path at a time.
+///
+/// The dedup set is the only structure that grows with the number of paths,
+/// so a filelist can be validated while streaming it.
+#[derive(Default)]
+struct ReportBuilder {
+    seen: HashSet<String>,
+    total: usize,
+    valid_count: usize,
+    errors: Vec<PathValidation>,
+    duplicates: Vec<String>,
+    /// Sum the size of each valid path into `bytes` (a full tree walk)
+    measure: bool,
+    bytes: u64,
+}
+
+impl ReportBuilder {
+    fn add(&mut self, path: &str) {
+        self.total += 1;
+        if !self.seen.insert(path.to_string()) {
+            self.duplicates.push(path.to_string());
+            return;
+        }
+
+        match validate_path(path) {
+            PathValidation::Valid => {
+                self.valid_count += 1;
+                if self.measure {
+                    self.bytes += path_size(Path::new(path));
+                }
+            }
+            other => self.errors.push(other),
+        }
+    }
+
+    fn finish(self) -> ValidationReport {
+        ValidationReport {
+            total: self.total,
+            valid_count: self.valid_count,
+            errors: self.errors,
+            duplicates: self.duplicates,
+        }
+    }
+}
+
 /// Validate a list of path strings (typically read from a filelist file).
 ///
 /// Checks each path for existence and readability, and detects duplicates.
 pub fn validate_filelist(paths: &[String]) -> ValidationReport {
-    let mut seen = HashSet::new();
-    let mut errors = Vec::new();
-    let mut duplicates = Vec::new();
-    let mut valid_count = 0;
-
+    let mut builder = ReportBuilder::default();
     for path in paths {
-        if !seen.insert(path.clone()) {
-            duplicates.push(path.clone());
-            continue;
-        }
+        builder.add(path);
+    }
+    builder.finish()
+}
 
-        let validation = validate_path(path);
-        match validation {
-            PathValidation::Valid => valid_count += 1,
-            other => errors.push(other),
+/// Validate a filelist file line by line without reading it into memory.
+///
+/// Blank lines are skipped, matching `filelist::read_filelist`, so the
+/// report is identical to `validate_filelist(&read_filelist(path)?)`.
+pub fn validate_filelist_file(path: &Path) -> Result<ValidationReport> {
+    Ok(scan_filelist_file(path, false)?.finish())
+}
+
+fn scan_filelist_file(path: &Path, measure: bool) -> Result<ReportBuilder> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    let mut builder = ReportBuilder {
+        measure,
+        ..ReportBuilder::default()
+    };
+    for line in reader.lines() {
+        let line = line?;
+        if !line.is_empty() {
+            builder.add(&line);
         }
     }
+    Ok(builder)
+}
+
+/// Recursively sum the size of a path in bytes. Symlinks are not followed
+/// and unreadable entries count as zero.
+fn path_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| path_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Total size in bytes of the regular files at or below `path`.
+///
+/// Symlinks are not followed (and count as zero), so a symlink cycle cannot
+/// make the walk loop. Unlike `estimate_size`, any unreadable entry fails
+/// the whole computation instead of counting as zero.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    walk_size(path, None)
+}
 
-    ValidationReport {
-        total: paths.len(),
-        valid_count,
-        errors,
-        duplicates,
+/// Like `dir_size`, but gives up with `ErrorKind::TimedOut` once the walk
+/// has taken longer than `timeout`, so huge trees can't stall the caller.
+pub fn dir_size_within(path: &Path, timeout: Duration) -> io::Result<u64> {
+    walk_size(path, Some(Instant::now() + timeout))
+}
+
+fn walk_size(path: &Path, deadline: Option<Instant>) -> io::Result<u64> {
+    if deadline.is_some_and(|d| Instant::now() > d) {
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("size scan of {} timed out", path.display()),
+        ));
+    }
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_file() {
+        return Ok(meta.len());
+    }
+    if !meta.is_dir() {
+        return Ok(0);
+    }
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += walk_size(&entry?.path(), deadline)?;
+    }
+    Ok(total)
+}
+
+/// Estimate the total source size of a filelist in bytes.
+///
+/// Duplicate paths are only counted once. This is an upper bound on what
+/// rsync will transfer, since unchanged files are skipped.
+pub fn estimate_size(paths: &[String]) -> u64 {
+    let unique: HashSet<&String> = paths.iter().collect();
+    unique.into_iter().map(|p| path_size(Path::new(p))).sum()
+}
+
+/// Free space available to unprivileged users on the filesystem holding
+/// `path`, or its nearest existing ancestor (the destination may not have
+/// been created yet).
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let c_path = CString::new(existing.as_os_str().as_bytes()).ok()?;
+    // SAFETY: c_path is NUL-terminated and stat is a valid out-pointer
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // The field widths differ between platforms (32-bit block counts on macOS)
+    #[allow(clippy::useless_conversion)]
+    let free = u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize));
+    Some(free)
+}
+
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Fail if `required` bytes will not fit in `available`.
+///
+/// An unknown amount of free space (`None`) passes, so filesystems that
+/// can't be queried don't block syncing.
+pub fn check_free_space(required: u64, available: Option<u64>, destination: &str) -> Result<()> {
+    match available {
+        Some(available) if available < required => Err(ShrikeError::SyncFailed(format!(
+            "not enough free space at {destination}: need {required} bytes, {available} available"
+        ))),
+        _ => Ok(()),
     }
 }
 
@@ -163,32 +365,282 @@ pub fn validate_destination(destination: &str) -> Result<()> {
     Ok(())
 }
 
-/// Run full pre-sync validation: check entries are non-empty, validate all
-/// paths, validate destination. Returns an error if anything critical fails.
-pub fn pre_sync_check(paths: &[String], destination: &str) -> Result<ValidationReport> {
-    if paths.is_empty() {
-        return Err(ShrikeError::SyncFailed("no entries to sync".to_string()));
+/// Whether a sync could write to `destination` now, checked without creating
+/// anything: `root`, the destination's `gdrive_path` (e.g. a Drive mount that
+/// may be offline), must be a directory, and so must the deepest existing
+/// path on the way to `destination`, which must also be writable.
+pub fn destination_reachable(root: &Path, destination: &Path) -> bool {
+    if !root.is_dir() {
+        return false;
     }
+    destination
+        .ancestors()
+        .find(|p| p.exists())
+        .is_some_and(|existing| existing.is_dir() && is_writable(existing))
+}
 
-    let report = validate_filelist(paths);
+/// How long `is_destination_mounted` waits for the drive root to answer.
+pub const MOUNT_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
 
-    // If ALL paths are invalid, fail early
-    if report.valid_count == 0 {
-        return Err(ShrikeError::SyncFailed(format!(
-            "no valid paths to sync: {}",
-            report.summary()
-        )));
+/// Whether `root`, the destination's `gdrive_path`, is a live mount.
+///
+/// When Google Drive isn't running, the `CloudStorage` folders can be left
+/// behind while listing them hangs or errors, which would otherwise stall
+/// rsync mid-transfer. The probe only stats and lists `root` (nothing is
+/// written), on a helper thread so a hung mount is reported as offline after
+/// `timeout` instead of blocking the caller.
+pub fn is_destination_mounted(root: &Path, timeout: Duration) -> bool {
+    let root = root.to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let live = root.is_dir()
+            && fs::read_dir(&root).is_ok_and(|mut entries| !matches!(entries.next(), Some(Err(_))));
+        let _ = tx.send(live);
+    });
+    rx.recv_timeout(timeout).unwrap_or(false)
+}
+
+/// Fail if the drive root at `root` isn't a live mount.
+pub fn check_destination_mounted(root: &Path, timeout: Duration) -> Result<()> {
+    if is_destination_mounted(root, timeout) {
+        return Ok(());
     }
+    Err(ShrikeError::SyncFailed(format!(
+        "Google Drive appears offline: {} is missing or not responding",
+        root.display()
+    )))
+}
+
+/// Whether this process may create files in `dir`.
+#[cfg(unix)]
+fn is_writable(dir: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: c_path is NUL-terminated
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable(dir: &Path) -> bool {
+    fs::metadata(dir).is_ok_and(|m| !m.permissions().readonly())
+}
+
+/// Check a validation report and the destination before syncing: the drive
+/// root the destination lives under must be mounted before anything is
+/// created there. When the builder measured its paths, also check they fit
+/// at the destination.
+fn check_report(
+    builder: ReportBuilder,
+    root: &Path,
+    destination: &str,
+) -> Result<ValidationReport> {
+    let (measure, bytes) = (builder.measure, builder.bytes);
+    let report = check_paths(builder.finish())?;
+
+    check_destination_mounted(root, MOUNT_PROBE_TIMEOUT)?;
 
     // Validate destination
     validate_destination(destination)?;
 
+    if measure {
+        check_free_space(bytes, free_space(Path::new(destination)), destination)?;
+    }
+
+    Ok(report)
+}
+
+/// Fail if the report has no paths, or no valid ones.
+fn check_paths(report: ValidationReport) -> Result<ValidationReport> {
+    if report.total == 0 {
+        return Err(ShrikeError::SyncFailed("no entries to sync".to_string()));
+    }
+
+    // If ALL paths are invalid, fail early
+    if report.valid_count == 0 {
+        return Err(ShrikeError::ValidationFailed(report));
+    }
+
+    Ok(report)
+}
+
+/// Run full pre-sync validation: check entries are non-empty, validate all
+/// paths, check the drive root `root` is mounted, validate destination, and
+/// with `check_space` make sure the estimated source size fits in the
+/// destination's free space. Returns an error if anything critical fails.
+pub fn pre_sync_check(
+    paths: &[String],
+    root: &Path,
+    destination: &str,
+    check_space: bool,
+) -> Result<ValidationReport> {
+    let mut builder = ReportBuilder {
+        measure: check_space,
+        ..ReportBuilder::default()
+    };
+    for path in paths {
+        builder.add(path);
+    }
+    check_report(builder, root, destination)
+}
+
+/// Same as `pre_sync_check`, but streams the paths from a filelist file.
+pub fn pre_sync_check_file(
+    filelist: &Path,
+    root: &Path,
+    destination: &str,
+    check_space: bool,
+) -> Result<ValidationReport> {
+    check_report(
+        scan_filelist_file(filelist, check_space)?,
+        root,
+        destination,
+    )
+}
+
+/// Same as `pre_sync_check_file`, but never creates the destination; it
+/// only has to be a directory if it already exists. Used by dry runs.
+pub fn dry_run_check_file(filelist: &Path, destination: &str) -> Result<ValidationReport> {
+    let report = check_paths(validate_filelist_file(filelist)?)?;
+    let path = Path::new(destination);
+    if path.exists() && !path.is_dir() {
+        return Err(ShrikeError::SyncFailed(format!(
+            "destination is not a directory: {destination}"
+        )));
+    }
     Ok(report)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
+
+    // --- estimate_size ---
+
+    #[test]
+    fn estimate_size_sums_files_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.txt"), "12345").unwrap();
+        fs::write(dir.path().join("sub/b.txt"), "123").unwrap();
+
+        let paths = vec![dir.path().to_str().unwrap().to_string()];
+        assert_eq!(estimate_size(&paths), 8);
+    }
+
+    #[test]
+    fn estimate_size_counts_duplicates_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "12345").unwrap();
+
+        let path = file.to_str().unwrap().to_string();
+        assert_eq!(estimate_size(&[path.clone(), path]), 5);
+    }
+
+    #[test]
+    fn estimate_size_missing_path_is_zero() {
+        assert_eq!(estimate_size(&["/nonexistent/shrike-abc".to_string()]), 0);
+    }
+
+    // --- dir_size ---
+
+    #[test]
+    fn dir_size_sums_regular_files_in_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        fs::write(dir.path().join("a.txt"), "12345").unwrap();
+        fs::write(dir.path().join("sub/b.txt"), "123").unwrap();
+        fs::write(dir.path().join("sub/deeper/c.txt"), "1234567").unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 15);
+        assert_eq!(dir_size(&dir.path().join("a.txt")).unwrap(), 5);
+    }
+
+    #[test]
+    fn dir_size_does_not_follow_symlink_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/a.txt"), "12345").unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("sub/loop")).unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 5);
+    }
+
+    #[test]
+    fn dir_size_missing_path_is_error() {
+        assert!(dir_size(Path::new("/nonexistent/shrike-abc")).is_err());
+    }
+
+    #[test]
+    fn dir_size_within_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "12345").unwrap();
+
+        let err = dir_size_within(dir.path(), Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(
+            dir_size_within(dir.path(), Duration::from_secs(60)).unwrap(),
+            5
+        );
+    }
+
+    // --- free space ---
+
+    #[test]
+    fn free_space_uses_nearest_existing_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not/yet/created");
+        assert_eq!(free_space(&missing).is_some(), cfg!(unix));
+    }
+
+    #[test]
+    fn check_free_space_rejects_insufficient_space() {
+        let err = check_free_space(100, Some(99), "/Volumes/SSD").unwrap_err();
+        assert!(matches!(err, ShrikeError::SyncFailed(_)));
+        assert!(err.to_string().contains("need 100 bytes, 99 available"));
+
+        assert!(check_free_space(100, Some(100), "/Volumes/SSD").is_ok());
+        assert!(check_free_space(u64::MAX, None, "/Volumes/SSD").is_ok());
+    }
+
+    #[test]
+    fn pre_sync_check_space_check_measures_valid_paths() {
+        let src = tempfile::tempdir().unwrap();
+        fs::write(src.path().join("a.txt"), "12345").unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let paths = vec![
+            src.path().to_str().unwrap().to_string(),
+            "/nonexistent/shrike-abc".to_string(),
+        ];
+
+        let report =
+            pre_sync_check(&paths, dest.path(), dest.path().to_str().unwrap(), true).unwrap();
+        assert_eq!(report.valid_count, 1);
+    }
+
+    #[test]
+    fn scan_filelist_file_measures_known_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("nested/deeper")).unwrap();
+        fs::write(dir.path().join("nested/a.txt"), "1234567890").unwrap();
+        fs::write(dir.path().join("nested/deeper/b.txt"), "12").unwrap();
+        let single = dir.path().join("single.txt");
+        fs::write(&single, "123").unwrap();
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{}", dir.path().join("nested").display()).unwrap();
+        writeln!(file, "{}", single.display()).unwrap();
+        writeln!(file, "{}", single.display()).unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(scan_filelist_file(file.path(), true).unwrap().bytes, 15);
+        assert_eq!(scan_filelist_file(file.path(), false).unwrap().bytes, 0);
+    }
 
     // --- validate_path ---
 
@@ -234,6 +686,41 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn validate_path_dangling_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("gone"), &link).unwrap();
+        let link = link.to_str().unwrap();
+        assert_eq!(
+            validate_path(link),
+            PathValidation::BrokenSymlink(link.to_string())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_path_symlink_to_existing_target_is_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        fs::write(&target, "x").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        assert_eq!(validate_path(link.to_str().unwrap()), PathValidation::Valid);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn report_summary_counts_broken_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink("/nonexistent/shrike-target", &link).unwrap();
+        let report = validate_filelist(&["/etc/hosts".into(), link.to_str().unwrap().into()]);
+        assert!(report.has_issues());
+        assert!(report.summary().contains("1 broken symlinks"));
+    }
+
     #[test]
     fn validate_path_home_directory() {
         let home = std::env::var("HOME").unwrap();
@@ -335,6 +822,40 @@ mod tests {
         assert_eq!(report.duplicates.len(), 1);
     }
 
+    #[test]
+    fn path_validation_serializes_tagged() {
+        assert_eq!(
+            serde_json::to_value(PathValidation::NotFound("/x".into())).unwrap(),
+            serde_json::json!({"status": "not_found", "path": "/x"})
+        );
+        assert_eq!(
+            serde_json::to_value(PathValidation::BrokenSymlink("/l".into())).unwrap(),
+            serde_json::json!({"status": "broken_symlink", "path": "/l"})
+        );
+        assert_eq!(
+            serde_json::to_value(PathValidation::Valid).unwrap(),
+            serde_json::json!({"status": "valid"})
+        );
+    }
+
+    #[test]
+    fn report_roundtrips_through_json() {
+        let report = ValidationReport {
+            total: 5,
+            valid_count: 1,
+            errors: vec![
+                PathValidation::NotFound("/gone".into()),
+                PathValidation::NotReadable("/locked".into()),
+                PathValidation::NotAbsolute("rel".into()),
+                PathValidation::BrokenSymlink("/dangling".into()),
+            ],
+            duplicates: vec!["/twice".into()],
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        let back: ValidationReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, report);
+    }
+
     // --- ValidationReport ---
 
     #[test]
@@ -378,6 +899,20 @@ mod tests {
         assert!(!report.has_issues());
     }
 
+    #[test]
+    fn report_skipped_paths_lists_invalid_paths_only() {
+        let report = ValidationReport {
+            total: 4,
+            valid_count: 1,
+            errors: vec![
+                PathValidation::NotFound("/gone".into()),
+                PathValidation::BrokenSymlink("/dangling".into()),
+            ],
+            duplicates: vec!["/twice".into()],
+        };
+        assert_eq!(report.skipped_paths(), vec!["/gone", "/dangling"]);
+    }
+
     #[test]
     fn report_is_ok_false_with_errors() {
         let report = ValidationReport {
@@ -425,11 +960,133 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("not a directory"));
     }
 
+    #[test]
+    fn destination_reachable_without_creating_it() {
+        let root = tempfile::tempdir().unwrap();
+        let destination = root.path().join("Backup/Mac");
+        assert!(destination_reachable(root.path(), &destination));
+        assert!(!destination.exists());
+    }
+
+    #[test]
+    fn destination_unreachable_when_root_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("GoogleDrive-offline");
+        assert!(!destination_reachable(&root, &root.join("Backup/Mac")));
+    }
+
+    #[test]
+    fn destination_unreachable_when_blocked_by_a_file() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("Backup"), "").unwrap();
+        let destination = root.path().join("Backup/Mac");
+        assert!(!destination_reachable(root.path(), &destination));
+    }
+
+    // --- is_destination_mounted ---
+
+    #[test]
+    fn destination_mounted_when_root_is_listable() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(is_destination_mounted(root.path(), MOUNT_PROBE_TIMEOUT));
+        assert!(check_destination_mounted(root.path(), MOUNT_PROBE_TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn destination_not_mounted_when_drive_root_is_missing() {
+        // Drive offline: the account folder is left behind without its
+        // drive root
+        let account = tempfile::tempdir().unwrap();
+        let root = account.path().join("My Drive");
+        assert!(!is_destination_mounted(&root, MOUNT_PROBE_TIMEOUT));
+
+        let err = check_destination_mounted(&root, MOUNT_PROBE_TIMEOUT).unwrap_err();
+        assert!(matches!(err, ShrikeError::SyncFailed(_)));
+        assert!(err.to_string().contains("Google Drive appears offline"));
+    }
+
+    #[test]
+    fn pre_sync_check_fails_fast_when_drive_is_offline() {
+        let account = tempfile::tempdir().unwrap();
+        let root = account.path().join("My Drive");
+        let destination = root.join("Backup/Mac");
+        let paths = vec!["/etc/hosts".to_string()];
+
+        let err = pre_sync_check(&paths, &root, destination.to_str().unwrap(), false).unwrap_err();
+        assert!(err.to_string().contains("Google Drive appears offline"));
+        assert!(!root.exists(), "the offline mount must not be recreated");
+    }
+
+    // --- validate_filelist_file ---
+
+    #[test]
+    fn validate_filelist_file_matches_in_memory_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("exists.txt");
+        fs::write(&existing, "x").unwrap();
+        let existing = existing.to_str().unwrap().to_string();
+
+        let mut paths = Vec::new();
+        for i in 0..5000 {
+            paths.push(match i % 4 {
+                0 => existing.clone(),
+                1 => format!("/nonexistent/shrike/{i}"),
+                2 => format!("relative/{i}"),
+                _ => format!("{}/missing-{i}", dir.path().display()),
+            });
+        }
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for path in &paths {
+            writeln!(file, "{path}").unwrap();
+            if path.starts_with("relative/1") {
+                writeln!(file).unwrap();
+            }
+        }
+        file.flush().unwrap();
+
+        let streamed = validate_filelist_file(file.path()).unwrap();
+        assert_eq!(streamed, validate_filelist(&paths));
+        assert_eq!(streamed.total, 5000);
+        assert_eq!(streamed.duplicates.len(), 1249);
+    }
+
+    #[test]
+    fn validate_filelist_file_missing_file_errors() {
+        assert!(validate_filelist_file(Path::new("/nonexistent/filelist.txt")).is_err());
+    }
+
+    #[test]
+    fn pre_sync_check_file_rejects_empty_filelist() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let err =
+            pre_sync_check_file(file.path(), Path::new("/tmp"), "/tmp/dest", false).unwrap_err();
+        assert!(err.to_string().contains("no entries"));
+    }
+
+    #[test]
+    fn dry_run_check_file_does_not_create_destination() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "/tmp").unwrap();
+        file.flush().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("Backup/Mac");
+
+        let report = dry_run_check_file(file.path(), destination.to_str().unwrap()).unwrap();
+        assert_eq!(report.valid_count, 1);
+        assert!(!destination.exists());
+
+        // A destination that exists but is a file is still rejected
+        let not_a_dir = dir.path().join("file");
+        fs::write(&not_a_dir, "").unwrap();
+        assert!(dry_run_check_file(file.path(), not_a_dir.to_str().unwrap()).is_err());
+    }
+
     // --- pre_sync_check ---
 
     #[test]
     fn pre_sync_check_empty_entries_errors() {
-        let result = pre_sync_check(&[], "/tmp/dest");
+        let result = pre_sync_check(&[], Path::new("/tmp"), "/tmp/dest", false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("no entries"));
     }
@@ -438,7 +1095,8 @@ mod tests {
     fn pre_sync_check_all_valid() {
         let dir = tempfile::tempdir().unwrap();
         let paths = vec!["/etc/hosts".to_string()];
-        let report = pre_sync_check(&paths, dir.path().to_str().unwrap()).unwrap();
+        let report =
+            pre_sync_check(&paths, dir.path(), dir.path().to_str().unwrap(), false).unwrap();
         assert!(report.is_ok());
     }
 
@@ -446,9 +1104,17 @@ mod tests {
     fn pre_sync_check_all_invalid_errors() {
         let dir = tempfile::tempdir().unwrap();
         let paths = vec!["/nonexistent/x".to_string()];
-        let result = pre_sync_check(&paths, dir.path().to_str().unwrap());
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("no valid paths"));
+        let result = pre_sync_check(&paths, dir.path(), dir.path().to_str().unwrap(), false);
+        match result.unwrap_err() {
+            ShrikeError::ValidationFailed(report) => {
+                assert_eq!(report.valid_count, 0);
+                assert_eq!(
+                    report.errors,
+                    vec![PathValidation::NotFound("/nonexistent/x".into())]
+                );
+            }
+            other => panic!("expected ValidationFailed, got: {other}"),
+        }
     }
 
     #[test]
@@ -458,7 +1124,8 @@ mod tests {
             "/etc/hosts".to_string(),
             "/nonexistent/file.txt".to_string(),
         ];
-        let report = pre_sync_check(&paths, dir.path().to_str().unwrap()).unwrap();
+        let report =
+            pre_sync_check(&paths, dir.path(), dir.path().to_str().unwrap(), false).unwrap();
         assert!(report.has_issues());
         assert_eq!(report.valid_count, 1);
     }
@@ -468,7 +1135,8 @@ mod tests {
         // Use a file as destination (not a dir)
         let file = tempfile::NamedTempFile::new().unwrap();
         let paths = vec!["/etc/hosts".to_string()];
-        let result = pre_sync_check(&paths, file.path().to_str().unwrap());
+        let root = file.path().parent().unwrap();
+        let result = pre_sync_check(&paths, root, file.path().to_str().unwrap(), false);
         assert!(result.is_err());
     }
 }
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Component, Path, PathBuf};
 
 use crate::error::ShrikeError;
@@ -22,6 +24,44 @@ pub fn default_webhook_port() -> u16 {
     }
 }
 
+/// Ports after `webhook_port` tried by `WebhookPortStrategy::NextFree`.
+pub const WEBHOOK_PORT_ATTEMPTS: u16 = 10;
+
+/// Highest zlib level rsync accepts for `--compress-level`.
+pub const MAX_COMPRESS_LEVEL: u8 = 9;
+
+/// Characters exFAT, FAT and Windows filesystems reject in file names.
+const PORTABLE_FORBIDDEN_CHARS: &str = "<>:\"\\|?*";
+
+/// Device names Windows (and so exFAT/FAT drives used there) reserves.
+const RESERVED_DEVICE_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Percentage of `max_entries` at which adding entries logs a warning.
+pub const ENTRY_WARN_PERCENT: usize = 90;
+
+/// Junk excluded from every entry in new configs.
+pub const DEFAULT_GLOBAL_EXCLUDES: &[&str] = &[".DS_Store", "*.pyc", "__pycache__/", ".git/"];
+
+/// Default webhook bind address: loopback, so only this machine can reach it.
+pub const DEFAULT_WEBHOOK_BIND_ADDR: &str = "127.0.0.1";
+
+fn default_webhook_bind_addr() -> String {
+    DEFAULT_WEBHOOK_BIND_ADDR.to_string()
+}
+
+/// rsync exit codes retried by default: file I/O error (11), partial
+/// transfer due to error (23), and timeout (30). A Drive mount that is still
+/// waking up produces these; usage (1) or protocol (2) errors never fix
+/// themselves.
+pub const DEFAULT_TRANSIENT_EXIT_CODES: &[i32] = &[11, 23, 30];
+
+fn default_transient_exit_codes() -> Vec<i32> {
+    DEFAULT_TRANSIENT_EXIT_CODES.to_vec()
+}
+
 /// The type of a backup entry (file or directory).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -38,6 +78,25 @@ pub struct BackupEntry {
     pub item_type: ItemType,
     pub added_at: DateTime<Utc>,
     pub last_synced: Option<DateTime<Utc>>,
+    /// Whether periodic syncs include this entry (manual syncs always do)
+    #[serde(default = "default_true")]
+    pub scheduled: bool,
+    /// Per-entry compression override; `None` inherits `AppSettings::compress`
+    #[serde(default)]
+    pub compress: Option<bool>,
+    /// rsync exclude patterns applied only when syncing this entry
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// Allowlist: when set, only files matching one of these rsync patterns
+    /// are synced from this entry (see `RsyncOptions::includes`)
+    #[serde(default)]
+    pub includes: Vec<String>,
+    /// Whether syncs include this entry at all; disabled entries stay listed
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// On-disk size in bytes, or `None` if it was never or couldn't be measured
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
 }
 
 impl BackupEntry {
@@ -49,53 +108,175 @@ impl BackupEntry {
             item_type,
             added_at: Utc::now(),
             last_synced: None,
+            scheduled: true,
+            compress: None,
+            excludes: Vec::new(),
+            includes: Vec::new(),
+            enabled: true,
+            size_bytes: None,
         }
     }
 }
 
-/// Detect the Google Drive "My Drive" path on macOS.
+/// Set `last_synced` to `synced_at` on every entry whose ID is in `ids`.
+///
+/// Entries added or removed while the sync ran are left alone. Returns the
+/// number of entries stamped.
+pub fn stamp_synced(items: &mut [BackupEntry], ids: &[Uuid], synced_at: DateTime<Utc>) -> usize {
+    let mut stamped = 0;
+    for item in items.iter_mut().filter(|e| ids.contains(&e.id)) {
+        item.last_synced = Some(synced_at);
+        stamped += 1;
+    }
+    stamped
+}
+
+/// How entry paths are laid out inside the backup destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathLayout {
+    /// Reproduce the full absolute source path (rsync `-R`)
+    #[default]
+    Full,
+    /// Drop the home directory, so `~/x` is backed up to `<dest>/x`; paths
+    /// outside home keep the full layout
+    RelativeToHome,
+}
+
+/// What the webhook server does when `webhook_port` is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPortStrategy {
+    /// Fail to start
+    #[default]
+    Fixed,
+    /// Try the next `WEBHOOK_PORT_ATTEMPTS` ports in turn
+    NextFree,
+    /// Let the OS assign an ephemeral port
+    Ephemeral,
+}
+
+/// Cloud storage provider whose synced folder holds the backup destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudProvider {
+    #[default]
+    GoogleDrive,
+    Dropbox,
+    OneDrive,
+}
+
+impl CloudProvider {
+    /// Returns true if `name` is one of this provider's account folders in
+    /// CloudStorage, e.g. `GoogleDrive-user@example.com`, `Dropbox`,
+    /// `Dropbox-Personal` or `OneDrive-Contoso`.
+    fn is_account_folder(self, name: &str) -> bool {
+        match self {
+            Self::GoogleDrive => name.starts_with("GoogleDrive-"),
+            // A single Dropbox account gets a bare `Dropbox` folder
+            Self::Dropbox => name == "Dropbox" || name.starts_with("Dropbox-"),
+            Self::OneDrive => name.starts_with("OneDrive-"),
+        }
+    }
+}
+
+/// Detect the folder files are synced from for `provider` on macOS.
 ///
-/// Scans `~/Library/CloudStorage/` for directories matching `GoogleDrive-*`,
-/// then looks inside for the user's drive root directory (e.g. "My Drive",
-/// "我的云端硬盘", "マイドライブ", etc.) by picking the first visible,
-/// non-special subdirectory.
+/// Scans `~/Library/CloudStorage/` for the provider's account directories.
+/// Dropbox and OneDrive sync the account folder itself. Google Drive nests
+/// the user's drive root inside it (e.g. "My Drive", "我的云端硬盘",
+/// "マイドライブ", etc.), the first of `list_drive_roots`.
 ///
-/// Returns `None` if Google Drive is not installed or no drive root is found.
-pub fn detect_gdrive_path(cloud_storage_dir: &Path) -> Option<PathBuf> {
+/// Returns `None` if the provider is not installed or no root is found.
+pub fn detect_cloud_root(provider: CloudProvider, cloud_storage_dir: &Path) -> Option<PathBuf> {
     let entries = std::fs::read_dir(cloud_storage_dir).ok()?;
 
-    // Find the first GoogleDrive-* directory
-    let gdrive_account = entries.filter_map(|e| e.ok()).find(|e| {
-        e.file_name().to_string_lossy().starts_with("GoogleDrive-") && e.path().is_dir()
+    // Find the first account directory
+    let account = entries.filter_map(|e| e.ok()).find(|e| {
+        provider.is_account_folder(&e.file_name().to_string_lossy()) && e.path().is_dir()
     })?;
 
+    match provider {
+        CloudProvider::GoogleDrive => find_gdrive_root(&account.path()),
+        CloudProvider::Dropbox | CloudProvider::OneDrive => Some(account.path()),
+    }
+}
+
+/// Detect the Google Drive "My Drive" path on macOS.
+///
+/// Returns `None` if Google Drive is not installed or no drive root is found.
+pub fn detect_gdrive_path(cloud_storage_dir: &Path) -> Option<PathBuf> {
+    detect_cloud_root(CloudProvider::GoogleDrive, cloud_storage_dir)
+}
+
+/// Find the drive root inside a `GoogleDrive-*` account folder: the first
+/// of `list_drive_roots`, which puts "My Drive" first.
+fn find_gdrive_root(account_path: &Path) -> Option<PathBuf> {
+    list_drive_roots(account_path).into_iter().next()
+}
+
+/// Every folder inside a `GoogleDrive-*` account folder that can serve as
+/// `gdrive_path`: the visible, non-special subdirectories ("My Drive" or a
+/// localized equivalent) plus each Shared Drive, whose container folder
+/// itself is not writable and is replaced by its children.
+///
+/// "My Drive" comes first, then the rest sorted by path. Returns an empty
+/// list if the account folder can't be read.
+pub fn list_drive_roots(account_path: &Path) -> Vec<PathBuf> {
     // Known special directories inside the account folder to skip
     const SKIP_NAMES: &[&str] = &["Computers", "其他计算机", "他のパソコン"];
+    // The folder holding one subfolder per Shared Drive, localized
+    const SHARED_DRIVES_NAMES: &[&str] = &["Shared drives", "共享云端硬盘", "共有ドライブ"];
+
+    let named = |path: &Path, names: &[&str]| {
+        path.file_name()
+            .is_some_and(|name| names.iter().any(|n| name == *n))
+    };
+    let mut roots: Vec<PathBuf> = visible_subdirs(account_path)
+        .into_iter()
+        .filter(|path| !named(path, SKIP_NAMES))
+        .flat_map(|path| {
+            if named(&path, SHARED_DRIVES_NAMES) {
+                visible_subdirs(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect();
+    // Prefer "My Drive" over other dirs
+    roots.sort_by_key(|path| (!path.ends_with("My Drive"), path.clone()));
+    roots
+}
 
-    // Look for the drive root: first non-hidden, non-special subdirectory
-    let account_path = gdrive_account.path();
-    let children = std::fs::read_dir(&account_path).ok()?;
+/// Non-hidden subdirectories of `dir`, or none if it can't be read.
+fn visible_subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(children) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    children
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.') && e.path().is_dir())
+        .map(|e| e.path())
+        .collect()
+}
 
-    let drive_root = children
+/// Drive roots (see `list_drive_roots`) of every Google Drive account in
+/// `cloud_storage_dir`, so the user can pick one other than the detected
+/// default, e.g. a Shared Drive.
+pub fn list_gdrive_roots(cloud_storage_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(cloud_storage_dir) else {
+        return Vec::new();
+    };
+    let mut accounts: Vec<PathBuf> = entries
         .filter_map(|e| e.ok())
         .filter(|e| {
-            let name = e.file_name();
-            let name_str = name.to_string_lossy();
-            e.path().is_dir()
-                && !name_str.starts_with('.')
-                && !SKIP_NAMES.contains(&name_str.as_ref())
+            CloudProvider::GoogleDrive.is_account_folder(&e.file_name().to_string_lossy())
+                && e.path().is_dir()
         })
-        .min_by_key(|e| {
-            // Prefer "My Drive" or localized equivalents over other dirs
-            let name = e.file_name().to_string_lossy().to_string();
-            if name == "My Drive" {
-                0
-            } else {
-                1
-            }
-        })?;
-
-    Some(drive_root.path())
+        .map(|e| e.path())
+        .collect();
+    accounts.sort();
+    accounts.iter().flat_map(|a| list_drive_roots(a)).collect()
 }
 
 /// Return the default CloudStorage directory for the current user.
@@ -104,7 +285,10 @@ pub fn default_cloud_storage_dir() -> Option<PathBuf> {
 }
 
 /// Application settings persisted in the Tauri store.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// `Debug` redacts the webhook token and HMAC secret (see `redact_secret`),
+/// so settings can be logged.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AppSettings {
     pub gdrive_path: String,
     pub backup_dir_name: String,
@@ -122,12 +306,259 @@ pub struct AppSettings {
     pub theme: String,
     #[serde(default = "default_auto")]
     pub language: String,
+    /// Octal umask applied to rsync (e.g. "077"); `None` inherits the app's
+    #[serde(default)]
+    pub file_umask: Option<String>,
+    /// Log verbosity: error, warn, info, debug, or trace
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Compress file data during transfer (`-z`); entries may override
+    #[serde(default)]
+    pub compress: bool,
+    /// Hours after the last successful sync before the backup counts as stale
+    #[serde(default = "default_stale_after_hours")]
+    pub stale_after_hours: u32,
+    /// Write an age-encrypted archive instead of mirroring files with rsync
+    #[serde(default)]
+    pub encrypt: bool,
+    /// age X25519 public key (`age1...`) the archive is encrypted to
+    #[serde(default)]
+    pub encryption_recipient: Option<String>,
+    /// Destinations synced after the primary `gdrive_path` one, in order
+    #[serde(default)]
+    pub extra_destinations: Vec<Destination>,
+    /// Compare file contents (`--checksum`) instead of size and mtime.
+    /// Catches edits that preserve timestamps, but reads every file on both
+    /// sides, so syncs are much slower.
+    #[serde(default)]
+    pub verify_checksums: bool,
+    /// Sync scheduled entries every N minutes; `None` or 0 disables it
+    #[serde(default)]
+    pub auto_sync_interval_minutes: Option<u32>,
+    /// Times a transient rsync failure is retried
+    #[serde(default)]
+    pub max_sync_retries: u32,
+    /// rsync exit codes treated as transient, and so worth retrying
+    #[serde(default = "default_transient_exit_codes")]
+    pub transient_exit_codes: Vec<i32>,
+    /// Skip the pre-sync free-space check, which walks every tracked path.
+    /// Worth it for incremental syncs where the delta is tiny.
+    #[serde(default)]
+    pub skip_space_check: bool,
+    /// Back up what symlinks point to (`rsync -L`) instead of copying the
+    /// links themselves
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Provider whose synced folder `gdrive_path` lives in
+    #[serde(default)]
+    pub cloud_provider: CloudProvider,
+    /// IP address the webhook server binds to. `0.0.0.0` accepts requests
+    /// from the network, guarded only by the bearer token.
+    #[serde(default = "default_webhook_bind_addr")]
+    pub webhook_bind_addr: String,
+    /// Shared secret for HMAC-SHA256 request signatures. When set, webhook
+    /// requests must carry `X-Shrike-Signature: sha256=<hex>` over the body.
+    #[serde(default)]
+    pub webhook_hmac_secret: Option<String>,
+    /// With `webhook_hmac_secret` set, still accept unsigned requests that
+    /// carry the bearer token
+    #[serde(default)]
+    pub webhook_token_fallback: bool,
+    /// Maximum `POST /sync` webhook requests per minute; 0 disables the limit
+    #[serde(default = "default_webhook_rate_limit_per_min")]
+    pub webhook_rate_limit_per_min: u32,
+    /// rsync exclude patterns applied to every entry, via `--exclude-from`.
+    /// New configs start with `DEFAULT_GLOBAL_EXCLUDES`.
+    #[serde(default)]
+    pub global_excludes: Vec<String>,
+    /// Delete backup files whose source was deleted or excluded (`--delete
+    /// --delete-excluded`). Destructive: a source wiped by mistake wipes its
+    /// backup on the next sync.
+    #[serde(default)]
+    pub mirror_deletes: bool,
+    /// rsync executable to run (e.g. `/opt/homebrew/bin/rsync`); `None` uses
+    /// `rsync` from `PATH`
+    #[serde(default)]
+    pub rsync_binary: Option<String>,
+    /// Kill rsync and fail the sync after this many seconds, e.g. when the
+    /// Drive mount hangs; `None` or 0 waits indefinitely
+    #[serde(default)]
+    pub sync_timeout_secs: Option<u64>,
+    /// zlib level (0-9) for compressed transfers (`--compress-level`);
+    /// `None` uses rsync's default
+    #[serde(default)]
+    pub compress_level: Option<u8>,
+    /// Fallback when `webhook_port` is taken; the port actually bound is
+    /// reported by the `webhook_status` command
+    #[serde(default)]
+    pub webhook_port_strategy: WebhookPortStrategy,
+    /// Show a system notification when a sync from the app, the scheduler
+    /// or the webhook finishes
+    #[serde(default = "default_true")]
+    pub notify_on_sync: bool,
+    /// Most entries the backup list may hold, so a pasted directory tree
+    /// can't bloat the store; 0 disables the cap
+    #[serde(default = "default_max_entries")]
+    pub max_entries: u32,
+    /// Require `machine_name` and `backup_dir_name` to be valid on exFAT,
+    /// FAT and Windows filesystems too. Off by default on macOS, where the
+    /// destination is usually APFS
+    #[serde(default = "default_portable_names")]
+    pub portable_names: bool,
+    /// Where entries land inside the destination: `full` mirrors each
+    /// absolute source path, `relative_to_home` drops the home directory.
+    #[serde(default)]
+    pub path_layout: PathLayout,
+    /// Lines of rsync stdout and stderr kept in a `SyncResult` (the last
+    /// ones); 0 keeps all of it
+    #[serde(default = "default_max_output_lines")]
+    pub max_output_lines: u32,
+}
+
+/// Secrets shorter than this are redacted to `…` alone, since their first
+/// 4 characters would give away too much of them.
+const MIN_REDACTED_PREFIX_LEN: usize = 8;
+
+/// Mask a secret for logs: its first 4 characters followed by `…`, or just
+/// `…` for a secret under `MIN_REDACTED_PREFIX_LEN` characters.
+pub fn redact_secret(secret: &str) -> String {
+    if secret.chars().count() < MIN_REDACTED_PREFIX_LEN {
+        return "…".to_string();
+    }
+    let prefix: String = secret.chars().take(4).collect();
+    format!("{prefix}…")
+}
+
+impl fmt::Debug for AppSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Destructured so a new field can't be added without deciding
+        // whether it is a secret
+        let Self {
+            gdrive_path,
+            backup_dir_name,
+            machine_name,
+            webhook_port,
+            webhook_token,
+            show_tray_icon,
+            show_dock_icon,
+            autostart,
+            theme,
+            language,
+            file_umask,
+            log_level,
+            compress,
+            stale_after_hours,
+            encrypt,
+            encryption_recipient,
+            extra_destinations,
+            verify_checksums,
+            auto_sync_interval_minutes,
+            max_sync_retries,
+            transient_exit_codes,
+            skip_space_check,
+            follow_symlinks,
+            cloud_provider,
+            webhook_bind_addr,
+            webhook_hmac_secret,
+            webhook_token_fallback,
+            webhook_rate_limit_per_min,
+            global_excludes,
+            mirror_deletes,
+            rsync_binary,
+            sync_timeout_secs,
+            compress_level,
+            webhook_port_strategy,
+            notify_on_sync,
+            max_entries,
+            portable_names,
+            path_layout,
+            max_output_lines,
+        } = self;
+        f.debug_struct("AppSettings")
+            .field("gdrive_path", gdrive_path)
+            .field("backup_dir_name", backup_dir_name)
+            .field("machine_name", machine_name)
+            .field("webhook_port", webhook_port)
+            .field("webhook_token", &redact_secret(webhook_token))
+            .field("show_tray_icon", show_tray_icon)
+            .field("show_dock_icon", show_dock_icon)
+            .field("autostart", autostart)
+            .field("theme", theme)
+            .field("language", language)
+            .field("file_umask", file_umask)
+            .field("log_level", log_level)
+            .field("compress", compress)
+            .field("stale_after_hours", stale_after_hours)
+            .field("encrypt", encrypt)
+            .field("encryption_recipient", encryption_recipient)
+            .field("extra_destinations", extra_destinations)
+            .field("verify_checksums", verify_checksums)
+            .field("auto_sync_interval_minutes", auto_sync_interval_minutes)
+            .field("max_sync_retries", max_sync_retries)
+            .field("transient_exit_codes", transient_exit_codes)
+            .field("skip_space_check", skip_space_check)
+            .field("follow_symlinks", follow_symlinks)
+            .field("cloud_provider", cloud_provider)
+            .field("webhook_bind_addr", webhook_bind_addr)
+            .field(
+                "webhook_hmac_secret",
+                &webhook_hmac_secret.as_deref().map(redact_secret),
+            )
+            .field("webhook_token_fallback", webhook_token_fallback)
+            .field("webhook_rate_limit_per_min", webhook_rate_limit_per_min)
+            .field("global_excludes", global_excludes)
+            .field("mirror_deletes", mirror_deletes)
+            .field("rsync_binary", rsync_binary)
+            .field("sync_timeout_secs", sync_timeout_secs)
+            .field("compress_level", compress_level)
+            .field("webhook_port_strategy", webhook_port_strategy)
+            .field("notify_on_sync", notify_on_sync)
+            .field("max_entries", max_entries)
+            .field("portable_names", portable_names)
+            .field("path_layout", path_layout)
+            .field("max_output_lines", max_output_lines)
+            .finish()
+    }
+}
+
+/// A backup location: `<gdrive_path>/<backup_dir_name>/<machine_name>`.
+///
+/// The primary destination is stored inline in `AppSettings`, so configs
+/// written before multiple destinations existed load as a single one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Destination {
+    pub gdrive_path: String,
+    pub backup_dir_name: String,
 }
 
 fn default_auto() -> String {
     "auto".to_string()
 }
 
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_webhook_rate_limit_per_min() -> u32 {
+    10
+}
+
+fn default_max_entries() -> u32 {
+    10_000
+}
+
+fn default_max_output_lines() -> u32 {
+    500
+}
+
+fn default_portable_names() -> bool {
+    !cfg!(target_os = "macos")
+}
+
+fn default_stale_after_hours() -> u32 {
+    24
+}
+
 fn default_true() -> bool {
     true
 }
@@ -158,13 +589,240 @@ impl Default for AppSettings {
             autostart: false,
             theme: "auto".to_string(),
             language: "auto".to_string(),
+            file_umask: None,
+            log_level: "info".to_string(),
+            compress: false,
+            stale_after_hours: 24,
+            encrypt: false,
+            encryption_recipient: None,
+            extra_destinations: vec![],
+            verify_checksums: false,
+            auto_sync_interval_minutes: None,
+            max_sync_retries: 0,
+            transient_exit_codes: default_transient_exit_codes(),
+            skip_space_check: false,
+            follow_symlinks: false,
+            cloud_provider: CloudProvider::GoogleDrive,
+            webhook_bind_addr: default_webhook_bind_addr(),
+            webhook_hmac_secret: None,
+            webhook_token_fallback: false,
+            webhook_rate_limit_per_min: default_webhook_rate_limit_per_min(),
+            global_excludes: DEFAULT_GLOBAL_EXCLUDES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            mirror_deletes: false,
+            rsync_binary: None,
+            sync_timeout_secs: None,
+            compress_level: None,
+            webhook_port_strategy: WebhookPortStrategy::Fixed,
+            notify_on_sync: true,
+            max_entries: default_max_entries(),
+            portable_names: default_portable_names(),
+            path_layout: PathLayout::Full,
+            max_output_lines: default_max_output_lines(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl AppSettings {
+    /// Settings for tests that back up to `<gdrive_path>/Backup/TestMac`.
+    ///
+    /// Anything that would make tests depend on the machine running them
+    /// (global excludes, the entry cap, portable names) is turned off; every
+    /// other field keeps its default, so adding a field doesn't break the
+    /// tests built on this. Integration tests get it through the
+    /// `test-support` feature.
+    pub fn for_test(gdrive_path: &str) -> Self {
+        Self {
+            gdrive_path: gdrive_path.to_string(),
+            backup_dir_name: "Backup".to_string(),
+            machine_name: "TestMac".to_string(),
+            webhook_port: 0,
+            webhook_token: "test".to_string(),
+            webhook_rate_limit_per_min: 0,
+            global_excludes: vec![],
+            max_entries: 0,
+            portable_names: false,
+            ..Self::default()
         }
     }
 }
 
+/// Parse an octal umask string such as "077" or "0022".
+///
+/// Accepts 1–4 octal digits with a value no greater than `0o777`.
+pub fn parse_umask(value: &str) -> Result<u32, ShrikeError> {
+    let invalid = || ShrikeError::InvalidSettings(format!("invalid umask: {value:?}"));
+    if value.is_empty() || value.len() > 4 || !value.chars().all(|c| c.is_digit(8)) {
+        return Err(invalid());
+    }
+    let mask = u32::from_str_radix(value, 8).map_err(|_| invalid())?;
+    if mask > 0o777 {
+        return Err(invalid());
+    }
+    Ok(mask)
+}
+
 impl AppSettings {
+    /// Parsed `file_umask`, or `None` if unset.
+    pub fn umask(&self) -> Result<Option<u32>, ShrikeError> {
+        self.file_umask.as_deref().map(parse_umask).transpose()
+    }
+
+    /// Address the webhook server listens on: `webhook_bind_addr` and
+    /// `webhook_port`.
+    pub fn webhook_addr(&self) -> Result<SocketAddr, ShrikeError> {
+        let ip: IpAddr = self.webhook_bind_addr.trim().parse().map_err(|_| {
+            ShrikeError::InvalidSettings(format!(
+                "webhook_bind_addr is not an IP address: {}",
+                self.webhook_bind_addr
+            ))
+        })?;
+        Ok(SocketAddr::new(ip, self.webhook_port))
+    }
+
+    /// Addresses the webhook server tries in order: `webhook_addr`, then
+    /// the fallbacks `webhook_port_strategy` allows.
+    pub fn webhook_addrs(&self) -> Result<Vec<SocketAddr>, ShrikeError> {
+        let ip = self.webhook_addr()?.ip();
+        let mut ports = vec![self.webhook_port];
+        match self.webhook_port_strategy {
+            WebhookPortStrategy::Fixed => {}
+            WebhookPortStrategy::NextFree => ports.extend(
+                (1..=WEBHOOK_PORT_ATTEMPTS).filter_map(|n| self.webhook_port.checked_add(n)),
+            ),
+            WebhookPortStrategy::Ephemeral => ports.push(0),
+        }
+        Ok(ports
+            .iter()
+            .map(|&port| SocketAddr::new(ip, port))
+            .collect())
+    }
+
+    /// The rsync executable to run: `rsync_binary`, or `rsync` from `PATH`.
+    pub fn rsync_program(&self) -> &str {
+        self.rsync_binary
+            .as_deref()
+            .filter(|b| !b.is_empty())
+            .unwrap_or("rsync")
+    }
+
+    /// How long a single rsync run may take, from `sync_timeout_secs`.
+    pub fn sync_timeout(&self) -> Option<std::time::Duration> {
+        self.sync_timeout_secs
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs)
+    }
+
+    /// How many lines of rsync output a `SyncResult` keeps, from
+    /// `max_output_lines`; `None` keeps all of it.
+    pub fn output_line_cap(&self) -> Option<usize> {
+        Some(self.max_output_lines as usize).filter(|&lines| lines > 0)
+    }
+
+    /// Check that the backup list may grow to `count` entries.
+    pub fn check_entry_count(&self, count: usize) -> Result<(), ShrikeError> {
+        if self.max_entries > 0 && count > self.max_entries as usize {
+            return Err(ShrikeError::TooManyEntries(self.max_entries));
+        }
+        Ok(())
+    }
+
+    /// Whether `count` entries is within `ENTRY_WARN_PERCENT` of the cap.
+    pub fn entries_near_limit(&self, count: usize) -> bool {
+        let max = self.max_entries as usize;
+        max > 0 && count * 100 >= max * ENTRY_WARN_PERCENT
+    }
+
+    /// Check settings that can be validated without touching the filesystem.
+    pub fn validate(&self) -> Result<(), ShrikeError> {
+        self.umask()?;
+        self.webhook_addr()?;
+        self.log_level.parse::<crate::logging::LogLevel>()?;
+        if let Some(level) = self.compress_level.filter(|&l| l > MAX_COMPRESS_LEVEL) {
+            return Err(ShrikeError::InvalidSettings(format!(
+                "compress_level must be between 0 and {MAX_COMPRESS_LEVEL}, got {level}"
+            )));
+        }
+        if self.stale_after_hours == 0 {
+            return Err(ShrikeError::InvalidSettings(
+                "stale_after_hours must be at least 1".to_string(),
+            ));
+        }
+        if self.encrypt {
+            let recipient = self.encryption_recipient.as_deref().ok_or_else(|| {
+                ShrikeError::InvalidSettings("encryption requires a recipient key".to_string())
+            })?;
+            crate::sync::encrypted::parse_recipient(recipient)?;
+        }
+        let destinations = self.destinations();
+        for (i, destination) in destinations.iter().enumerate().skip(1) {
+            self.for_destination(destination)
+                .destination_path()
+                .map_err(|e| ShrikeError::InvalidSettings(format!("destination {}: {e}", i + 1)))?;
+            if destinations[..i].contains(destination) {
+                return Err(ShrikeError::InvalidSettings(format!(
+                    "destination {} is listed twice: {}/{}",
+                    i + 1,
+                    destination.gdrive_path,
+                    destination.backup_dir_name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adopt a freshly detected Drive root as `gdrive_path`, returning
+    /// whether it changed. A path that is already set (detected earlier or
+    /// chosen by the user) is only replaced with `force`.
+    pub fn adopt_detected_gdrive_path(&mut self, detected: &Path, force: bool) -> bool {
+        if !self.gdrive_path.is_empty() && !force {
+            return false;
+        }
+        let detected = detected.to_string_lossy();
+        if self.gdrive_path == detected {
+            return false;
+        }
+        self.gdrive_path = detected.into_owned();
+        true
+    }
+
+    /// Every configured destination, primary first.
+    pub fn destinations(&self) -> Vec<Destination> {
+        let primary = Destination {
+            gdrive_path: self.gdrive_path.clone(),
+            backup_dir_name: self.backup_dir_name.clone(),
+        };
+        std::iter::once(primary)
+            .chain(self.extra_destinations.iter().cloned())
+            .collect()
+    }
+
+    /// These settings with `destination` as the only destination.
+    pub fn for_destination(&self, destination: &Destination) -> AppSettings {
+        AppSettings {
+            gdrive_path: destination.gdrive_path.clone(),
+            backup_dir_name: destination.backup_dir_name.clone(),
+            extra_destinations: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Full path of every destination, primary first.
+    pub fn destination_paths(&self) -> Result<Vec<String>, ShrikeError> {
+        self.destinations()
+            .iter()
+            .map(|d| self.for_destination(d).destination_path())
+            .collect()
+    }
+
     /// Full destination path for rsync: gdrive_path/backup_dir_name/machine_name
     ///
+    /// Trailing slashes on `gdrive_path` are dropped, so the path never
+    /// contains `//`.
+    ///
     /// Returns an error if:
     /// - `gdrive_path` is empty (Google Drive not detected)
     /// - `backup_dir_name` or `machine_name` contain path traversal (`..`)
@@ -177,21 +835,31 @@ impl AppSettings {
         }
 
         // Sanitize backup_dir_name: must be a single, safe path component
-        Self::validate_path_component(&self.backup_dir_name, "backup directory name")?;
+        let portable = self.portable_names;
+        Self::validate_path_component(&self.backup_dir_name, "backup directory name", portable)?;
         // Sanitize machine_name: must be a single, safe path component
-        Self::validate_path_component(&self.machine_name, "machine name")?;
+        Self::validate_path_component(&self.machine_name, "machine name", portable)?;
 
         Ok(format!(
             "{}/{}/{}",
-            self.gdrive_path, self.backup_dir_name, self.machine_name
+            self.gdrive_path.trim_end_matches('/'),
+            self.backup_dir_name,
+            self.machine_name
         ))
     }
 
     /// Validate that a string is a safe, single path component.
     ///
     /// Rejects empty strings, path separators, `..` traversal, and
-    /// any component that is not a normal filename.
-    fn validate_path_component(value: &str, field_name: &str) -> Result<(), ShrikeError> {
+    /// any component that is not a normal filename. With `portable`, also
+    /// rejects names that exFAT, FAT or Windows filesystems can't store:
+    /// leading/trailing whitespace, a trailing `.`, reserved device names
+    /// (`CON`, `COM1`, ...) and the characters `<>:"\|?*`.
+    fn validate_path_component(
+        value: &str,
+        field_name: &str,
+        portable: bool,
+    ) -> Result<(), ShrikeError> {
         if value.is_empty() {
             return Err(ShrikeError::SyncFailed(format!(
                 "{field_name} cannot be empty"
@@ -206,12 +874,45 @@ impl AppSettings {
                 "{field_name} contains path separators: {value}"
             )));
         }
-        match components[0] {
-            Component::Normal(_) => Ok(()),
-            _ => Err(ShrikeError::SyncFailed(format!(
+        if !matches!(components[0], Component::Normal(_)) {
+            return Err(ShrikeError::SyncFailed(format!(
                 "{field_name} contains invalid path component: {value}"
-            ))),
+            )));
+        }
+        if portable {
+            Self::validate_portable_name(value, field_name)?;
+        }
+        Ok(())
+    }
+
+    /// The `portable` checks of `validate_path_component`.
+    fn validate_portable_name(value: &str, field_name: &str) -> Result<(), ShrikeError> {
+        let invalid = |reason: &str| {
+            Err(ShrikeError::SyncFailed(format!(
+                "{field_name} {reason}: {value:?}"
+            )))
+        };
+        if value.trim() != value {
+            return invalid("has leading or trailing whitespace");
         }
+        if value.ends_with('.') {
+            return invalid("ends with a dot");
+        }
+        if let Some(c) = value
+            .chars()
+            .find(|c| c.is_control() || PORTABLE_FORBIDDEN_CHARS.contains(*c))
+        {
+            return invalid(&format!("contains {c:?}, which some filesystems reject"));
+        }
+        // Reserved with or without an extension, in any case
+        let stem = value.split('.').next().unwrap_or(value);
+        if RESERVED_DEVICE_NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(stem))
+        {
+            return invalid("is a reserved device name");
+        }
+        Ok(())
     }
 }
 
@@ -231,6 +932,18 @@ pub struct SyncResult {
     pub dirs_transferred: u64,
     /// Total bytes transferred
     pub bytes_transferred: u64,
+    /// Files new to the destination
+    #[serde(default)]
+    pub created: u64,
+    /// Files whose content or attributes changed
+    #[serde(default)]
+    pub updated: u64,
+    /// Files deleted from the destination (with `mirror_deletes`)
+    #[serde(default)]
+    pub deleted: u64,
+    /// Files itemized without changes
+    #[serde(default)]
+    pub unchanged: u64,
     /// rsync stdout (verbose output)
     pub stdout: String,
     /// rsync stderr (warnings/errors)
@@ -239,14 +952,134 @@ pub struct SyncResult {
     pub exit_code: i32,
     /// Timestamp of this sync
     pub synced_at: DateTime<Utc>,
+    /// Non-fatal warnings reported on stderr (e.g. vanished files)
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Paths left out because they failed validation (missing, unreadable,
+    /// duplicated); see `ValidationReport::skipped_paths`
+    #[serde(default)]
+    pub skipped_paths: Vec<String>,
+    /// `stdout` or `stderr` were cut down to their last lines (see
+    /// `AppSettings::max_output_lines`); counts still cover the full output
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 impl SyncResult {
+    /// Returns true for exit code 0 and for soft exits such as vanished
+    /// files (see `SOFT_EXIT_CODES`), whose details are in `warnings`.
     pub fn is_success(&self) -> bool {
-        self.exit_code == 0
+        self.exit_code == 0 || crate::sync::executor::is_soft_exit_code(self.exit_code)
+    }
+}
+
+/// Maximum number of runs kept in the sync history.
+pub const SYNC_HISTORY_CAP: usize = 50;
+
+/// One recorded sync run, successful or not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncHistoryEntry {
+    pub id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub files_transferred: u64,
+    #[serde(default)]
+    pub dirs_transferred: u64,
+    pub bytes_transferred: u64,
+    /// rsync exit code; -1 if the sync failed before rsync reported one
+    pub exit_code: i32,
+    /// Error message if the sync failed
+    pub error: Option<String>,
+}
+
+impl SyncHistoryEntry {
+    /// Record the outcome of a sync that ran from `started_at` to `finished_at`.
+    pub fn from_outcome(
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        outcome: &crate::error::Result<SyncResult>,
+    ) -> Self {
+        let (files_transferred, dirs_transferred, bytes_transferred, exit_code, error) =
+            match outcome {
+                Ok(result) => (
+                    result.files_transferred,
+                    result.dirs_transferred,
+                    result.bytes_transferred,
+                    result.exit_code,
+                    None,
+                ),
+                Err(e) => {
+                    let exit_code = match e {
+                        ShrikeError::RsyncError { code, .. } => *code,
+                        _ => -1,
+                    };
+                    (0, 0, 0, exit_code, Some(e.to_string()))
+                }
+            };
+        Self {
+            id: Uuid::new_v4(),
+            started_at,
+            finished_at,
+            files_transferred,
+            dirs_transferred,
+            bytes_transferred,
+            exit_code,
+            error,
+        }
+    }
+}
+
+/// Add `entry` to a newest-first history, dropping runs beyond
+/// `SYNC_HISTORY_CAP`.
+pub fn push_history(history: &mut Vec<SyncHistoryEntry>, entry: SyncHistoryEntry) {
+    history.insert(0, entry);
+    history.truncate(SYNC_HISTORY_CAP);
+}
+
+/// Header row of `history_to_csv`.
+pub const HISTORY_CSV_HEADER: &str = "timestamp,files,dirs,bytes,exit_code,duration_ms";
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
     }
 }
 
+/// Render sync history as CSV, one row per run in the given order, after
+/// a header row. The timestamp is the run's start in RFC 3339.
+pub fn history_to_csv(history: &[SyncHistoryEntry]) -> String {
+    let mut csv = format!("{HISTORY_CSV_HEADER}\n");
+    for run in history {
+        let duration_ms = (run.finished_at - run.started_at).num_milliseconds().max(0);
+        let row = [
+            run.started_at.to_rfc3339(),
+            run.files_transferred.to_string(),
+            run.dirs_transferred.to_string(),
+            run.bytes_transferred.to_string(),
+            run.exit_code.to_string(),
+            duration_ms.to_string(),
+        ];
+        let row: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Progress of a running rsync pass, emitted as `sync://progress`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncProgress {
+    /// Overall completion of the current pass, 0-100
+    pub percent: u8,
+    /// Bytes transferred so far in the current pass
+    pub bytes: u64,
+    /// Path of the file most recently reported by rsync
+    pub current_file: String,
+}
+
 /// Current status of the sync engine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -255,32 +1088,291 @@ pub enum SyncStatus {
     Running,
 }
 
-/// A detected coding agent configuration.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct DetectedConfig {
-    /// Display name of the coding agent (e.g. "Claude Code")
-    pub agent: String,
-    /// Absolute path to the config directory or file
-    pub path: String,
-    /// Whether this is a directory or single file
-    pub item_type: ItemType,
+/// First-run checks for the onboarding flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Onboarding {
+    /// Settings have been saved at least once
+    pub has_settings: bool,
+    /// A Google Drive path is configured or was auto-detected
+    pub drive_detected: bool,
+    /// At least one backup entry exists
+    pub has_entries: bool,
+    /// At least one entry has been synced
+    pub has_synced: bool,
 }
 
-/// A child entry inside an agent's config directory (first level only).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct TreeChild {
-    /// File or directory name (not full path)
-    pub name: String,
-    /// Absolute path
-    pub path: String,
-    /// Whether this is a file or directory
-    pub item_type: ItemType,
+impl Onboarding {
+    /// Evaluate onboarding state from stored data.
+    ///
+    /// `settings` is `None` when nothing has been saved yet, in which case
+    /// `detected_gdrive` (the auto-detected Drive path) decides
+    /// `drive_detected`.
+    pub fn evaluate(
+        settings: Option<&AppSettings>,
+        items: &[BackupEntry],
+        detected_gdrive: Option<&Path>,
+    ) -> Self {
+        let drive_detected = match settings {
+            Some(s) => !s.gdrive_path.is_empty(),
+            None => detected_gdrive.is_some(),
+        };
+        Self {
+            has_settings: settings.is_some(),
+            drive_detected,
+            has_entries: !items.is_empty(),
+            has_synced: items.iter().any(|e| e.last_synced.is_some()),
+        }
+    }
+
+    /// Returns true if every onboarding step is complete.
+    pub fn is_complete(&self) -> bool {
+        self.has_settings && self.drive_detected && self.has_entries && self.has_synced
+    }
 }
 
-/// A tree-structured view of a coding agent's configuration.
-///
-/// Groups the agent's main config directory, its first-level children,
-/// and any sibling files (e.g. `.claude.json` next to `.claude/`).
+/// How recently the backup last succeeded, for a health badge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Freshness {
+    /// Most recent successful sync of any entry; `None` if never synced
+    pub last_synced: Option<DateTime<Utc>>,
+    /// Seconds since `last_synced`; `None` if never synced
+    pub age_secs: Option<i64>,
+    /// Never synced, or last synced more than `stale_after_hours` ago
+    pub stale: bool,
+}
+
+impl Freshness {
+    /// Evaluate freshness from the entries' `last_synced` times at `now`.
+    pub fn evaluate(items: &[BackupEntry], stale_after_hours: u32, now: DateTime<Utc>) -> Self {
+        let last_synced = items.iter().filter_map(|e| e.last_synced).max();
+        let age_secs = last_synced.map(|t| (now - t).num_seconds().max(0));
+        Self {
+            last_synced,
+            age_secs,
+            stale: age_secs.is_none_or(|age| age > i64::from(stale_after_hours) * 3600),
+        }
+    }
+
+    /// Returns true if no entry has ever been synced.
+    pub fn is_never_synced(&self) -> bool {
+        self.last_synced.is_none()
+    }
+}
+
+/// Whether Google Drive appears to be syncing the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriveStatus {
+    Online,
+    Offline,
+    Unknown,
+}
+
+/// A suggested new location for an entry whose source path has moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Relocation {
+    /// The entry whose path no longer exists
+    pub entry_id: Uuid,
+    /// The entry's current (missing) path
+    pub old_path: String,
+    /// An existing item with the same basename and type
+    pub suggested_path: String,
+}
+
+/// Strength assessment of a webhook token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenStrength {
+    /// Score from 0 (trivially guessable) to 100
+    pub score: u8,
+    /// Whether the token is strong enough to use without a warning
+    pub strong: bool,
+    /// Human-readable weaknesses (empty for a strong token)
+    pub reasons: Vec<String>,
+}
+
+/// Whether the webhook server is listening, and why not if it failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookStatus {
+    pub running: bool,
+    /// Address the server is listening on
+    pub bound_addr: Option<String>,
+    /// Why the server failed to bind or stopped serving
+    pub error: Option<String>,
+}
+
+/// A configured destination as reported by `GET /status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DestinationStatus {
+    /// Full destination path
+    pub path: String,
+    /// When a sync to it last succeeded, since the app started
+    pub last_synced: Option<DateTime<Utc>>,
+    /// Exit code of the last sync to it; -1 if that sync failed before rsync ran
+    pub last_exit_code: Option<i32>,
+    /// Its `gdrive_path` is present and a sync could write the destination
+    pub reachable: bool,
+}
+
+/// Whether an optional rsync feature works with the detected binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSupport {
+    /// Feature key (e.g. "xattrs", "progress2")
+    pub feature: String,
+    pub supported: bool,
+    /// Why the feature is or isn't available
+    pub reason: String,
+}
+
+/// Shrike, rsync, and OS versions for bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// Shrike version (`CARGO_PKG_VERSION`)
+    pub shrike_version: String,
+    /// Detected rsync version, or "not found"
+    pub rsync_version: String,
+    /// Resolved path of the rsync binary, if found
+    pub rsync_path: Option<String>,
+    /// Whether rsync is the OS-bundled `/usr/bin/rsync` rather than a custom install
+    pub rsync_is_system: bool,
+    pub os_version: String,
+    /// One-line summary suitable for pasting into a bug report
+    pub summary: String,
+}
+
+/// Entries sharing a top-level directory, for grouped display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryGroup {
+    /// Display label, with the home directory shown as `~` (e.g. "~/.config")
+    pub label: String,
+    /// Absolute path of the shared directory
+    pub prefix: String,
+    /// Entries in this group, sorted by path
+    pub entries: Vec<BackupEntry>,
+    /// Combined on-disk size of the group's entries in bytes
+    pub total_bytes: u64,
+}
+
+/// Compute the group prefix for an entry path.
+///
+/// Paths under `home_dir` are grouped by their first component below home,
+/// other paths by their first component below `/`. A file directly inside
+/// home (or `/`) has no such directory and is grouped under its parent.
+fn group_prefix(entry: &BackupEntry, home_dir: Option<&Path>) -> PathBuf {
+    let path = Path::new(&entry.path);
+    let base = home_dir
+        .filter(|home| path.starts_with(home) && path != *home)
+        .unwrap_or(Path::new("/"));
+    let relative = path.strip_prefix(base).unwrap_or(path);
+
+    let mut components = relative.components();
+    match components.next() {
+        Some(first) if components.next().is_some() || entry.item_type == ItemType::Directory => {
+            base.join(first)
+        }
+        _ => base.to_path_buf(),
+    }
+}
+
+/// Group entries by their first meaningful path prefix.
+///
+/// Groups are sorted by prefix and entries within a group by path, so the
+/// result is deterministic regardless of the input order.
+pub fn group_entries(entries: &[BackupEntry], home_dir: Option<&Path>) -> Vec<EntryGroup> {
+    let mut buckets: std::collections::BTreeMap<PathBuf, Vec<BackupEntry>> =
+        std::collections::BTreeMap::new();
+    for entry in entries {
+        buckets
+            .entry(group_prefix(entry, home_dir))
+            .or_default()
+            .push(entry.clone());
+    }
+
+    buckets
+        .into_iter()
+        .map(|(prefix, mut entries)| {
+            entries.sort_by(|a, b| a.path.cmp(&b.path));
+            let paths: Vec<String> = entries.iter().map(|e| e.path.clone()).collect();
+            let label = match home_dir.and_then(|home| prefix.strip_prefix(home).ok()) {
+                Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+                Some(rest) => format!("~/{}", rest.display()),
+                None => prefix.to_string_lossy().to_string(),
+            };
+            EntryGroup {
+                label,
+                prefix: prefix.to_string_lossy().to_string(),
+                entries,
+                total_bytes: crate::sync::validation::estimate_size(&paths),
+            }
+        })
+        .collect()
+}
+
+/// Largest page `PagedEntries::page` will return.
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// One page of the entry list plus the size of the whole list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PagedEntries {
+    /// Entries in this page, in stored order
+    pub entries: Vec<BackupEntry>,
+    /// Offset of the first entry in this page, after clamping
+    pub offset: usize,
+    /// Total number of entries across all pages
+    pub total: usize,
+}
+
+impl PagedEntries {
+    /// Slice `limit` entries starting at `offset`.
+    ///
+    /// `limit` is clamped to `1..=MAX_PAGE_SIZE`; an offset past the end
+    /// yields an empty page that still reports the correct total.
+    pub fn page(entries: &[BackupEntry], offset: usize, limit: usize) -> Self {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let offset = offset.min(entries.len());
+        let end = offset.saturating_add(limit).min(entries.len());
+        Self {
+            entries: entries[offset..end].to_vec(),
+            offset,
+            total: entries.len(),
+        }
+    }
+}
+
+/// Outcome of adding several paths at once.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddedEntries {
+    /// Entries that were added, in request order
+    pub added: Vec<BackupEntry>,
+    /// Requested paths that were missing, unreadable or already tracked
+    pub skipped: Vec<String>,
+}
+
+/// A detected coding agent configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectedConfig {
+    /// Display name of the coding agent (e.g. "Claude Code")
+    pub agent: String,
+    /// Absolute path to the config directory or file
+    pub path: String,
+    /// Whether this is a directory or single file
+    pub item_type: ItemType,
+}
+
+/// A child entry inside an agent's config directory (first level only).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeChild {
+    /// File or directory name (not full path)
+    pub name: String,
+    /// Absolute path
+    pub path: String,
+    /// Whether this is a file or directory
+    pub item_type: ItemType,
+}
+
+/// A tree-structured view of a coding agent's configuration.
+///
+/// Groups the agent's main config directory, its first-level children,
+/// and any sibling files (e.g. `.claude.json` next to `.claude/`).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AgentTree {
     /// Display name of the coding agent
@@ -295,76 +1387,238 @@ pub struct AgentTree {
     pub siblings: Vec<TreeChild>,
 }
 
-/// Known coding agent configuration locations (macOS).
+/// Where a coding agent keeps its configuration.
+struct AgentConfig {
+    agent: &'static str,
+    /// Path relative to home on macOS, and on any platform without its own.
+    /// Components may use `*` and `?` wildcards (see `expand_agent_path`).
+    path: &'static str,
+    /// Path relative to home on Linux, where it differs from macOS
+    linux_path: Option<&'static str>,
+    is_dir: bool,
+}
+
+impl AgentConfig {
+    const fn new(agent: &'static str, path: &'static str, is_dir: bool) -> Self {
+        Self {
+            agent,
+            path,
+            linux_path: None,
+            is_dir,
+        }
+    }
+
+    const fn linux(self, linux_path: &'static str) -> Self {
+        Self {
+            linux_path: Some(linux_path),
+            ..self
+        }
+    }
+
+    /// The relative path for `os`, as named by `std::env::consts::OS`.
+    fn path_for(&self, os: &str) -> &'static str {
+        match (os, self.linux_path) {
+            ("linux", Some(linux_path)) => linux_path,
+            _ => self.path,
+        }
+    }
+
+    /// The relative path for the platform the app is running on.
+    fn rel_path(&self) -> &'static str {
+        self.path_for(std::env::consts::OS)
+    }
+
+    fn item_type(&self) -> ItemType {
+        if self.is_dir {
+            ItemType::Directory
+        } else {
+            ItemType::File
+        }
+    }
+
+    /// Every existing config this entry matches, sorted. Paths under
+    /// `.config` resolve against `config_dir`, the rest against `home_dir`.
+    /// Wildcard matches must also be of the expected kind (file or dir).
+    fn matches(&self, home_dir: &Path, config_dir: &Path) -> Vec<PathBuf> {
+        let rel_path = self.rel_path();
+        let mut paths = match rel_path.strip_prefix(".config/") {
+            Some(rest) => expand_agent_path(config_dir, rest),
+            None => expand_agent_path(home_dir, rel_path),
+        };
+        if rel_path.contains(['*', '?']) {
+            paths.retain(|path| path.is_dir() == self.is_dir);
+        }
+        paths
+    }
+}
+
+/// The user's config directory: `$XDG_CONFIG_HOME` when set to an absolute
+/// path (as the XDG spec requires), else `~/.config`.
+pub fn config_dir_for(home_dir: &Path, xdg_config_home: Option<std::ffi::OsString>) -> PathBuf {
+    xdg_config_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home_dir.join(".config"))
+}
+
+/// Expand a home-relative path whose components may contain `*` / `?`
+/// wildcards into the existing paths it matches, sorted.
 ///
-/// Each tuple: (agent_name, relative_path_from_home, is_directory).
-const KNOWN_AGENT_CONFIGS: &[(&str, &str, bool)] = &[
+/// Only matches within a single component (no `**`), one `read_dir` per
+/// wildcard component, so it stays out of the sync path. Wildcards don't
+/// match hidden names unless the pattern itself starts with `.`.
+fn expand_agent_path(home_dir: &Path, rel_path: &str) -> Vec<PathBuf> {
+    let mut paths = vec![home_dir.to_path_buf()];
+    for part in rel_path.split('/').filter(|p| !p.is_empty()) {
+        if !part.contains(['*', '?']) {
+            paths.iter_mut().for_each(|p| p.push(part));
+            continue;
+        }
+        let mut expanded: Vec<PathBuf> = paths
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.filter_map(|e| e.ok()))
+            .filter(|e| {
+                let name = e.file_name();
+                let name = name.to_string_lossy();
+                (!name.starts_with('.') || part.starts_with('.')) && glob_match(part, &name)
+            })
+            .map(|e| e.path())
+            .collect();
+        expanded.sort();
+        paths = expanded;
+    }
+    paths.retain(|p| p.exists());
+    paths
+}
+
+/// Match `name` against `pattern`, where `*` matches any run of characters
+/// and `?` exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    // Position after the last `*`, and the name position it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry
+                Some((after, matched)) => {
+                    star = Some((after, matched + 1));
+                    p = after;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Known coding agent configuration locations.
+const KNOWN_AGENT_CONFIGS: &[AgentConfig] = &[
     // Claude Code
-    ("Claude Code", ".claude", true),
+    AgentConfig::new("Claude Code", ".claude", true),
     // Cursor
-    ("Cursor", ".cursor", true),
+    AgentConfig::new("Cursor", ".cursor", true),
     // OpenCode
-    ("OpenCode", ".config/opencode", true),
+    AgentConfig::new("OpenCode", ".config/opencode", true),
     // Windsurf
-    ("Windsurf", ".windsurf", true),
+    AgentConfig::new("Windsurf", ".windsurf", true),
     // GitHub Copilot
-    ("GitHub Copilot", ".config/github-copilot", true),
+    AgentConfig::new("GitHub Copilot", ".config/github-copilot", true),
     // Aider
-    ("Aider", ".aider.conf.yml", false),
+    AgentConfig::new("Aider", ".aider.conf.yml", false),
     // VS Code
-    ("VS Code", "Library/Application Support/Code/User", true),
+    AgentConfig::new("VS Code", "Library/Application Support/Code/User", true)
+        .linux(".config/Code/User"),
+    // Zed
+    AgentConfig::new("Zed", ".config/zed", true),
+    // Continue
+    AgentConfig::new("Continue", ".continue", true),
+    // Cline: the CLI's config, then the VS Code extension's storage
+    AgentConfig::new("Cline", ".config/cline", true),
+    AgentConfig::new(
+        "Cline",
+        "Library/Application Support/Code/User/globalStorage/saoudrizwan.claude-dev",
+        true,
+    )
+    .linux(".config/Code/User/globalStorage/saoudrizwan.claude-dev"),
+    // Roo Code (Cline fork)
+    AgentConfig::new(
+        "Roo Code",
+        "Library/Application Support/Code/User/globalStorage/rooveterinaryinc.roo-cline",
+        true,
+    )
+    .linux(".config/Code/User/globalStorage/rooveterinaryinc.roo-cline"),
+    // Gemini CLI
+    AgentConfig::new("Gemini CLI", ".gemini", true),
+    // Codeium
+    AgentConfig::new("Codeium", ".codeium", true),
+    // JetBrains IDEs, one versioned directory per IDE release
+    AgentConfig::new(
+        "JetBrains",
+        "Library/Application Support/JetBrains/*20??.*",
+        true,
+    )
+    .linux(".config/JetBrains/*20??.*"),
 ];
 
 /// Known sibling file patterns for each agent.
 ///
 /// Each tuple: (agent_name, relative_sibling_path_from_home).
 /// These are files that sit alongside the main config directory.
-const KNOWN_AGENT_SIBLINGS: &[(&str, &str)] = &[("Claude Code", ".claude.json")];
+const KNOWN_AGENT_SIBLINGS: &[(&str, &str)] = &[
+    ("Claude Code", ".claude.json"),
+    ("Continue", ".continuerc.json"),
+];
 
 /// Scan the user's home directory for known coding agent configurations.
 ///
-/// Returns a list of detected configs that actually exist on disk.
-pub fn scan_coding_configs(home_dir: &Path) -> Vec<DetectedConfig> {
+/// Returns a list of detected configs that actually exist on disk; an
+/// agent path with wildcards yields one config per match. `.config` paths
+/// are looked up in `config_dir` (see `config_dir_for`).
+pub fn scan_coding_configs(home_dir: &Path, config_dir: &Path) -> Vec<DetectedConfig> {
     KNOWN_AGENT_CONFIGS
         .iter()
-        .filter_map(|(agent, rel_path, is_dir)| {
-            let full_path = home_dir.join(rel_path);
-            if full_path.exists() {
-                Some(DetectedConfig {
-                    agent: (*agent).to_string(),
+        .flat_map(|config| {
+            config
+                .matches(home_dir, config_dir)
+                .into_iter()
+                .map(|full_path| DetectedConfig {
+                    agent: config.agent.to_string(),
                     path: full_path.to_string_lossy().to_string(),
-                    item_type: if *is_dir {
-                        ItemType::Directory
-                    } else {
-                        ItemType::File
-                    },
+                    item_type: config.item_type(),
                 })
-            } else {
-                None
-            }
         })
         .collect()
 }
 
 /// Scan the user's home directory for known coding agent configurations,
 /// returning a tree structure with first-level children and sibling files.
-pub fn scan_coding_configs_tree(home_dir: &Path) -> Vec<AgentTree> {
+pub fn scan_coding_configs_tree(home_dir: &Path, config_dir: &Path) -> Vec<AgentTree> {
     KNOWN_AGENT_CONFIGS
         .iter()
-        .filter_map(|(agent, rel_path, is_dir)| {
-            let full_path = home_dir.join(rel_path);
-            if !full_path.exists() {
-                return None;
-            }
-
-            let item_type = if *is_dir {
-                ItemType::Directory
-            } else {
-                ItemType::File
-            };
+        .flat_map(|config| {
+            config
+                .matches(home_dir, config_dir)
+                .into_iter()
+                .map(move |p| (config, p))
+        })
+        .map(|(config, full_path)| {
+            let item_type = config.item_type();
 
             // Collect first-level children for directories
-            let children = if *is_dir {
+            let children = if config.is_dir {
                 list_first_level_children(&full_path)
             } else {
                 Vec::new()
@@ -373,7 +1627,7 @@ pub fn scan_coding_configs_tree(home_dir: &Path) -> Vec<AgentTree> {
             // Collect sibling files
             let siblings = KNOWN_AGENT_SIBLINGS
                 .iter()
-                .filter(|(a, _)| *a == *agent)
+                .filter(|(a, _)| *a == config.agent)
                 .filter_map(|(_, sibling_rel)| {
                     let sibling_path = home_dir.join(sibling_rel);
                     if sibling_path.exists() {
@@ -393,32 +1647,125 @@ pub fn scan_coding_configs_tree(home_dir: &Path) -> Vec<AgentTree> {
                 })
                 .collect();
 
-            Some(AgentTree {
-                agent: (*agent).to_string(),
+            AgentTree {
+                agent: config.agent.to_string(),
                 path: full_path.to_string_lossy().to_string(),
                 item_type,
                 children,
                 siblings,
-            })
+            }
         })
         .collect()
 }
 
+/// Flatten scanned agent trees into the paths that back them up: each
+/// agent's main config followed by its sibling files.
+pub fn coding_config_paths(trees: &[AgentTree]) -> Vec<String> {
+    trees
+        .iter()
+        .flat_map(|tree| {
+            std::iter::once(tree.path.clone()).chain(tree.siblings.iter().map(|s| s.path.clone()))
+        })
+        .collect()
+}
+
+/// Coding agent config paths under `home` that have a copy in `backup_home`,
+/// the backup's mirror of the home directory.
+///
+/// The backup is scanned rather than `home`, so configs missing locally
+/// (e.g. on a freshly set up machine) are still restored.
+pub fn restorable_coding_config_paths(home: &Path, backup_home: &Path) -> Vec<String> {
+    // The backup mirrors the home directory, so its config dir is `.config`
+    let backup_config = backup_home.join(".config");
+    coding_config_paths(&scan_coding_configs_tree(backup_home, &backup_config))
+        .iter()
+        .filter_map(|path| Path::new(path).strip_prefix(backup_home).ok())
+        .map(|rel| home.join(rel).to_string_lossy().to_string())
+        .collect()
+}
+
+/// Returns true if `path` is tracked by an entry for itself or an ancestor.
+///
+/// Paths are compared in canonical form, component by component, so
+/// `/a/b` covers `/a/b/c` but not `/a/bc`.
+pub fn is_covered_by(path: &str, entries: &[BackupEntry]) -> bool {
+    let path = crate::config::canonical_path(path);
+    entries
+        .iter()
+        .any(|e| Path::new(&path).starts_with(crate::config::canonical_path(&e.path)))
+}
+
+/// Pairs of entries where the first entry's path contains the second's.
+///
+/// The nested entry is redundant, since syncing the outer one already
+/// copies it. Paths are compared like in [`is_covered_by`], so `/a/b`
+/// contains `/a/b/c` but not `/a/bc`. Entries with identical paths are
+/// reported once, in stored order.
+pub fn find_overlapping_entries(entries: &[BackupEntry]) -> Vec<(Uuid, Uuid)> {
+    let paths: Vec<PathBuf> = entries
+        .iter()
+        .map(|e| PathBuf::from(crate::config::canonical_path(&e.path)))
+        .collect();
+    let mut overlaps = Vec::new();
+    for (i, outer) in paths.iter().enumerate() {
+        for (j, inner) in paths.iter().enumerate() {
+            let identical = outer == inner;
+            if i != j && inner.starts_with(outer) && (!identical || i < j) {
+                overlaps.push((entries[i].id, entries[j].id));
+            }
+        }
+    }
+    overlaps
+}
+
+/// Map each first-level child of an agent's config tree to whether an
+/// existing entry already backs it up.
+pub fn child_coverage(tree: &AgentTree, entries: &[BackupEntry]) -> Vec<(String, bool)> {
+    tree.children
+        .iter()
+        .map(|child| (child.path.clone(), is_covered_by(&child.path, entries)))
+        .collect()
+}
+
+/// List one level of this machine's backup: the children of `sub_path`
+/// inside `destination`, or of the destination itself when omitted.
+///
+/// Sorted like `list_first_level_children`, but keeps dotfiles, which are
+/// much of what gets backed up. A destination (or sub-path) that doesn't
+/// exist yet lists as empty. `sub_path` must stay inside the destination.
+pub fn list_backup_tree(
+    destination: &Path,
+    sub_path: Option<&str>,
+) -> Result<Vec<TreeChild>, ShrikeError> {
+    let mut dir = destination.to_path_buf();
+    if let Some(sub) = sub_path {
+        for component in Path::new(sub).components() {
+            match component {
+                Component::Normal(part) => dir.push(part),
+                _ => return Err(ShrikeError::PathNotFound(sub.to_string())),
+            }
+        }
+    }
+    Ok(list_children(&dir, |name| name != ".DS_Store"))
+}
+
 /// List first-level children of a directory, sorted alphabetically.
 /// Skips hidden files/directories (starting with '.') and .DS_Store.
 fn list_first_level_children(dir: &Path) -> Vec<TreeChild> {
+    // Skip hidden files and .DS_Store
+    list_children(dir, |name| !name.starts_with('.'))
+}
+
+/// List the children of `dir` whose names pass `keep`, directories first,
+/// then files, alphabetically within each group.
+fn list_children(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<TreeChild> {
     let Ok(entries) = std::fs::read_dir(dir) else {
         return Vec::new();
     };
 
     let mut children: Vec<TreeChild> = entries
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            let name = e.file_name();
-            let name_str = name.to_string_lossy();
-            // Skip hidden files and .DS_Store
-            !name_str.starts_with('.')
-        })
+        .filter(|e| keep(&e.file_name().to_string_lossy()))
         .map(|e| {
             let name = e.file_name().to_string_lossy().to_string();
             let path = e.path().to_string_lossy().to_string();
@@ -468,6 +1815,20 @@ mod tests {
         assert!(json["last_synced"].is_null());
     }
 
+    #[test]
+    fn backup_entry_missing_scheduled_defaults_true() {
+        let json = r#"{"id":"550e8400-e29b-41d4-a716-446655440000","path":"/etc/hosts","item_type":"file","added_at":"2024-01-01T00:00:00Z","last_synced":null}"#;
+        let entry: BackupEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.scheduled);
+    }
+
+    #[test]
+    fn backup_entry_missing_enabled_defaults_true() {
+        let json = r#"{"id":"550e8400-e29b-41d4-a716-446655440000","path":"/etc/hosts","item_type":"file","added_at":"2024-01-01T00:00:00Z","last_synced":null,"scheduled":false}"#;
+        let entry: BackupEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.enabled);
+    }
+
     #[test]
     fn backup_entry_roundtrips_json() {
         let entry = BackupEntry::new("/foo/bar".into(), ItemType::File);
@@ -485,6 +1846,7 @@ mod tests {
         assert_eq!(settings.webhook_port, 7023); // dev default (debug_assertions)
         assert!(!settings.webhook_token.is_empty());
         assert!(settings.show_tray_icon);
+        assert!(settings.notify_on_sync);
         assert!(settings.show_dock_icon);
         assert!(!settings.autostart);
         assert_eq!(settings.theme, "auto");
@@ -502,113 +1864,838 @@ mod tests {
     }
 
     #[test]
-    fn app_settings_destination_path() {
-        let settings = AppSettings {
-            gdrive_path: "/mnt/gdrive".into(),
-            backup_dir_name: "Backup".into(),
-            machine_name: "TestMac".into(),
-            webhook_port: 8080,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        assert_eq!(
-            settings.destination_path().unwrap(),
-            "/mnt/gdrive/Backup/TestMac"
-        );
+    fn for_test_settings_have_a_valid_destination() {
+        let settings = AppSettings::for_test("/tmp/test_gdrive");
+        assert_eq!(
+            settings.destination_path().unwrap(),
+            "/tmp/test_gdrive/Backup/TestMac"
+        );
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn app_settings_destination_path() {
+        let settings = AppSettings::for_test("/mnt/gdrive");
+        assert_eq!(
+            settings.destination_path().unwrap(),
+            "/mnt/gdrive/Backup/TestMac"
+        );
+    }
+
+    #[test]
+    fn destination_path_trims_trailing_slashes_from_gdrive() {
+        let with_gdrive = |gdrive_path: &str| AppSettings {
+            gdrive_path: gdrive_path.into(),
+            backup_dir_name: "Backup".into(),
+            machine_name: "TestMac".into(),
+            ..AppSettings::default()
+        };
+        for gdrive in ["/mnt/gdrive", "/mnt/gdrive/", "/mnt/gdrive//"] {
+            assert_eq!(
+                with_gdrive(gdrive).destination_path().unwrap(),
+                "/mnt/gdrive/Backup/TestMac",
+                "{gdrive}"
+            );
+        }
+        assert_eq!(
+            with_gdrive("/").destination_path().unwrap(),
+            "/Backup/TestMac"
+        );
+    }
+
+    #[test]
+    fn portable_names_reject_names_other_filesystems_cannot_store() {
+        let with_machine = |machine_name: &str, portable_names| AppSettings {
+            gdrive_path: "/mnt/gdrive".into(),
+            machine_name: machine_name.into(),
+            portable_names,
+            ..AppSettings::default()
+        };
+        let cases = [
+            (" Mac", "whitespace"),
+            ("Mac ", "whitespace"),
+            ("Mac.", "ends with a dot"),
+            ("Con", "reserved device name"),
+            ("nul.txt", "reserved device name"),
+            ("LPT9", "reserved device name"),
+            ("My:Mac", "':'"),
+            ("What?", "'?'"),
+            ("a<b", "'<'"),
+            ("a\\b", "'\\\\'"),
+            ("tab\tname", "'\\t'"),
+        ];
+        for (name, reason) in cases {
+            let err = with_machine(name, true).destination_path().unwrap_err();
+            assert!(err.to_string().contains(reason), "{name:?}: {err}");
+            // Only rejected when portable names are required
+            let lenient = with_machine(name, false);
+            assert!(lenient.destination_path().is_ok(), "{name:?}");
+        }
+        // Merely containing a reserved name is fine
+        assert!(with_machine("Console", true).destination_path().is_ok());
+        assert!(with_machine("Work Mac 2", true).destination_path().is_ok());
+    }
+
+    #[test]
+    fn portable_names_default_off_only_on_macos() {
+        let settings = AppSettings::default();
+        assert_eq!(settings.portable_names, !cfg!(target_os = "macos"));
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let parsed: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.portable_names, settings.portable_names);
+    }
+
+    #[test]
+    fn destination_path_rejects_empty_gdrive() {
+        let settings = AppSettings::for_test("");
+        let err = settings.destination_path().unwrap_err();
+        assert!(err.to_string().contains("Google Drive path"));
+    }
+
+    #[test]
+    fn destination_path_rejects_traversal_in_backup_dir() {
+        let settings = AppSettings {
+            backup_dir_name: "../etc".into(),
+            ..AppSettings::for_test("/mnt/gdrive")
+        };
+        let err = settings.destination_path().unwrap_err();
+        assert!(err.to_string().contains("path separators"));
+    }
+
+    #[test]
+    fn destination_path_rejects_traversal_in_machine_name() {
+        let settings = AppSettings {
+            machine_name: "../../root".into(),
+            ..AppSettings::for_test("/mnt/gdrive")
+        };
+        let err = settings.destination_path().unwrap_err();
+        assert!(err.to_string().contains("path separators"));
+    }
+
+    #[test]
+    fn destination_path_rejects_slash_in_backup_dir() {
+        let settings = AppSettings {
+            backup_dir_name: "back/up".into(),
+            ..AppSettings::for_test("/mnt/gdrive")
+        };
+        let err = settings.destination_path().unwrap_err();
+        assert!(err.to_string().contains("path separators"));
+    }
+
+    #[test]
+    fn destination_path_rejects_dotdot_machine_name() {
+        let settings = AppSettings {
+            machine_name: "..".into(),
+            ..AppSettings::for_test("/mnt/gdrive")
+        };
+        let err = settings.destination_path().unwrap_err();
+        assert!(err.to_string().contains("invalid path component"));
+    }
+
+    #[test]
+    fn parse_umask_accepts_octal() {
+        assert_eq!(parse_umask("077").unwrap(), 0o077);
+        assert_eq!(parse_umask("0022").unwrap(), 0o022);
+        assert_eq!(parse_umask("0").unwrap(), 0);
+        assert_eq!(parse_umask("777").unwrap(), 0o777);
+    }
+
+    #[test]
+    fn parse_umask_rejects_invalid() {
+        for value in ["", "8", "078", "abc", "-22", "1000", "07777", " 77"] {
+            let err = parse_umask(value).unwrap_err();
+            assert!(
+                matches!(err, ShrikeError::InvalidSettings(_)),
+                "expected {value:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn settings_validate_checks_umask() {
+        let mut settings = AppSettings {
+            file_umask: Some("077".into()),
+            ..AppSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.umask().unwrap(), Some(0o077));
+
+        settings.file_umask = Some("999".into());
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn settings_webhook_addr_honors_bind_addr() {
+        let mut settings = AppSettings {
+            webhook_port: 7015,
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.webhook_bind_addr, DEFAULT_WEBHOOK_BIND_ADDR);
+        assert_eq!(
+            settings.webhook_addr().unwrap(),
+            "127.0.0.1:7015".parse().unwrap()
+        );
+
+        settings.webhook_bind_addr = "0.0.0.0".into();
+        let addr = settings.webhook_addr().unwrap();
+        assert!(addr.ip().is_unspecified());
+        assert_eq!(addr.port(), 7015);
+
+        settings.webhook_bind_addr = "192.168.1.20".into();
+        assert_eq!(
+            settings.webhook_addr().unwrap(),
+            "192.168.1.20:7015".parse().unwrap()
+        );
+
+        settings.webhook_bind_addr = "::1".into();
+        assert_eq!(
+            settings.webhook_addr().unwrap(),
+            "[::1]:7015".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn settings_webhook_addrs_follow_port_strategy() {
+        let mut settings = AppSettings {
+            webhook_port: 7015,
+            ..AppSettings::default()
+        };
+        let ports = |s: &AppSettings| -> Vec<u16> {
+            let addrs = s.webhook_addrs().unwrap();
+            addrs.iter().map(|a| a.port()).collect()
+        };
+        assert_eq!(ports(&settings), vec![7015]);
+
+        settings.webhook_port_strategy = WebhookPortStrategy::NextFree;
+        assert_eq!(ports(&settings), (7015..=7025).collect::<Vec<_>>());
+
+        settings.webhook_port_strategy = WebhookPortStrategy::Ephemeral;
+        assert_eq!(ports(&settings), vec![7015, 0]);
+
+        // Never wraps past the last port
+        settings.webhook_port = u16::MAX - 1;
+        settings.webhook_port_strategy = WebhookPortStrategy::NextFree;
+        assert_eq!(ports(&settings), vec![u16::MAX - 1, u16::MAX]);
+    }
+
+    #[test]
+    fn settings_validate_rejects_invalid_bind_addr() {
+        for addr in ["localhost", "192.168.1", "0.0.0.0:7015", ""] {
+            let settings = AppSettings {
+                webhook_bind_addr: addr.into(),
+                ..AppSettings::default()
+            };
+            let err = settings.validate().unwrap_err();
+            assert!(
+                err.to_string().contains("webhook_bind_addr"),
+                "addr {addr:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn settings_without_bind_addr_default_to_loopback() {
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert!(settings.webhook_addr().unwrap().ip().is_loopback());
+    }
+
+    #[test]
+    fn rsync_program_falls_back_to_path_rsync() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.rsync_program(), "rsync");
+        settings.rsync_binary = Some(String::new());
+        assert_eq!(settings.rsync_program(), "rsync");
+        settings.rsync_binary = Some("/opt/homebrew/bin/rsync".into());
+        assert_eq!(settings.rsync_program(), "/opt/homebrew/bin/rsync");
+    }
+
+    #[test]
+    fn sync_timeout_zero_disables_it() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.sync_timeout(), None);
+        settings.sync_timeout_secs = Some(0);
+        assert_eq!(settings.sync_timeout(), None);
+        settings.sync_timeout_secs = Some(90);
+        assert_eq!(
+            settings.sync_timeout(),
+            Some(std::time::Duration::from_secs(90))
+        );
+    }
+
+    #[test]
+    fn output_line_cap_zero_keeps_everything() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.output_line_cap(), Some(500));
+        settings.max_output_lines = 0;
+        assert_eq!(settings.output_line_cap(), None);
+    }
+
+    #[test]
+    fn entry_cap_checks_and_warns_near_the_limit() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.max_entries, 10_000);
+        settings.max_entries = 100;
+        assert!(settings.check_entry_count(100).is_ok());
+        assert!(matches!(
+            settings.check_entry_count(101),
+            Err(ShrikeError::TooManyEntries(100))
+        ));
+        assert!(!settings.entries_near_limit(89));
+        assert!(settings.entries_near_limit(90));
+
+        settings.max_entries = 0;
+        assert!(settings.check_entry_count(1_000_000).is_ok());
+        assert!(!settings.entries_near_limit(1_000_000));
+    }
+
+    #[test]
+    fn global_excludes_default_only_for_new_configs() {
+        assert!(
+            AppSettings::default()
+                .global_excludes
+                .contains(&".DS_Store".to_string())
+        );
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert!(settings.global_excludes.is_empty());
+    }
+
+    #[test]
+    fn settings_validate_checks_compress_level_range() {
+        let mut settings = AppSettings {
+            compress_level: Some(9),
+            ..AppSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.compress_level = Some(10);
+        let err = settings.validate().unwrap_err();
+        assert!(err.to_string().contains("compress_level"));
+    }
+
+    #[test]
+    fn settings_debug_redacts_secrets() {
+        let settings = AppSettings {
+            webhook_token: "tok1-secret-bearer-token".into(),
+            webhook_hmac_secret: Some("hmac-shared-secret".into()),
+            ..AppSettings::default()
+        };
+        let debug = format!("{settings:?}");
+        assert!(!debug.contains("tok1-secret-bearer-token"), "{debug}");
+        assert!(!debug.contains("hmac-shared-secret"), "{debug}");
+        assert!(debug.contains("\"tok1…\""), "{debug}");
+        assert!(debug.contains("Some(\"hmac…\")"), "{debug}");
+        assert!(debug.contains("webhook_port"), "{debug}");
+    }
+
+    #[test]
+    fn redact_secret_keeps_four_chars() {
+        assert_eq!(redact_secret("abcdefgh"), "abcd…");
+        // Too short to show any of it
+        assert_eq!(redact_secret("abcdefg"), "…");
+        assert_eq!(redact_secret("ab"), "…");
+        assert_eq!(redact_secret(""), "…");
+    }
+
+    #[test]
+    fn settings_validate_checks_log_level() {
+        let mut settings = AppSettings {
+            log_level: "debug".into(),
+            ..AppSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.log_level = "loud".into();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn settings_without_umask_deserializes() {
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.file_umask, None);
+        assert_eq!(settings.log_level, "info");
+        assert_eq!(settings.umask().unwrap(), None);
+    }
+
+    #[test]
+    fn single_destination_settings_load_as_one_destination() {
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            settings.destinations(),
+            vec![Destination {
+                gdrive_path: "/g".into(),
+                backup_dir_name: "B".into(),
+            }]
+        );
+        assert_eq!(settings.destination_paths().unwrap(), vec!["/g/B/M"]);
+    }
+
+    #[test]
+    fn extra_destinations_follow_primary() {
+        let settings = AppSettings {
+            gdrive_path: "/g".into(),
+            backup_dir_name: "B".into(),
+            machine_name: "M".into(),
+            extra_destinations: vec![Destination {
+                gdrive_path: "/Volumes/SSD".into(),
+                backup_dir_name: "Shrike".into(),
+            }],
+            ..AppSettings::default()
+        };
+        assert_eq!(
+            settings.destination_paths().unwrap(),
+            vec!["/g/B/M", "/Volumes/SSD/Shrike/M"]
+        );
+
+        let ssd = settings.for_destination(&settings.destinations()[1]);
+        assert_eq!(ssd.destination_path().unwrap(), "/Volumes/SSD/Shrike/M");
+        assert!(ssd.extra_destinations.is_empty());
+        assert_eq!(ssd.machine_name, "M");
+    }
+
+    #[test]
+    fn settings_validate_checks_extra_destinations() {
+        let destination = |gdrive_path: &str| Destination {
+            gdrive_path: gdrive_path.into(),
+            backup_dir_name: "B".into(),
+        };
+        let mut settings = AppSettings {
+            gdrive_path: "/g".into(),
+            backup_dir_name: "B".into(),
+            machine_name: "M".into(),
+            extra_destinations: vec![destination("/Volumes/SSD")],
+            ..AppSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.extra_destinations = vec![destination("")];
+        assert!(matches!(
+            settings.validate(),
+            Err(ShrikeError::InvalidSettings(msg)) if msg.contains("destination 2")
+        ));
+
+        settings.extra_destinations = vec![destination("/g")];
+        assert!(matches!(
+            settings.validate(),
+            Err(ShrikeError::InvalidSettings(msg)) if msg.contains("listed twice")
+        ));
+    }
+
+    #[test]
+    fn group_entries_groups_shared_parent() {
+        let home = Path::new("/Users/me");
+        let entries = vec![
+            BackupEntry::new("/Users/me/.config/nvim".into(), ItemType::Directory),
+            BackupEntry::new("/Users/me/.config/fish/config.fish".into(), ItemType::File),
+            BackupEntry::new("/Users/me/.config".into(), ItemType::Directory),
+        ];
+        let groups = group_entries(&entries, Some(home));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].label, "~/.config");
+        assert_eq!(groups[0].prefix, "/Users/me/.config");
+        let paths: Vec<&str> = groups[0].entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/Users/me/.config",
+                "/Users/me/.config/fish/config.fish",
+                "/Users/me/.config/nvim"
+            ]
+        );
+    }
+
+    #[test]
+    fn group_entries_separates_unrelated() {
+        let home = Path::new("/Users/me");
+        let entries = vec![
+            BackupEntry::new("/etc/hosts".into(), ItemType::File),
+            BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+            BackupEntry::new("/Users/me/.claude".into(), ItemType::Directory),
+            BackupEntry::new("/Users/me/.gitconfig".into(), ItemType::File),
+        ];
+        let groups = group_entries(&entries, Some(home));
+        let labels: Vec<&str> = groups.iter().map(|g| g.label.as_str()).collect();
+        assert_eq!(labels, vec!["~", "~/.claude", "/etc"]);
+        assert_eq!(groups[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn group_entries_is_order_independent() {
+        let mut entries = vec![
+            BackupEntry::new("/opt/a".into(), ItemType::Directory),
+            BackupEntry::new("/etc/b".into(), ItemType::File),
+            BackupEntry::new("/etc/a".into(), ItemType::File),
+        ];
+        let forward = group_entries(&entries, None);
+        entries.reverse();
+        assert_eq!(group_entries(&entries, None), forward);
+    }
+
+    #[test]
+    fn group_entries_sums_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir(root.join("conf")).unwrap();
+        std::fs::write(root.join("conf/a"), "1234").unwrap();
+        std::fs::write(root.join("conf/b"), "56").unwrap();
+        let entries = vec![
+            BackupEntry::new(root.join("conf/a").to_string_lossy().into(), ItemType::File),
+            BackupEntry::new(root.join("conf/b").to_string_lossy().into(), ItemType::File),
+        ];
+        let groups = group_entries(&entries, Some(&root));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].label, "~/conf");
+        assert_eq!(groups[0].total_bytes, 6);
+    }
+
+    fn numbered_entries(n: usize) -> Vec<BackupEntry> {
+        (0..n)
+            .map(|i| BackupEntry::new(format!("/Users/me/file{i}"), ItemType::File))
+            .collect()
+    }
+
+    #[test]
+    fn paged_entries_first_page() {
+        let entries = numbered_entries(5);
+        let page = PagedEntries::page(&entries, 0, 2);
+        assert_eq!(page.entries, entries[0..2]);
+        assert_eq!(page.offset, 0);
+        assert_eq!(page.total, 5);
+    }
+
+    #[test]
+    fn paged_entries_middle_page() {
+        let entries = numbered_entries(5);
+        let page = PagedEntries::page(&entries, 2, 2);
+        assert_eq!(page.entries, entries[2..4]);
+
+        // The last page may be short
+        let last = PagedEntries::page(&entries, 4, 2);
+        assert_eq!(last.entries, entries[4..]);
+    }
+
+    #[test]
+    fn paged_entries_out_of_range_offset_is_empty() {
+        let entries = numbered_entries(3);
+        let page = PagedEntries::page(&entries, 10, 2);
+        assert!(page.entries.is_empty());
+        assert_eq!(page.offset, 3);
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn paged_entries_clamps_limit() {
+        let entries = numbered_entries(MAX_PAGE_SIZE + 10);
+        assert_eq!(PagedEntries::page(&entries, 0, 0).entries.len(), 1);
+        assert_eq!(
+            PagedEntries::page(&entries, 0, usize::MAX).entries.len(),
+            MAX_PAGE_SIZE
+        );
+    }
+
+    fn synced_at(hours_ago: i64, now: DateTime<Utc>) -> BackupEntry {
+        let mut entry = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        entry.last_synced = Some(now - chrono::Duration::hours(hours_ago));
+        entry
+    }
+
+    #[test]
+    fn freshness_recent_sync_is_fresh() {
+        let now = Utc::now();
+        let items = vec![synced_at(30, now), synced_at(2, now)];
+        let freshness = Freshness::evaluate(&items, 24, now);
+        assert!(!freshness.stale);
+        assert_eq!(freshness.age_secs, Some(2 * 3600));
+        assert_eq!(freshness.last_synced, items[1].last_synced);
+    }
+
+    #[test]
+    fn freshness_old_sync_is_stale() {
+        let now = Utc::now();
+        let freshness = Freshness::evaluate(&[synced_at(25, now)], 24, now);
+        assert!(freshness.stale);
+        assert!(!freshness.is_never_synced());
+        assert!(!Freshness::evaluate(&[synced_at(25, now)], 48, now).stale);
+    }
+
+    #[test]
+    fn freshness_never_synced() {
+        let unsynced = BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File);
+        for items in [vec![], vec![unsynced]] {
+            let freshness = Freshness::evaluate(&items, 24, Utc::now());
+            assert!(freshness.is_never_synced());
+            assert!(freshness.stale);
+            assert_eq!(freshness.age_secs, None);
+        }
+    }
+
+    #[test]
+    fn stamp_synced_only_touches_synced_entries() {
+        let synced_at = Utc::now();
+        let mut items = vec![
+            BackupEntry::new("/Users/me/.zshrc".into(), ItemType::File),
+            BackupEntry::new("/Users/me/Movies".into(), ItemType::Directory),
+        ];
+        let ids = vec![items[0].id, Uuid::new_v4()];
+
+        assert_eq!(stamp_synced(&mut items, &ids, synced_at), 1);
+        assert_eq!(items[0].last_synced, Some(synced_at));
+        assert_eq!(items[1].last_synced, None);
+    }
+
+    fn history_entry(files: u64) -> SyncHistoryEntry {
+        let now = Utc::now();
+        SyncHistoryEntry {
+            id: Uuid::new_v4(),
+            started_at: now,
+            finished_at: now,
+            files_transferred: files,
+            dirs_transferred: 0,
+            bytes_transferred: 0,
+            exit_code: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn history_to_csv_parses_back_into_rows() {
+        let started_at = Utc::now();
+        let mut ok = history_entry(3);
+        ok.started_at = started_at;
+        ok.finished_at = started_at + chrono::Duration::milliseconds(1500);
+        ok.dirs_transferred = 2;
+        ok.bytes_transferred = 4096;
+        let mut failed = history_entry(0);
+        failed.exit_code = 23;
+
+        let csv = history_to_csv(&[ok, failed]);
+        let rows: Vec<Vec<&str>> = csv.lines().map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].join(","), HISTORY_CSV_HEADER);
+        assert_eq!(
+            rows[1],
+            [
+                started_at.to_rfc3339().as_str(),
+                "3",
+                "2",
+                "4096",
+                "0",
+                "1500"
+            ]
+        );
+        assert_eq!(rows[2][4], "23");
+        assert_eq!(
+            DateTime::parse_from_rfc3339(rows[1][0]).unwrap(),
+            started_at
+        );
+    }
+
+    #[test]
+    fn history_to_csv_empty_history_is_just_the_header() {
+        assert_eq!(history_to_csv(&[]), format!("{HISTORY_CSV_HEADER}\n"));
+    }
+
+    #[test]
+    fn csv_field_quotes_separators_and_quotes() {
+        assert_eq!(csv_field("42"), "42");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn push_history_keeps_newest_first() {
+        let mut history = Vec::new();
+        push_history(&mut history, history_entry(1));
+        push_history(&mut history, history_entry(2));
+        let files: Vec<u64> = history.iter().map(|h| h.files_transferred).collect();
+        assert_eq!(files, vec![2, 1]);
+    }
+
+    #[test]
+    fn push_history_truncates_at_cap() {
+        let mut history = Vec::new();
+        for files in 0..(SYNC_HISTORY_CAP as u64 + 5) {
+            push_history(&mut history, history_entry(files));
+        }
+        assert_eq!(history.len(), SYNC_HISTORY_CAP);
+        assert_eq!(history[0].files_transferred, SYNC_HISTORY_CAP as u64 + 4);
+        // The five oldest runs (0..5) were dropped
+        assert_eq!(history[SYNC_HISTORY_CAP - 1].files_transferred, 5);
+    }
+
+    #[test]
+    fn history_entry_from_failed_outcome() {
+        let now = Utc::now();
+        let rsync_failed: crate::error::Result<SyncResult> = Err(ShrikeError::RsyncError {
+            code: 23,
+            message: "partial transfer".into(),
+        });
+        let entry = SyncHistoryEntry::from_outcome(now, now, &rsync_failed);
+        assert_eq!(entry.exit_code, 23);
+        assert!(entry.error.unwrap().contains("partial transfer"));
+
+        let locked: crate::error::Result<SyncResult> =
+            Err(ShrikeError::SyncFailed("already in progress".into()));
+        let entry = SyncHistoryEntry::from_outcome(now, now, &locked);
+        assert_eq!(entry.exit_code, -1);
+        assert_eq!(entry.files_transferred, 0);
+    }
+
+    #[test]
+    fn settings_validate_rejects_zero_stale_threshold() {
+        let settings = AppSettings {
+            stale_after_hours: 0,
+            ..AppSettings::default()
+        };
+        assert!(matches!(
+            settings.validate().unwrap_err(),
+            ShrikeError::InvalidSettings(_)
+        ));
+    }
+
+    #[test]
+    fn settings_validate_requires_recipient_when_encrypting() {
+        let mut settings = AppSettings {
+            encrypt: true,
+            ..AppSettings::default()
+        };
+        assert!(settings.validate().is_err());
+
+        settings.encryption_recipient = Some("age1notakey".into());
+        assert!(settings.validate().is_err());
+
+        settings.encryption_recipient =
+            Some(age::x25519::Identity::generate().to_public().to_string());
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn onboarding_fresh_store_is_all_false() {
+        let status = Onboarding::evaluate(None, &[], None);
+        assert_eq!(
+            status,
+            Onboarding {
+                has_settings: false,
+                drive_detected: false,
+                has_entries: false,
+                has_synced: false,
+            }
+        );
+        assert!(!status.is_complete());
+    }
+
+    #[test]
+    fn onboarding_configured_store_is_complete() {
+        let settings = AppSettings {
+            gdrive_path: "/mnt/gdrive".into(),
+            ..AppSettings::default()
+        };
+        let mut entry = BackupEntry::new("/etc/hosts".into(), ItemType::File);
+        entry.last_synced = Some(Utc::now());
+
+        let status = Onboarding::evaluate(Some(&settings), &[entry], None);
+        assert!(status.is_complete());
+    }
+
+    #[test]
+    fn onboarding_uses_detection_only_without_settings() {
+        let detected = Path::new("/mnt/gdrive");
+        assert!(Onboarding::evaluate(None, &[], Some(detected)).drive_detected);
+
+        let unconfigured = AppSettings {
+            gdrive_path: String::new(),
+            ..AppSettings::default()
+        };
+        let status = Onboarding::evaluate(Some(&unconfigured), &[], Some(detected));
+        assert!(status.has_settings);
+        assert!(!status.drive_detected);
+    }
+
+    fn claude_tree() -> AgentTree {
+        let child = |name: &str| TreeChild {
+            name: name.to_string(),
+            path: format!("/Users/me/.claude/{name}"),
+            item_type: ItemType::Directory,
+        };
+        AgentTree {
+            agent: "Claude Code".into(),
+            path: "/Users/me/.claude".into(),
+            item_type: ItemType::Directory,
+            children: vec![child("agents"), child("commands"), child("projects")],
+            siblings: vec![],
+        }
+    }
+
+    #[test]
+    fn child_coverage_parent_tracked_covers_all() {
+        let entries = vec![BackupEntry::new(
+            "/Users/me/.claude".into(),
+            ItemType::Directory,
+        )];
+        let coverage = child_coverage(&claude_tree(), &entries);
+        assert_eq!(coverage.len(), 3);
+        assert!(coverage.iter().all(|(_, covered)| *covered));
     }
 
     #[test]
-    fn destination_path_rejects_empty_gdrive() {
-        let settings = AppSettings {
-            gdrive_path: String::new(),
-            backup_dir_name: "Backup".into(),
-            machine_name: "TestMac".into(),
-            webhook_port: 7015,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        let err = settings.destination_path().unwrap_err();
-        assert!(err.to_string().contains("Google Drive path"));
+    fn child_coverage_single_child_tracked() {
+        let entries = vec![
+            BackupEntry::new("/Users/me/.claude/commands".into(), ItemType::Directory),
+            BackupEntry::new("/Users/me/.claude-other".into(), ItemType::Directory),
+        ];
+        let coverage = child_coverage(&claude_tree(), &entries);
+        assert_eq!(
+            coverage,
+            vec![
+                ("/Users/me/.claude/agents".to_string(), false),
+                ("/Users/me/.claude/commands".to_string(), true),
+                ("/Users/me/.claude/projects".to_string(), false),
+            ]
+        );
     }
 
     #[test]
-    fn destination_path_rejects_traversal_in_backup_dir() {
-        let settings = AppSettings {
-            gdrive_path: "/mnt/gdrive".into(),
-            backup_dir_name: "../etc".into(),
-            machine_name: "TestMac".into(),
-            webhook_port: 7015,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        let err = settings.destination_path().unwrap_err();
-        assert!(err.to_string().contains("path separators"));
+    fn is_covered_by_respects_component_boundaries() {
+        let entries = vec![BackupEntry::new("/a/b/".into(), ItemType::Directory)];
+        assert!(is_covered_by("/a/b", &entries));
+        assert!(is_covered_by("/a/b/c", &entries));
+        assert!(!is_covered_by("/a/bc", &entries));
     }
 
     #[test]
-    fn destination_path_rejects_traversal_in_machine_name() {
-        let settings = AppSettings {
-            gdrive_path: "/mnt/gdrive".into(),
-            backup_dir_name: "Backup".into(),
-            machine_name: "../../root".into(),
-            webhook_port: 7015,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        let err = settings.destination_path().unwrap_err();
-        assert!(err.to_string().contains("path separators"));
+    fn find_overlapping_entries_reports_nested_entry() {
+        let outer = BackupEntry::new("/Users/me/project/".into(), ItemType::Directory);
+        let inner = BackupEntry::new("/Users/me/project/src".into(), ItemType::Directory);
+        let entries = vec![inner.clone(), outer.clone()];
+        assert_eq!(
+            find_overlapping_entries(&entries),
+            vec![(outer.id, inner.id)]
+        );
     }
 
     #[test]
-    fn destination_path_rejects_slash_in_backup_dir() {
-        let settings = AppSettings {
-            gdrive_path: "/mnt/gdrive".into(),
-            backup_dir_name: "back/up".into(),
-            machine_name: "TestMac".into(),
-            webhook_port: 7015,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        let err = settings.destination_path().unwrap_err();
-        assert!(err.to_string().contains("path separators"));
+    fn find_overlapping_entries_ignores_siblings_and_shared_prefixes() {
+        let entries = vec![
+            BackupEntry::new("/a/b".into(), ItemType::Directory),
+            BackupEntry::new("/a/bc".into(), ItemType::Directory),
+            BackupEntry::new("/a/d".into(), ItemType::Directory),
+        ];
+        assert!(find_overlapping_entries(&entries).is_empty());
     }
 
     #[test]
-    fn destination_path_rejects_dotdot_machine_name() {
-        let settings = AppSettings {
-            gdrive_path: "/mnt/gdrive".into(),
-            backup_dir_name: "Backup".into(),
-            machine_name: "..".into(),
-            webhook_port: 7015,
-            webhook_token: "token".into(),
-            show_tray_icon: true,
-            show_dock_icon: true,
-            autostart: false,
-            theme: "auto".into(),
-            language: "auto".into(),
-        };
-        let err = settings.destination_path().unwrap_err();
-        assert!(err.to_string().contains("invalid path component"));
+    fn find_overlapping_entries_reports_identical_paths_once() {
+        let first = BackupEntry::new("/a/b".into(), ItemType::Directory);
+        let second = BackupEntry::new("/a/b/".into(), ItemType::Directory);
+        let entries = vec![first.clone(), second.clone()];
+        assert_eq!(
+            find_overlapping_entries(&entries),
+            vec![(first.id, second.id)]
+        );
     }
 
     #[test]
@@ -618,6 +2705,35 @@ mod tests {
         assert_eq!(store.settings.webhook_port, 7023); // dev default (debug_assertions)
     }
 
+    // --- adopt_detected_gdrive_path ---
+
+    #[test]
+    fn adopt_detected_gdrive_path_fills_empty_path() {
+        let mut settings = AppSettings {
+            gdrive_path: String::new(),
+            ..AppSettings::default()
+        };
+        let detected = Path::new("/Users/me/Library/CloudStorage/GoogleDrive-me/My Drive");
+        assert!(settings.adopt_detected_gdrive_path(detected, false));
+        assert_eq!(settings.gdrive_path, detected.to_str().unwrap());
+    }
+
+    #[test]
+    fn adopt_detected_gdrive_path_keeps_chosen_path_unless_forced() {
+        let mut settings = AppSettings {
+            gdrive_path: "/Volumes/SSD".to_string(),
+            ..AppSettings::default()
+        };
+        let detected = Path::new("/Users/me/Library/CloudStorage/GoogleDrive-me/My Drive");
+        assert!(!settings.adopt_detected_gdrive_path(detected, false));
+        assert_eq!(settings.gdrive_path, "/Volumes/SSD");
+
+        assert!(settings.adopt_detected_gdrive_path(detected, true));
+        assert_eq!(settings.gdrive_path, detected.to_str().unwrap());
+        // Already up to date: nothing changes, even when forced
+        assert!(!settings.adopt_detected_gdrive_path(detected, true));
+    }
+
     // --- detect_gdrive_path ---
 
     #[test]
@@ -685,6 +2801,62 @@ mod tests {
         assert!(result.unwrap().to_string_lossy().ends_with("My Drive"));
     }
 
+    #[test]
+    fn list_drive_roots_includes_my_drive_and_shared_drives() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = dir.path().join("GoogleDrive-user@example.com");
+        for sub in [
+            "Shared drives/Team",
+            "Shared drives/Design",
+            "Shared drives/.hidden",
+            "My Drive",
+            "Computers/Laptop",
+            ".Trash",
+        ] {
+            std::fs::create_dir_all(account.join(sub)).unwrap();
+        }
+
+        assert_eq!(
+            list_drive_roots(&account),
+            vec![
+                account.join("My Drive"),
+                account.join("Shared drives/Design"),
+                account.join("Shared drives/Team"),
+            ]
+        );
+        // Detection still picks My Drive
+        assert_eq!(
+            detect_gdrive_path(dir.path()),
+            Some(account.join("My Drive"))
+        );
+    }
+
+    #[test]
+    fn list_drive_roots_missing_account_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list_drive_roots(&dir.path().join("nonexistent")).is_empty());
+    }
+
+    #[test]
+    fn list_gdrive_roots_covers_every_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("GoogleDrive-work@example.com");
+        let home = dir.path().join("GoogleDrive-home@example.com");
+        std::fs::create_dir_all(work.join("My Drive")).unwrap();
+        std::fs::create_dir_all(work.join("Shared drives/Team")).unwrap();
+        std::fs::create_dir_all(home.join("我的云端硬盘")).unwrap();
+        std::fs::create_dir_all(dir.path().join("Dropbox/Files")).unwrap();
+
+        assert_eq!(
+            list_gdrive_roots(dir.path()),
+            vec![
+                home.join("我的云端硬盘"),
+                work.join("My Drive"),
+                work.join("Shared drives/Team"),
+            ]
+        );
+    }
+
     #[test]
     fn detect_gdrive_skips_non_gdrive_dirs() {
         let dir = tempfile::tempdir().unwrap();
@@ -695,6 +2867,68 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn detect_cloud_root_dropbox_uses_account_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        // Unlike Google Drive, subfolders are the user's own files
+        std::fs::create_dir_all(dir.path().join("Dropbox/My Drive")).unwrap();
+
+        let result = detect_cloud_root(CloudProvider::Dropbox, dir.path()).unwrap();
+        assert_eq!(result, dir.path().join("Dropbox"));
+    }
+
+    #[test]
+    fn detect_cloud_root_dropbox_multiple_accounts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Dropbox-Personal")).unwrap();
+
+        let result = detect_cloud_root(CloudProvider::Dropbox, dir.path()).unwrap();
+        assert_eq!(result, dir.path().join("Dropbox-Personal"));
+    }
+
+    #[test]
+    fn detect_cloud_root_dropbox_ignores_lookalikes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("DropboxBackup")).unwrap();
+        std::fs::write(dir.path().join("Dropbox"), "").unwrap();
+
+        assert!(detect_cloud_root(CloudProvider::Dropbox, dir.path()).is_none());
+    }
+
+    #[test]
+    fn detect_cloud_root_onedrive_uses_account_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("OneDrive-Contoso/Documents")).unwrap();
+        std::fs::create_dir_all(dir.path().join("OneDrive")).unwrap();
+
+        let result = detect_cloud_root(CloudProvider::OneDrive, dir.path()).unwrap();
+        assert_eq!(result, dir.path().join("OneDrive-Contoso"));
+    }
+
+    #[test]
+    fn detect_cloud_root_google_drive_uses_localized_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = dir.path().join("GoogleDrive-user@example.com");
+        std::fs::create_dir_all(account.join("マイドライブ")).unwrap();
+        std::fs::create_dir_all(account.join("他のパソコン")).unwrap();
+        std::fs::create_dir_all(dir.path().join("Dropbox")).unwrap();
+
+        let result = detect_cloud_root(CloudProvider::GoogleDrive, dir.path()).unwrap();
+        assert_eq!(result, account.join("マイドライブ"));
+        assert_eq!(detect_gdrive_path(dir.path()), Some(result));
+    }
+
+    #[test]
+    fn cloud_provider_defaults_to_google_drive() {
+        let json = r#"{"gdrive_path":"/g","backup_dir_name":"B","machine_name":"M","webhook_port":1,"webhook_token":"t"}"#;
+        let settings: AppSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.cloud_provider, CloudProvider::GoogleDrive);
+        assert_eq!(
+            serde_json::to_value(CloudProvider::OneDrive).unwrap(),
+            serde_json::json!("one_drive")
+        );
+    }
+
     #[test]
     fn detect_gdrive_real_system() {
         // On this machine, Google Drive should be detectable
@@ -716,10 +2950,17 @@ mod tests {
             files_transferred: 5,
             dirs_transferred: 2,
             bytes_transferred: 1024,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
             stdout: "sent 1024 bytes".into(),
             stderr: String::new(),
             exit_code: 0,
             synced_at: Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
         };
         assert!(result.is_success());
     }
@@ -730,10 +2971,17 @@ mod tests {
             files_transferred: 0,
             dirs_transferred: 0,
             bytes_transferred: 0,
+            created: 0,
+            updated: 0,
+            deleted: 0,
+            unchanged: 0,
             stdout: String::new(),
             stderr: "rsync error".into(),
             exit_code: 23,
             synced_at: Utc::now(),
+            warnings: vec![],
+            skipped_paths: vec![],
+            truncated: false,
         };
         assert!(!result.is_success());
     }
@@ -769,6 +3017,7 @@ mod tests {
         assert!(!settings.autostart); // default false
         assert_eq!(settings.theme, "auto"); // default_auto
         assert_eq!(settings.language, "auto"); // default_auto
+        assert_eq!(settings.transient_exit_codes, DEFAULT_TRANSIENT_EXIT_CODES);
     }
 
     #[test]
@@ -784,6 +3033,35 @@ mod tests {
             autostart: true,
             theme: "dark".into(),
             language: "zh".into(),
+            file_umask: None,
+            log_level: "info".into(),
+            compress: false,
+            stale_after_hours: 24,
+            encrypt: false,
+            encryption_recipient: None,
+            extra_destinations: vec![],
+            verify_checksums: false,
+            auto_sync_interval_minutes: None,
+            max_sync_retries: 0,
+            transient_exit_codes: vec![23],
+            skip_space_check: false,
+            follow_symlinks: false,
+            cloud_provider: CloudProvider::GoogleDrive,
+            webhook_bind_addr: "127.0.0.1".to_string(),
+            webhook_hmac_secret: None,
+            webhook_token_fallback: false,
+            webhook_rate_limit_per_min: 0,
+            global_excludes: vec![],
+            mirror_deletes: false,
+            rsync_binary: None,
+            sync_timeout_secs: None,
+            compress_level: None,
+            webhook_port_strategy: WebhookPortStrategy::Fixed,
+            notify_on_sync: true,
+            max_entries: 0,
+            portable_names: false,
+            path_layout: PathLayout::Full,
+            max_output_lines: 500,
         };
         let json = serde_json::to_string(&settings).unwrap();
         let deserialized: AppSettings = serde_json::from_str(&json).unwrap();
@@ -800,7 +3078,7 @@ mod tests {
     #[test]
     fn scan_coding_configs_empty_home() {
         let dir = tempfile::tempdir().unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert!(results.is_empty());
     }
 
@@ -808,7 +3086,7 @@ mod tests {
     fn scan_coding_configs_finds_claude() {
         let dir = tempfile::tempdir().unwrap();
         std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].agent, "Claude Code");
         assert_eq!(results[0].item_type, ItemType::Directory);
@@ -819,16 +3097,232 @@ mod tests {
     fn scan_coding_configs_finds_cursor() {
         let dir = tempfile::tempdir().unwrap();
         std::fs::create_dir_all(dir.path().join(".cursor")).unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].agent, "Cursor");
     }
 
+    /// VS Code's user directory in a fake home on this platform.
+    fn vscode_user_dir() -> &'static str {
+        if cfg!(target_os = "linux") {
+            ".config/Code/User"
+        } else {
+            "Library/Application Support/Code/User"
+        }
+    }
+
+    #[test]
+    fn agent_config_path_for_resolves_per_platform() {
+        let vscode = KNOWN_AGENT_CONFIGS
+            .iter()
+            .find(|c| c.agent == "VS Code")
+            .unwrap();
+        assert_eq!(
+            vscode.path_for("macos"),
+            "Library/Application Support/Code/User"
+        );
+        assert_eq!(vscode.path_for("linux"), ".config/Code/User");
+        // Platforms without an override fall back to the macOS layout
+        assert_eq!(vscode.path_for("windows"), vscode.path_for("macos"));
+
+        let claude = &KNOWN_AGENT_CONFIGS[0];
+        assert_eq!(claude.path_for("linux"), ".claude");
+        assert_eq!(claude.path_for("macos"), ".claude");
+    }
+
+    #[test]
+    fn scan_coding_configs_uses_linux_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".config/Code/User")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        if cfg!(target_os = "linux") {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].agent, "VS Code");
+            assert!(results[0].path.ends_with(".config/Code/User"));
+        } else {
+            assert!(results.is_empty());
+        }
+    }
+
+    #[test]
+    fn scan_coding_configs_uses_macos_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Library/Application Support/Code/User")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        if cfg!(target_os = "macos") {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].agent, "VS Code");
+        } else {
+            assert!(results.is_empty());
+        }
+    }
+
+    #[test]
+    fn scan_coding_configs_expands_versioned_jetbrains_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = if cfg!(target_os = "linux") {
+            dir.path().join(".config/JetBrains")
+        } else {
+            dir.path().join("Library/Application Support/JetBrains")
+        };
+        for ide in [
+            "PyCharm2024.1",
+            "IntelliJIdea2024.2",
+            "consentOptions",
+            ".IdeaIC2024.1",
+        ] {
+            std::fs::create_dir_all(root.join(ide)).unwrap();
+        }
+        // Matches the pattern but isn't a directory
+        std::fs::write(root.join("GoLand2023.3"), "").unwrap();
+
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        let paths: Vec<&str> = results.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                root.join("IntelliJIdea2024.2").to_str().unwrap(),
+                root.join("PyCharm2024.1").to_str().unwrap(),
+            ]
+        );
+        assert!(results.iter().all(|c| c.agent == "JetBrains"));
+
+        let trees = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
+        assert_eq!(trees.len(), 2);
+        assert_eq!(trees[1].path, paths[1]);
+    }
+
+    #[test]
+    fn scan_coding_configs_reads_config_agents_from_config_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let xdg = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(xdg.path().join("opencode")).unwrap();
+        std::fs::create_dir_all(home.path().join(".claude")).unwrap();
+        // Not where the config dir points, so not scanned
+        std::fs::create_dir_all(home.path().join(".config/zed")).unwrap();
+
+        let results = scan_coding_configs(home.path(), xdg.path());
+        let agents: Vec<&str> = results.iter().map(|c| c.agent.as_str()).collect();
+        assert_eq!(agents, vec!["Claude Code", "OpenCode"]);
+        assert_eq!(
+            results[1].path,
+            xdg.path().join("opencode").to_string_lossy()
+        );
+
+        let trees = scan_coding_configs_tree(home.path(), xdg.path());
+        assert_eq!(trees.len(), 2);
+        assert_eq!(trees[1].agent, "OpenCode");
+    }
+
+    #[test]
+    fn config_dir_for_prefers_absolute_xdg_config_home() {
+        let home = Path::new("/Users/me");
+        assert_eq!(
+            config_dir_for(home, Some("/custom/config".into())),
+            PathBuf::from("/custom/config")
+        );
+        assert_eq!(config_dir_for(home, None), home.join(".config"));
+        // Empty or relative values are ignored, per the XDG spec
+        assert_eq!(config_dir_for(home, Some("".into())), home.join(".config"));
+        assert_eq!(
+            config_dir_for(home, Some("rel/config".into())),
+            home.join(".config")
+        );
+    }
+
+    #[test]
+    fn glob_match_handles_stars_and_question_marks() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("*20??.*", "IntelliJIdea2024.2"));
+        assert!(glob_match("Py*20??.?", "PyCharm2024.1"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("*20??.*", "consentOptions"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("a*c", "abcd"));
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_zed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".config/zed")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].agent, "Zed");
+        assert_eq!(results[0].item_type, ItemType::Directory);
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_continue() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".continue")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].agent, "Continue");
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_cline_cli_and_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".config/cline")).unwrap();
+        let extension = format!("{}/globalStorage/saoudrizwan.claude-dev", vscode_user_dir());
+        std::fs::create_dir_all(dir.path().join(extension)).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        let cline: Vec<_> = results.iter().filter(|c| c.agent == "Cline").collect();
+        assert_eq!(cline.len(), 2);
+        assert!(cline[0].path.ends_with(".config/cline"));
+        assert!(cline[1].path.ends_with("saoudrizwan.claude-dev"));
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_roo_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let extension = format!(
+            "{}/globalStorage/rooveterinaryinc.roo-cline",
+            vscode_user_dir()
+        );
+        std::fs::create_dir_all(dir.path().join(extension)).unwrap();
+        let agents: Vec<String> = scan_coding_configs(dir.path(), &dir.path().join(".config"))
+            .into_iter()
+            .map(|c| c.agent)
+            .collect();
+        // The extension lives inside the VS Code user directory
+        assert_eq!(agents, vec!["VS Code", "Roo Code"]);
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_gemini_cli() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".gemini")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].agent, "Gemini CLI");
+    }
+
+    #[test]
+    fn scan_coding_configs_finds_codeium() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".codeium")).unwrap();
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].agent, "Codeium");
+    }
+
+    #[test]
+    fn scan_coding_configs_tree_includes_continue_sibling() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".continue")).unwrap();
+        std::fs::write(dir.path().join(".continuerc.json"), "{}").unwrap();
+        let trees = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
+        assert_eq!(trees.len(), 1);
+        assert_eq!(trees[0].siblings.len(), 1);
+        assert_eq!(trees[0].siblings[0].name, ".continuerc.json");
+    }
+
     #[test]
     fn scan_coding_configs_finds_aider_file() {
         let dir = tempfile::tempdir().unwrap();
         std::fs::write(dir.path().join(".aider.conf.yml"), "model: gpt-4").unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].agent, "Aider");
         assert_eq!(results[0].item_type, ItemType::File);
@@ -840,7 +3334,7 @@ mod tests {
         std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
         std::fs::create_dir_all(dir.path().join(".cursor")).unwrap();
         std::fs::create_dir_all(dir.path().join(".config/opencode")).unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 3);
         let agents: Vec<&str> = results.iter().map(|c| c.agent.as_str()).collect();
         assert!(agents.contains(&"Claude Code"));
@@ -853,7 +3347,7 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         // Only create one, others should be skipped
         std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
-        let results = scan_coding_configs(dir.path());
+        let results = scan_coding_configs(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
     }
 
@@ -875,7 +3369,7 @@ mod tests {
     #[test]
     fn scan_tree_empty_home() {
         let dir = tempfile::tempdir().unwrap();
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert!(results.is_empty());
     }
 
@@ -887,7 +3381,7 @@ mod tests {
         std::fs::write(claude_dir.join("settings.json"), "{}").unwrap();
         std::fs::create_dir_all(claude_dir.join("projects")).unwrap();
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].agent, "Claude Code");
         assert_eq!(results[0].children.len(), 2);
@@ -908,7 +3402,7 @@ mod tests {
         std::fs::write(claude_dir.join(".hidden"), "").unwrap();
         std::fs::write(claude_dir.join("visible.json"), "{}").unwrap();
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert_eq!(results[0].children.len(), 1);
         assert_eq!(results[0].children[0].name, "visible.json");
     }
@@ -919,7 +3413,7 @@ mod tests {
         std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
         std::fs::write(dir.path().join(".claude.json"), "{}").unwrap();
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].siblings.len(), 1);
         assert_eq!(results[0].siblings[0].name, ".claude.json");
@@ -932,16 +3426,42 @@ mod tests {
         std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
         // No .claude.json file
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert_eq!(results[0].siblings.len(), 0);
     }
 
+    #[test]
+    fn coding_config_paths_include_siblings() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
+        std::fs::write(dir.path().join(".claude.json"), "{}").unwrap();
+        std::fs::create_dir_all(dir.path().join(".cursor")).unwrap();
+
+        let trees = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
+        let paths = coding_config_paths(&trees);
+        let expected: Vec<String> = [".claude", ".claude.json", ".cursor"]
+            .iter()
+            .map(|rel| dir.path().join(rel).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn restorable_coding_config_paths_maps_backup_to_home() {
+        let backup = tempfile::tempdir().unwrap();
+        let backup_home = backup.path().join("Users/me");
+        std::fs::create_dir_all(backup_home.join(".cursor")).unwrap();
+
+        let paths = restorable_coding_config_paths(Path::new("/Users/me"), &backup_home);
+        assert_eq!(paths, vec!["/Users/me/.cursor"]);
+    }
+
     #[test]
     fn scan_tree_file_agent_has_no_children() {
         let dir = tempfile::tempdir().unwrap();
         std::fs::write(dir.path().join(".aider.conf.yml"), "model: gpt-4").unwrap();
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].agent, "Aider");
         assert_eq!(results[0].item_type, ItemType::File);
@@ -958,7 +3478,7 @@ mod tests {
         std::fs::write(claude_dir.join("gamma.txt"), "").unwrap();
         std::fs::create_dir_all(claude_dir.join("delta")).unwrap();
 
-        let results = scan_coding_configs_tree(dir.path());
+        let results = scan_coding_configs_tree(dir.path(), &dir.path().join(".config"));
         let names: Vec<&str> = results[0]
             .children
             .iter()
@@ -968,6 +3488,53 @@ mod tests {
         assert_eq!(names, vec!["beta", "delta", "alpha.txt", "gamma.txt"]);
     }
 
+    #[test]
+    fn list_backup_tree_lists_one_level_dirs_first() {
+        let dest = tempfile::tempdir().unwrap();
+        let home = dest.path().join("Users/me");
+        std::fs::create_dir_all(home.join(".config/nvim")).unwrap();
+        std::fs::write(home.join(".zshrc"), "").unwrap();
+        std::fs::write(home.join("notes.txt"), "").unwrap();
+        std::fs::create_dir_all(home.join("Documents")).unwrap();
+        std::fs::write(home.join(".DS_Store"), "").unwrap();
+
+        let root = list_backup_tree(dest.path(), None).unwrap();
+        assert_eq!(root.len(), 1);
+        assert_eq!(root[0].name, "Users");
+        assert_eq!(root[0].item_type, ItemType::Directory);
+
+        let children = list_backup_tree(dest.path(), Some("Users/me")).unwrap();
+        let names: Vec<&str> = children.iter().map(|c| c.name.as_str()).collect();
+        // Dotfiles are kept, .DS_Store is not
+        assert_eq!(names, vec![".config", "Documents", ".zshrc", "notes.txt"]);
+        assert_eq!(
+            children[0].path,
+            home.join(".config").to_string_lossy().to_string()
+        );
+
+        let nested = list_backup_tree(dest.path(), Some("Users/me/.config")).unwrap();
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].name, "nvim");
+    }
+
+    #[test]
+    fn list_backup_tree_missing_destination_is_empty() {
+        let dest = tempfile::tempdir().unwrap();
+        let missing = dest.path().join("Backup/NewMac");
+        assert!(list_backup_tree(&missing, None).unwrap().is_empty());
+        let sub = list_backup_tree(&missing, Some("Users")).unwrap();
+        assert!(sub.is_empty());
+    }
+
+    #[test]
+    fn list_backup_tree_rejects_escaping_sub_path() {
+        let dest = tempfile::tempdir().unwrap();
+        for sub in ["../other", "/etc", "Users/../.."] {
+            let err = list_backup_tree(dest.path(), Some(sub)).unwrap_err();
+            assert!(matches!(err, ShrikeError::PathNotFound(_)), "{sub}");
+        }
+    }
+
     #[test]
     fn scan_tree_serializes() {
         let tree = AgentTree {
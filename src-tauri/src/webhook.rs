@@ -1,26 +1,75 @@
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use axum::extract::State;
-use axum::http::{HeaderMap, StatusCode};
-use axum::response::IntoResponse;
+use axum::body::Bytes;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
 use serde_json::json;
-use tauri::AppHandle;
+use sha2::Sha256;
+use tauri::{AppHandle, Emitter};
 use tauri_plugin_store::StoreExt;
 
-use crate::sync;
-use crate::types::{AppSettings, BackupEntry, SyncStatus};
+use crate::error::ShrikeError;
+use crate::logging::{self, LogLevel};
+use crate::manifest::from_hex;
+use crate::sync::{self, Coalesced};
+use crate::types::{
+    AppSettings, BackupEntry, SyncHistoryEntry, SyncResult, SyncStatus, TokenStrength,
+    WebhookStatus, push_history, stamp_synced,
+};
 
 const STORE_FILE: &str = "shrike_data.json";
 const ITEMS_KEY: &str = "items";
 const SETTINGS_KEY: &str = "settings";
+const HISTORY_KEY: &str = "history";
+
+/// Event emitted with the error message when the webhook server fails to
+/// bind or stops serving.
+pub const WEBHOOK_ERROR_EVENT: &str = "webhook://error";
+
+/// Event emitted with a `WebhookStatus` once the server is listening.
+pub const WEBHOOK_STATUS_EVENT: &str = "webhook://status";
+
+/// Status of the server started by `start_webhook_server`.
+static SERVER_STATUS: Mutex<WebhookStatus> = Mutex::new(WebhookStatus {
+    running: false,
+    bound_addr: None,
+    error: None,
+});
+
+/// Stops the server started last by `start_webhook_server`.
+static SHUTDOWN: Mutex<Option<tokio::sync::oneshot::Sender<()>>> = Mutex::new(None);
+
+/// Current status of the webhook server.
+pub fn server_status() -> WebhookStatus {
+    SERVER_STATUS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
 
 /// Abstraction over the data layer so handlers can be tested without
 /// a real Tauri runtime.
 pub trait DataStore: Clone + Send + Sync + 'static {
     fn load_settings(&self) -> Result<AppSettings, String>;
     fn load_items(&self) -> Result<Vec<BackupEntry>, String>;
+    fn save_items(&self, items: &[BackupEntry]) -> Result<(), String>;
+    /// Recorded sync runs, newest first.
+    fn load_history(&self) -> Result<Vec<SyncHistoryEntry>, String>;
+    fn append_history(&self, entry: SyncHistoryEntry) -> Result<(), String>;
+    /// Called before a sync runs; a no-op unless overridden.
+    fn sync_started(&self) {}
+    /// Called after a sync finishes, to tell the user; a no-op unless
+    /// overridden.
+    fn sync_finished(&self, _settings: &AppSettings, _outcome: &crate::error::Result<SyncResult>) {}
 }
 
 /// Production implementation backed by the Tauri plugin-store.
@@ -45,10 +94,47 @@ impl DataStore for TauriStore {
             None => Ok(Vec::new()),
         }
     }
+
+    fn save_items(&self, items: &[BackupEntry]) -> Result<(), String> {
+        let store = self.app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(ITEMS_KEY.to_string(), json!(items));
+        Ok(())
+    }
+
+    fn load_history(&self) -> Result<Vec<SyncHistoryEntry>, String> {
+        let store = self.app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        match store.get(HISTORY_KEY) {
+            Some(val) => serde_json::from_value(val).map_err(|e| e.to_string()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn append_history(&self, entry: SyncHistoryEntry) -> Result<(), String> {
+        let mut history = self.load_history()?;
+        push_history(&mut history, entry);
+        let store = self.app.store(STORE_FILE).map_err(|e| e.to_string())?;
+        store.set(HISTORY_KEY.to_string(), json!(history));
+        Ok(())
+    }
+
+    fn sync_started(&self) {
+        crate::commands::sync_started(&self.app);
+    }
+
+    fn sync_finished(&self, settings: &AppSettings, outcome: &crate::error::Result<SyncResult>) {
+        crate::commands::sync_finished(&self.app, settings, outcome);
+    }
 }
 
 /// Validate the bearer token from the Authorization header.
+///
+/// An empty configured token rejects every request rather than matching a
+/// bare `Bearer ` header, since the server may be reachable from the network.
 fn validate_token(headers: &HeaderMap, expected_token: &str) -> Result<(), StatusCode> {
+    if expected_token.is_empty() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     let auth_header = headers
         .get("authorization")
         .and_then(|v| v.to_str().ok())
@@ -65,104 +151,597 @@ fn validate_token(headers: &HeaderMap, expected_token: &str) -> Result<(), Statu
     Ok(())
 }
 
+/// Header carrying the request body's HMAC-SHA256 signature.
+pub const SIGNATURE_HEADER: &str = "x-shrike-signature";
+
+/// Validate the `X-Shrike-Signature: sha256=<hex>` header against an
+/// HMAC-SHA256 of the raw request body, compared in constant time.
+fn validate_signature(headers: &HeaderMap, body: &[u8], secret: &str) -> Result<(), StatusCode> {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("sha256="))
+        .and_then(from_hex)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+/// Authenticate a request under the configured scheme.
+///
+/// Without `webhook_hmac_secret`, the bearer token is required. With it, the
+/// signature is required, except that unsigned requests may fall back to
+/// the bearer token when `webhook_token_fallback` is set. A request that
+/// carries a bad signature is rejected even if its token is valid.
+fn authorize(headers: &HeaderMap, body: &[u8], settings: &AppSettings) -> Result<(), StatusCode> {
+    let secret = settings
+        .webhook_hmac_secret
+        .as_deref()
+        .filter(|s| !s.is_empty());
+    match secret {
+        None => validate_token(headers, &settings.webhook_token),
+        Some(_) if !headers.contains_key(SIGNATURE_HEADER) && settings.webhook_token_fallback => {
+            validate_token(headers, &settings.webhook_token)
+        }
+        Some(secret) => validate_signature(headers, body, secret),
+    }
+}
+
+/// Minimum score for a token to be considered strong.
+const STRONG_TOKEN_SCORE: u8 = 60;
+
+/// Passwords that are rejected outright regardless of length or entropy.
+const COMMON_TOKENS: &[&str] = &[
+    "password", "passw0rd", "secret", "token", "shrike", "admin", "letmein", "qwerty", "welcome",
+    "changeme", "default", "webhook", "123456", "12345678", "abc123",
+];
+
+/// Estimate the entropy of a token in bits.
+///
+/// Uses the Shannon entropy of the token's own character distribution
+/// multiplied by its length, so the result is deterministic and penalizes
+/// repeated characters (e.g. "aaaaaaaa" scores zero).
+fn token_entropy_bits(token: &str) -> f64 {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+    let mut counts = std::collections::HashMap::new();
+    for c in &chars {
+        *counts.entry(*c).or_insert(0usize) += 1;
+    }
+    let len = chars.len() as f64;
+    let per_char: f64 = counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    per_char * len
+}
+
+/// Score a webhook token by length, entropy, character variety, and
+/// whether it is a well-known password.
+pub fn token_strength(token: &str) -> TokenStrength {
+    let mut score: u32 = 0;
+    let mut reasons = Vec::new();
+
+    let len = token.chars().count();
+    score += match len {
+        32.. => 40,
+        16..=31 => 25,
+        12..=15 => 10,
+        _ => 0,
+    };
+    if len < 16 {
+        reasons.push(format!(
+            "too short ({len} characters, at least 16 recommended)"
+        ));
+    }
+
+    let bits = token_entropy_bits(token);
+    score += if bits >= 80.0 {
+        40
+    } else if bits >= 48.0 {
+        20
+    } else {
+        0
+    };
+    if bits < 48.0 {
+        reasons.push(format!("low entropy (~{bits:.0} bits)"));
+    }
+
+    let classes = [
+        token.chars().any(|c| c.is_ascii_lowercase()),
+        token.chars().any(|c| c.is_ascii_uppercase()),
+        token.chars().any(|c| c.is_ascii_digit()),
+        token.chars().any(|c| !c.is_ascii_alphanumeric()),
+    ]
+    .iter()
+    .filter(|&&present| present)
+    .count() as u32;
+    score += classes * 5;
+    if classes < 2 {
+        reasons.push("uses only one character class".to_string());
+    }
+
+    // Strip trailing digits so "password123" is still caught
+    let lowered = token.to_lowercase();
+    let stem = lowered.trim_end_matches(|c: char| c.is_ascii_digit());
+    let common = COMMON_TOKENS.contains(&lowered.as_str()) || COMMON_TOKENS.contains(&stem);
+    if common {
+        score = 0;
+        reasons.push("is a common password".to_string());
+    }
+
+    let score = score.min(100) as u8;
+    TokenStrength {
+        score,
+        strong: !common && score >= STRONG_TOKEN_SCORE,
+        reasons,
+    }
+}
+
+/// Error response shared by the webhook handlers.
+///
+/// Serialized as `{"code": ..., "message": ...}`; `code` is a stable
+/// machine-readable identifier, `message` is human-readable prose.
+#[derive(Debug)]
+pub struct WebhookError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    /// Seconds sent in a `Retry-After` header, for rate-limited requests
+    retry_after: Option<u64>,
+}
+
+impl WebhookError {
+    fn new(status: StatusCode, code: &'static str, message: impl ToString) -> Self {
+        Self {
+            status,
+            code,
+            message: message.to_string(),
+            retry_after: None,
+        }
+    }
+
+    fn unauthorized() -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", "unauthorized")
+    }
+
+    /// The data store could not be read or written.
+    fn store(message: String) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "store_error", message)
+    }
+
+    fn rate_limited(retry_after: Duration) -> Self {
+        // Round up, so a client that waits exactly this long gets through
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        Self {
+            retry_after: Some(secs),
+            ..Self::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                "rate limit exceeded",
+            )
+        }
+    }
+}
+
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> Response {
+        let body = Json(json!({"code": self.code, "message": self.message}));
+        match self.retry_after {
+            Some(secs) => {
+                (self.status, [(header::RETRY_AFTER, secs.to_string())], body).into_response()
+            }
+            None => (self.status, body).into_response(),
+        }
+    }
+}
+
+/// [`authorize`] for handlers, mapping a rejection to a [`WebhookError`].
+fn authorize_request(
+    headers: &HeaderMap,
+    body: &[u8],
+    settings: &AppSettings,
+) -> Result<(), WebhookError> {
+    authorize(headers, body, settings).map_err(|_| WebhookError::unauthorized())
+}
+
+/// GET /healthz — unauthenticated liveness check.
+///
+/// Never reads the store, so it reports the server as up even when the
+/// store is unreadable; `/status` reports the real state.
+async fn healthz_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(json!({"ok": true})))
+}
+
 /// GET /status — returns current sync status.
+///
+/// `last_sync` is the newest run in the sync history, or `null` before the
+/// first sync. `destinations` lists each destination's path, last sync and
+/// reachability (see `sync::destination_statuses`); checking never creates
+/// a destination.
 async fn status_handler<S: DataStore>(
     State(store): State<S>,
     headers: HeaderMap,
-) -> impl IntoResponse {
-    let settings = match store.load_settings() {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e}))),
-    };
-
-    if let Err(status) = validate_token(&headers, &settings.webhook_token) {
-        return (status, Json(json!({"error": "unauthorized"})));
-    }
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, WebhookError> {
+    let settings = store.load_settings().map_err(WebhookError::store)?;
+    authorize_request(&headers, &body, &settings)?;
 
     let items = store.load_items().unwrap_or_default();
-    let destination = match settings.destination_path() {
-        Ok(d) => d,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": e.to_string()})),
-            )
+    let destinations = sync::destination_statuses(&settings)
+        .map_err(|e| WebhookError::new(StatusCode::INTERNAL_SERVER_ERROR, "invalid_settings", e))?;
+    let history = store.load_history().unwrap_or_default();
+    let last_sync = history.first().map(|run| {
+        json!({
+            "finished_at": run.finished_at,
+            "exit_code": run.exit_code,
+            "files_transferred": run.files_transferred,
+            "bytes_transferred": run.bytes_transferred,
+            "error": run.error,
+        })
+    });
+    Ok(Json(json!({
+        "status": if sync::is_sync_running() { SyncStatus::Running } else { SyncStatus::Idle },
+        "entries_count": items.len(),
+        "destination": destinations[0].path,
+        "destinations": destinations,
+        "last_sync": last_sync,
+    })))
+}
+
+/// Length of the `POST /sync` rate-limit window.
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Fixed-window limiter for `POST /sync`.
+///
+/// Shrike is single-user, so one window is shared by every caller; the
+/// limit is read from settings on each request so changes apply at once.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    /// Start of the current window and requests counted in it
+    window: Mutex<Option<(Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// Count a request made at `now` against `limit` requests per window.
+    ///
+    /// A `limit` of 0 disables limiting. Over the limit, returns how long
+    /// until the window resets.
+    pub fn check(&self, limit: u32, now: Instant) -> Result<(), Duration> {
+        if limit == 0 {
+            return Ok(());
         }
-    };
-    (
-        StatusCode::OK,
-        Json(json!({
-            "status": if sync::is_sync_running() { SyncStatus::Running } else { SyncStatus::Idle },
-            "entries_count": items.len(),
-            "destination": destination,
-        })),
-    )
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        match window.as_mut() {
+            Some((start, count)) if now.duration_since(*start) < RATE_LIMIT_WINDOW => {
+                if *count >= limit {
+                    return Err(RATE_LIMIT_WINDOW - now.duration_since(*start));
+                }
+                *count += 1;
+            }
+            _ => *window = Some((now, 1)),
+        }
+        Ok(())
+    }
+}
+
+/// Query parameters accepted by `POST /sync`.
+#[derive(Debug, Default, Deserialize)]
+struct SyncQuery {
+    /// Only sync entries included in scheduled syncs
+    #[serde(default)]
+    scheduled_only: bool,
+    /// Report what would be transferred without writing anything
+    #[serde(default)]
+    dry_run: bool,
 }
 
 /// POST /sync — triggers a sync operation.
+///
+/// Syncs every enabled entry by default; `?scheduled_only=true` also skips
+/// entries that are excluded from scheduled syncs, and `?dry_run=true`
+/// previews the sync with `rsync --dry-run`. A real sync is recorded in the history and, on
+/// success, stamps `last_synced` on the synced entries.
+///
+/// Authorized requests beyond `webhook_rate_limit_per_min` get
+/// `429 Too Many Requests` with a `Retry-After` header, dry runs included.
+/// A real sync requested while one is running is queued to run after it,
+/// answering `202 Accepted` with `{"status": "queued"}`.
+///
+/// The query is only checked once the request is authorized, so an
+/// unauthenticated caller gets `401` whatever it sends.
 async fn sync_handler<S: DataStore>(
     State(store): State<S>,
+    Extension(limiter): Extension<Arc<RateLimiter>>,
+    query: Result<Query<SyncQuery>, QueryRejection>,
     headers: HeaderMap,
-) -> impl IntoResponse {
-    let settings = match store.load_settings() {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e}))),
-    };
+    body: Bytes,
+) -> Result<Response, WebhookError> {
+    let settings = store.load_settings().map_err(WebhookError::store)?;
+    authorize_request(&headers, &body, &settings)?;
+    limiter
+        .check(settings.webhook_rate_limit_per_min, Instant::now())
+        .map_err(WebhookError::rate_limited)?;
+    let Query(query) = query
+        .map_err(|e| WebhookError::new(StatusCode::BAD_REQUEST, "invalid_query", e.body_text()))?;
 
-    if let Err(status) = validate_token(&headers, &settings.webhook_token) {
-        return (status, Json(json!({"error": "unauthorized"})));
+    let items = store.load_items().map_err(WebhookError::store)?;
+    let entries = entries_to_sync(&items, query.scheduled_only);
+    if entries.is_empty() {
+        return Err(WebhookError::new(
+            StatusCode::BAD_REQUEST,
+            "no_entries",
+            "no entries to sync",
+        ));
+    }
+
+    if query.dry_run {
+        let outcome = run_blocking(move || {
+            sync::merge_destination_results(sync::execute_sync_all(&entries, &settings, true))
+        })
+        .await?;
+        return sync_response(outcome);
     }
 
-    let entries = match store.load_items() {
-        Ok(items) => items,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e}))),
+    let scheduled_only = query.scheduled_only;
+    let run = move |sync: &dyn Fn(
+        &[BackupEntry],
+        &AppSettings,
+    ) -> crate::error::Result<Vec<SyncResult>>| {
+        // Reload under the sync lock, so a run queued behind another sync
+        // uses the entries and settings current when it starts
+        let reloaded = store.load_settings().and_then(|settings| {
+            let items = store.load_items()?;
+            Ok((entries_to_sync(&items, scheduled_only), settings))
+        });
+        let (entries, settings) = reloaded.unwrap_or_else(|e| {
+            logging::log(
+                LogLevel::Warn,
+                format_args!("failed to reload the store before syncing: {e}"),
+            );
+            (entries, settings)
+        });
+        let started_at = chrono::Utc::now();
+        store.sync_started();
+        let outcome = sync::merge_destination_results(sync(&entries, &settings));
+        record_sync(&store, &settings, &entries, started_at, &outcome);
+        outcome
     };
+    match run_blocking(move || sync::execute_sync_all_coalesced(run)).await? {
+        Coalesced::Ran(outcome) => sync_response(outcome),
+        Coalesced::Queued => {
+            Ok((StatusCode::ACCEPTED, Json(json!({"status": "queued"}))).into_response())
+        }
+    }
+}
 
-    if entries.is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(json!({"error": "no entries to sync"})),
-        );
+/// Run a sync on tokio's blocking pool, so rsync and retry backoff don't
+/// stall the runtime's worker threads.
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, WebhookError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| WebhookError::new(StatusCode::INTERNAL_SERVER_ERROR, "sync_failed", e))
+}
+
+/// The entries a `POST /sync` covers: every enabled entry, or only the
+/// scheduled ones with `scheduled_only`.
+fn entries_to_sync(items: &[BackupEntry], scheduled_only: bool) -> Vec<BackupEntry> {
+    if scheduled_only {
+        sync::scheduled_entries(items)
+    } else {
+        sync::enabled_entries(items)
     }
+}
 
-    match sync::execute_sync(&entries, &settings) {
-        Ok(result) => (StatusCode::OK, Json(json!(result))),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
-        ),
+/// Record a finished webhook sync: history, `last_synced` stamps and the
+/// finished event.
+fn record_sync<S: DataStore>(
+    store: &S,
+    settings: &AppSettings,
+    entries: &[BackupEntry],
+    started_at: chrono::DateTime<chrono::Utc>,
+    outcome: &crate::error::Result<SyncResult>,
+) {
+    let run = SyncHistoryEntry::from_outcome(started_at, chrono::Utc::now(), outcome);
+    if let Err(e) = store.append_history(run) {
+        logging::log(
+            LogLevel::Warn,
+            format_args!("failed to record sync history: {e}"),
+        );
+    }
+    if let Ok(result) = outcome {
+        let ids = sync::synced_entry_ids(entries, result);
+        let stamped = store.load_items().and_then(|mut items| {
+            stamp_synced(&mut items, &ids, result.synced_at);
+            store.save_items(&items)
+        });
+        if let Err(e) = stamped {
+            logging::log(
+                LogLevel::Warn,
+                format_args!("failed to update last_synced: {e}"),
+            );
+        }
     }
+    store.sync_finished(settings, outcome);
+}
+
+/// The response for a sync outcome; a sync already holding the lock is
+/// `409 Conflict`, not a failure.
+fn sync_response(outcome: crate::error::Result<SyncResult>) -> Result<Response, WebhookError> {
+    outcome
+        .map(|result| Json(result).into_response())
+        .map_err(|e| match e {
+            ShrikeError::SyncBusy => WebhookError::new(StatusCode::CONFLICT, "sync_busy", e),
+            e => WebhookError::new(StatusCode::INTERNAL_SERVER_ERROR, "sync_failed", e),
+        })
+}
+
+/// GET /history — returns recorded sync runs, newest first.
+async fn history_handler<S: DataStore>(
+    State(store): State<S>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Vec<SyncHistoryEntry>>, WebhookError> {
+    let settings = store.load_settings().map_err(WebhookError::store)?;
+    authorize_request(&headers, &body, &settings)?;
+    store.load_history().map(Json).map_err(WebhookError::store)
+}
+
+/// GET /entries — returns the tracked backup entries.
+async fn entries_handler<S: DataStore>(
+    State(store): State<S>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Vec<BackupEntry>>, WebhookError> {
+    let settings = store.load_settings().map_err(WebhookError::store)?;
+    authorize_request(&headers, &body, &settings)?;
+    store.load_items().map(Json).map_err(WebhookError::store)
 }
 
 /// Build the webhook router with the given data store.
 ///
+/// Each router gets its own `POST /sync` rate limiter, shared by its clones.
+///
 /// Exposed publicly so integration tests can build a router with a mock store
 /// and exercise the handlers via `tower::ServiceExt::oneshot`.
 pub fn build_router<S: DataStore>(store: S) -> Router {
     Router::new()
+        .route("/healthz", get(healthz_handler))
         .route("/status", get(status_handler::<S>))
         .route("/sync", post(sync_handler::<S>))
+        .route("/history", get(history_handler::<S>))
+        .route("/entries", get(entries_handler::<S>))
+        .layer(Extension(Arc::new(RateLimiter::default())))
+        .layer(middleware::from_fn(log_request))
         .with_state(store)
 }
 
-/// Start the webhook server in a background task.
-pub fn start_webhook_server(app: AppHandle, port: u16) {
-    let store = TauriStore { app };
-    let router = build_router(store);
+/// Log each request's method, path and response status. The query string
+/// and headers are left out so tokens never reach the log; `/healthz`
+/// probes are only logged at `debug`.
+async fn log_request(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    let level = if path == "/healthz" {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    };
+    logging::log(
+        level,
+        format_args!("webhook {method} {path} -> {}", response.status().as_u16()),
+    );
+    response
+}
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+/// Bind the first free address of `addrs`, recording the outcome in
+/// `status`.
+///
+/// On failure the error for the first address is stored and returned, and
+/// `status` is left not running.
+async fn bind_listener(
+    addrs: &[SocketAddr],
+    status: &Mutex<WebhookStatus>,
+) -> Result<tokio::net::TcpListener, String> {
+    let mut first_error = None;
+    for &addr in addrs {
+        match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => {
+                let local = listener.local_addr().unwrap_or(addr);
+                *status.lock().unwrap_or_else(|e| e.into_inner()) = WebhookStatus {
+                    running: true,
+                    bound_addr: Some(local.to_string()),
+                    error: None,
+                };
+                return Ok(listener);
+            }
+            Err(e) => {
+                first_error
+                    .get_or_insert_with(|| format!("webhook server failed to bind to {addr}: {e}"));
+            }
+        }
+    }
+    let message =
+        first_error.unwrap_or_else(|| "webhook server has no address to bind".to_string());
+    *status.lock().unwrap_or_else(|e| e.into_inner()) = WebhookStatus {
+        running: false,
+        bound_addr: None,
+        error: Some(message.clone()),
+    };
+    Err(message)
+}
+
+/// Serve `router` on `listener` until `shutdown` fires or its sender is
+/// dropped, then let in-flight requests finish.
+async fn serve_until(
+    listener: tokio::net::TcpListener,
+    router: Router,
+    shutdown: tokio::sync::oneshot::Receiver<()>,
+) -> std::io::Result<()> {
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = shutdown.await;
+        })
+        .await
+}
+
+/// Start the webhook server in a background task on the first free address
+/// of `addrs` (see `AppSettings::webhook_addrs`).
+///
+/// A server this started earlier is shut down first, so calling it again
+/// rebinds, e.g. after `webhook_port` changes. Bind and serve failures are
+/// recorded for `server_status` and emitted as `WEBHOOK_ERROR_EVENT` so the
+/// UI can show them.
+pub fn start_webhook_server(app: AppHandle, addrs: Vec<SocketAddr>) {
+    let store = TauriStore { app: app.clone() };
+    let router = build_router(store);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let previous = SHUTDOWN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(shutdown_tx);
 
     tauri::async_runtime::spawn(async move {
-        let listener = match tokio::net::TcpListener::bind(addr).await {
+        if let Some(previous) = previous {
+            let _ = previous.send(());
+        }
+        let listener = match bind_listener(&addrs, &SERVER_STATUS).await {
             Ok(l) => l,
-            Err(e) => {
-                eprintln!("webhook server failed to bind to {addr}: {e}");
+            Err(message) => {
+                logging::log(LogLevel::Error, &message);
+                let _ = app.emit(WEBHOOK_ERROR_EVENT, message);
                 return;
             }
         };
-        println!("webhook server listening on {addr}");
-        if let Err(e) = axum::serve(listener, router).await {
-            eprintln!("webhook server error: {e}");
+        let status = server_status();
+        if let Some(addr) = &status.bound_addr {
+            logging::log(
+                LogLevel::Info,
+                format_args!("webhook server listening on {addr}"),
+            );
+        }
+        let _ = app.emit(WEBHOOK_STATUS_EVENT, status);
+        if let Err(e) = serve_until(listener, router, shutdown_rx).await {
+            let message = format!("webhook server error: {e}");
+            logging::log(LogLevel::Error, &message);
+            {
+                let mut status = SERVER_STATUS.lock().unwrap_or_else(|e| e.into_inner());
+                status.running = false;
+                status.error = Some(message.clone());
+            }
+            let _ = app.emit(WEBHOOK_ERROR_EVENT, message);
         }
     });
 }
@@ -198,6 +777,168 @@ mod tests {
         );
     }
 
+    #[test]
+    fn token_strength_uuid_is_strong() {
+        let strength = token_strength("550e8400-e29b-41d4-a716-446655440000");
+        assert!(strength.strong);
+        assert!(strength.score >= STRONG_TOKEN_SCORE);
+        assert!(strength.reasons.is_empty());
+    }
+
+    #[test]
+    fn token_strength_password_is_weak() {
+        let strength = token_strength("password");
+        assert!(!strength.strong);
+        assert_eq!(strength.score, 0);
+        assert!(strength.reasons.iter().any(|r| r.contains("common")));
+        assert!(strength.reasons.iter().any(|r| r.contains("too short")));
+    }
+
+    #[test]
+    fn token_strength_catches_common_word_with_digits() {
+        let strength = token_strength("Password123");
+        assert!(!strength.strong);
+        assert!(strength.reasons.iter().any(|r| r.contains("common")));
+    }
+
+    #[test]
+    fn token_strength_repeated_chars_low_entropy() {
+        let strength = token_strength(&"a".repeat(40));
+        assert!(!strength.strong);
+        assert!(strength.reasons.iter().any(|r| r.contains("low entropy")));
+    }
+
+    #[test]
+    fn token_strength_is_deterministic() {
+        let token = "k3Y-9fQz!mW2pL7x";
+        assert_eq!(token_strength(token), token_strength(token));
+    }
+
+    #[test]
+    fn validate_token_empty_expected_token_rejects_everything() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer "));
+        assert_eq!(
+            validate_token(&headers, "").unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    // GitHub's published example: secret, payload, and expected signature
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    fn signed_headers(signature: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_static(signature));
+        headers
+    }
+
+    fn hmac_settings(token_fallback: bool) -> AppSettings {
+        AppSettings {
+            webhook_token: "my-token".into(),
+            webhook_hmac_secret: Some(SECRET.into()),
+            webhook_token_fallback: token_fallback,
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn validate_signature_known_triple() {
+        assert!(validate_signature(&signed_headers(SIGNATURE), BODY, SECRET).is_ok());
+    }
+
+    #[test]
+    fn validate_signature_rejects_tampered_body() {
+        assert_eq!(
+            validate_signature(&signed_headers(SIGNATURE), b"Hello, World?", SECRET).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn validate_signature_rejects_wrong_secret_and_malformed_headers() {
+        let headers = signed_headers(SIGNATURE);
+        assert!(validate_signature(&headers, BODY, "another secret").is_err());
+        for bad in [
+            "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            "sha1=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            "sha256=not-hex",
+            "sha256=757107",
+        ] {
+            assert!(
+                validate_signature(&signed_headers(bad), BODY, SECRET).is_err(),
+                "{bad}"
+            );
+        }
+        assert!(validate_signature(&HeaderMap::new(), BODY, SECRET).is_err());
+    }
+
+    #[test]
+    fn authorize_requires_signature_when_secret_set() {
+        let settings = hmac_settings(false);
+        assert!(authorize(&signed_headers(SIGNATURE), BODY, &settings).is_ok());
+
+        let mut bearer_only = HeaderMap::new();
+        bearer_only.insert("authorization", HeaderValue::from_static("Bearer my-token"));
+        assert!(authorize(&bearer_only, BODY, &settings).is_err());
+    }
+
+    #[test]
+    fn authorize_token_fallback_only_for_unsigned_requests() {
+        let settings = hmac_settings(true);
+        let mut bearer_only = HeaderMap::new();
+        bearer_only.insert("authorization", HeaderValue::from_static("Bearer my-token"));
+        assert!(authorize(&bearer_only, BODY, &settings).is_ok());
+
+        // A bad signature is not rescued by a valid token
+        let mut both = bearer_only.clone();
+        both.insert(SIGNATURE_HEADER, HeaderValue::from_static(SIGNATURE));
+        assert!(authorize(&both, b"tampered", &settings).is_err());
+    }
+
+    #[test]
+    fn authorize_without_secret_uses_bearer_token() {
+        let settings = AppSettings {
+            webhook_token: "my-token".into(),
+            ..AppSettings::default()
+        };
+        assert!(authorize(&signed_headers(SIGNATURE), BODY, &settings).is_err());
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer my-token"));
+        assert!(authorize(&headers, BODY, &settings).is_ok());
+    }
+
+    #[test]
+    fn rate_limiter_allows_limit_per_window() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check(3, start).is_ok());
+        }
+        let later = start + Duration::from_secs(15);
+        assert_eq!(
+            limiter.check(3, later).unwrap_err(),
+            Duration::from_secs(45)
+        );
+
+        // A new window starts once the old one has passed
+        let next_window = start + RATE_LIMIT_WINDOW;
+        assert!(limiter.check(3, next_window).is_ok());
+        assert!(limiter.check(3, next_window).is_ok());
+    }
+
+    #[test]
+    fn rate_limiter_zero_limit_is_unlimited() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.check(0, now).is_ok());
+        }
+    }
+
     #[test]
     fn validate_token_wrong_scheme() {
         let mut headers = HeaderMap::new();
@@ -207,4 +948,145 @@ mod tests {
             StatusCode::UNAUTHORIZED
         );
     }
+
+    #[tokio::test]
+    async fn bind_listener_records_address_in_use() {
+        let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = taken.local_addr().unwrap();
+        let status = Mutex::new(WebhookStatus::default());
+
+        let message = bind_listener(&[addr], &status).await.unwrap_err();
+
+        let status = status.into_inner().unwrap();
+        assert!(!status.running);
+        assert_eq!(status.bound_addr, None);
+        assert_eq!(status.error, Some(message.clone()));
+        assert!(message.contains(&addr.to_string()));
+    }
+
+    #[tokio::test]
+    async fn bind_listener_records_bound_address() {
+        let status = Mutex::new(WebhookStatus::default());
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        let listener = bind_listener(&[addr], &status).await.unwrap();
+
+        let status = status.into_inner().unwrap();
+        assert!(status.running);
+        assert_eq!(
+            status.bound_addr,
+            Some(listener.local_addr().unwrap().to_string())
+        );
+        assert_eq!(status.error, None);
+    }
+
+    #[tokio::test]
+    async fn bind_listener_falls_back_to_next_address() {
+        let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = taken.local_addr().unwrap();
+        let settings = AppSettings {
+            webhook_port: addr.port(),
+            webhook_port_strategy: crate::types::WebhookPortStrategy::Ephemeral,
+            ..AppSettings::default()
+        };
+        let status = Mutex::new(WebhookStatus::default());
+
+        let listener = bind_listener(&settings.webhook_addrs().unwrap(), &status)
+            .await
+            .unwrap();
+
+        let bound = listener.local_addr().unwrap();
+        assert_ne!(bound.port(), addr.port());
+        let status = status.into_inner().unwrap();
+        assert!(status.running);
+        assert_eq!(status.bound_addr, Some(bound.to_string()));
+    }
+
+    /// A store with nothing in it, for tests that only hit `/healthz`.
+    #[derive(Clone)]
+    struct EmptyStore;
+
+    impl DataStore for EmptyStore {
+        fn load_settings(&self) -> Result<AppSettings, String> {
+            Ok(AppSettings::default())
+        }
+
+        fn load_items(&self) -> Result<Vec<BackupEntry>, String> {
+            Ok(vec![])
+        }
+
+        fn save_items(&self, _items: &[BackupEntry]) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn load_history(&self) -> Result<Vec<SyncHistoryEntry>, String> {
+            Ok(vec![])
+        }
+
+        fn append_history(&self, _entry: SyncHistoryEntry) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    /// `GET /healthz` over a raw connection, returning the response head.
+    async fn get_healthz(addr: SocketAddr) -> std::io::Result<String> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let mut stream = tokio::net::TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    /// Serve on the address `settings` asks for, returning that address,
+    /// the shutdown trigger and the server task.
+    async fn serve_settings(
+        settings: &AppSettings,
+    ) -> (
+        SocketAddr,
+        tokio::sync::oneshot::Sender<()>,
+        tokio::task::JoinHandle<std::io::Result<()>>,
+    ) {
+        let status = Mutex::new(WebhookStatus::default());
+        let listener = bind_listener(&settings.webhook_addrs().unwrap(), &status)
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let task = tokio::spawn(serve_until(listener, build_router(EmptyStore), rx));
+        (addr, tx, task)
+    }
+
+    #[tokio::test]
+    async fn server_rebinds_on_new_port_after_shutdown() {
+        // Reserve two free ports, then release them for the server
+        let ports: Vec<u16> = {
+            let a = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let b = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            [&a, &b].map(|l| l.local_addr().unwrap().port()).to_vec()
+        };
+        let on_port = |webhook_port| AppSettings {
+            webhook_port,
+            ..AppSettings::default()
+        };
+
+        let (old_addr, old_shutdown, old_task) = serve_settings(&on_port(ports[0])).await;
+        let response = get_healthz(old_addr).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        old_shutdown.send(()).unwrap();
+        old_task.await.unwrap().unwrap();
+        let (new_addr, new_shutdown, new_task) = serve_settings(&on_port(ports[1])).await;
+
+        assert_eq!(new_addr.port(), ports[1]);
+        let response = get_healthz(new_addr).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(tokio::net::TcpStream::connect(old_addr).await.is_err());
+
+        // Dropping the trigger shuts the server down too
+        drop(new_shutdown);
+        new_task.await.unwrap().unwrap();
+    }
 }
@@ -0,0 +1,56 @@
+//! E2E test for mirroring the whole home directory under `RelativeToHome`.
+//!
+//! The home directory comes from `$HOME`, which this test points at a temp
+//! directory. It lives in its own test binary so no other test sees that.
+
+use std::fs;
+
+use shrike::sync::execute_sync;
+use shrike::types::{AppSettings, BackupEntry, ItemType, PathLayout};
+
+#[test]
+fn e2e_mirrored_home_entry_keeps_other_backups_and_the_sync_log() {
+    let home_dir = tempfile::tempdir().unwrap();
+    let home = fs::canonicalize(home_dir.path()).unwrap();
+    // SAFETY: the only test in this binary, so nothing reads the
+    // environment concurrently
+    unsafe { std::env::set_var("HOME", &home) };
+    fs::write(home.join("notes.txt"), "home").unwrap();
+
+    let outside_dir = tempfile::tempdir().unwrap();
+    let outside = fs::canonicalize(outside_dir.path()).unwrap().join("hosts");
+    fs::write(&outside, "127.0.0.1 localhost").unwrap();
+
+    let dest_dir = tempfile::tempdir().unwrap();
+    let settings = AppSettings {
+        path_layout: PathLayout::RelativeToHome,
+        mirror_deletes: true,
+        ..AppSettings::for_test(dest_dir.path().to_str().unwrap())
+    };
+    let outside = outside.to_string_lossy().to_string();
+    let entries = vec![
+        BackupEntry::new(home.to_string_lossy().to_string(), ItemType::Directory),
+        BackupEntry::new(outside.clone(), ItemType::File),
+    ];
+
+    let machine_dir = dest_dir.path().join("Backup/TestMac");
+    execute_sync(&entries, &settings, false).unwrap();
+    fs::write(machine_dir.join("manifest.json"), "{}").unwrap();
+
+    let result = execute_sync(&entries, &settings, false).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.deleted, 0, "{}", result.stdout);
+
+    assert_eq!(
+        fs::read_to_string(machine_dir.join("notes.txt")).unwrap(),
+        "home"
+    );
+    let outside_backup = format!("{}{outside}", machine_dir.display());
+    assert_eq!(
+        fs::read_to_string(&outside_backup).unwrap(),
+        "127.0.0.1 localhost"
+    );
+    assert!(machine_dir.join("manifest.json").is_file());
+    let log = fs::read_to_string(machine_dir.join("shrike-backup.log")).unwrap();
+    assert_eq!(log.lines().count(), 2, "{log}");
+}
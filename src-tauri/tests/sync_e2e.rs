@@ -8,8 +8,14 @@
 use std::fs;
 use std::io::Write;
 
-use shrike::sync::execute_sync;
-use shrike::types::{AppSettings, BackupEntry, ItemType};
+use shrike::sync::restore::restore_entry;
+use shrike::sync::{
+    execute_sync, execute_sync_all, execute_sync_with_paths, restore_paths, verify_sync,
+};
+use shrike::types::{
+    AppSettings, BackupEntry, Destination, ItemType, PathLayout, coding_config_paths,
+    scan_coding_configs_tree,
+};
 
 /// Helper: create a temp file with given content, return its canonical path.
 fn create_temp_file(dir: &std::path::Path, name: &str, content: &str) -> String {
@@ -25,32 +31,16 @@ fn create_temp_file(dir: &std::path::Path, name: &str, content: &str) -> String
         .to_string()
 }
 
-/// Helper: build test settings pointing to a temp destination.
-fn test_settings(dest: &str) -> AppSettings {
-    AppSettings {
-        gdrive_path: dest.to_string(),
-        backup_dir_name: "Backup".to_string(),
-        machine_name: "TestMac".to_string(),
-        webhook_port: 0,
-        webhook_token: "test".to_string(),
-        show_tray_icon: true,
-        show_dock_icon: true,
-        autostart: false,
-        theme: "auto".to_string(),
-        language: "auto".to_string(),
-    }
-}
-
 #[test]
 fn e2e_sync_single_file() {
     let source_dir = tempfile::tempdir().unwrap();
     let dest_dir = tempfile::tempdir().unwrap();
 
     let file_path = create_temp_file(source_dir.path(), "hello.txt", "hello world");
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
 
-    let result = execute_sync(&entries, &settings).unwrap();
+    let result = execute_sync(&entries, &settings, false).unwrap();
 
     assert!(result.is_success());
     assert_eq!(result.exit_code, 0);
@@ -80,7 +70,7 @@ fn e2e_sync_multiple_files_and_directories() {
         "[app]\nname = \"test\"",
     );
 
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![
         BackupEntry::new(f1.clone(), ItemType::File),
         BackupEntry::new(f2.clone(), ItemType::File),
@@ -88,7 +78,7 @@ fn e2e_sync_multiple_files_and_directories() {
         BackupEntry::new(f4.clone(), ItemType::File),
     ];
 
-    let result = execute_sync(&entries, &settings).unwrap();
+    let result = execute_sync(&entries, &settings, false).unwrap();
 
     assert!(result.is_success());
 
@@ -122,13 +112,13 @@ fn e2e_sync_preserves_directory_structure() {
     let f1 = create_temp_file(source_dir.path(), "a/b/c/deep.txt", "deep content");
     let f2 = create_temp_file(source_dir.path(), "x/y/z/deeper.txt", "deeper content");
 
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![
         BackupEntry::new(f1.clone(), ItemType::File),
         BackupEntry::new(f2.clone(), ItemType::File),
     ];
 
-    let result = execute_sync(&entries, &settings).unwrap();
+    let result = execute_sync(&entries, &settings, false).unwrap();
     assert!(result.is_success());
 
     // Verify the full directory path is preserved (rsync -R behavior)
@@ -146,18 +136,18 @@ fn e2e_sync_incremental_no_rewrite() {
 
     let f1 = create_temp_file(source_dir.path(), "stable.txt", "original");
 
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![BackupEntry::new(f1.clone(), ItemType::File)];
 
     // First sync
-    let r1 = execute_sync(&entries, &settings).unwrap();
+    let r1 = execute_sync(&entries, &settings, false).unwrap();
     assert!(r1.is_success());
     assert!(r1.files_transferred >= 1);
 
     // Second sync with same content — rsync should transfer fewer items
     // (macOS openrsync still lists directory entries in verbose output,
     // but actual file transfers should not occur)
-    let r2 = execute_sync(&entries, &settings).unwrap();
+    let r2 = execute_sync(&entries, &settings, false).unwrap();
     assert!(r2.is_success());
     assert!(
         r2.files_transferred < r1.files_transferred,
@@ -174,9 +164,9 @@ fn e2e_sync_incremental_no_rewrite() {
 #[test]
 fn e2e_sync_empty_entries_returns_error() {
     let dest_dir = tempfile::tempdir().unwrap();
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
 
-    let result = execute_sync(&[], &settings);
+    let result = execute_sync(&[], &settings, false);
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("no entries"), "unexpected error: {err}");
@@ -191,13 +181,13 @@ fn e2e_sync_unicode_content_and_paths() {
     let f1 = create_temp_file(source_dir.path(), "日本語/ファイル.txt", "こんにちは世界");
     let f2 = create_temp_file(source_dir.path(), "中文/笔记.md", "# 你好世界");
 
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![
         BackupEntry::new(f1.clone(), ItemType::File),
         BackupEntry::new(f2.clone(), ItemType::File),
     ];
 
-    let result = execute_sync(&entries, &settings).unwrap();
+    let result = execute_sync(&entries, &settings, false).unwrap();
     assert!(result.is_success());
 
     let dest = dest_dir.path().display();
@@ -223,11 +213,11 @@ fn e2e_sync_updates_detect_content_change() {
         .to_string_lossy()
         .to_string();
 
-    let settings = test_settings(dest_dir.path().to_str().unwrap());
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
     let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
 
     // First sync
-    let r1 = execute_sync(&entries, &settings).unwrap();
+    let r1 = execute_sync(&entries, &settings, false).unwrap();
     assert!(r1.is_success());
 
     let backup_path = format!("{}/Backup/TestMac{}", dest_dir.path().display(), file_path);
@@ -238,7 +228,7 @@ fn e2e_sync_updates_detect_content_change() {
     fs::write(&file_path_buf, "version 2").unwrap();
 
     // Second sync — should pick up changes
-    let r2 = execute_sync(&entries, &settings).unwrap();
+    let r2 = execute_sync(&entries, &settings, false).unwrap();
     assert!(r2.is_success());
     assert!(
         r2.files_transferred >= 1,
@@ -246,3 +236,492 @@ fn e2e_sync_updates_detect_content_change() {
     );
     assert_eq!(fs::read_to_string(&backup_path).unwrap(), "version 2");
 }
+
+#[test]
+fn e2e_sync_checksum_mode_catches_preserved_mtime() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let file_path = create_temp_file(source_dir.path(), "stamped.txt", "version 1");
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
+    execute_sync(&entries, &settings, false).unwrap();
+
+    // Same size, same mtime, different content
+    let mtime = fs::metadata(&file_path).unwrap().modified().unwrap();
+    fs::write(&file_path, "version 2").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&file_path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+
+    let backup_path = format!("{}/Backup/TestMac{}", dest_dir.path().display(), file_path);
+    execute_sync(&entries, &settings, false).unwrap();
+    assert_eq!(
+        fs::read_to_string(&backup_path).unwrap(),
+        "version 1",
+        "the size+mtime quick check should skip the edit"
+    );
+
+    let checksum_settings = AppSettings {
+        verify_checksums: true,
+        ..settings
+    };
+    let result = execute_sync(&entries, &checksum_settings, false).unwrap();
+    assert!(result.files_transferred >= 1);
+    assert_eq!(fs::read_to_string(&backup_path).unwrap(), "version 2");
+}
+
+#[test]
+fn e2e_sync_and_restore_coding_configs() {
+    let home_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let home = fs::canonicalize(home_dir.path()).unwrap();
+
+    let settings_json = create_temp_file(&home, ".claude/settings.json", "{\"theme\":\"dark\"}");
+    let sibling = create_temp_file(&home, ".claude.json", "{}");
+    let cursor_rules = create_temp_file(&home, ".cursor/rules.md", "be concise");
+    create_temp_file(&home, "Documents/unrelated.txt", "not a config");
+
+    let paths = coding_config_paths(&scan_coding_configs_tree(&home, &home.join(".config")));
+    assert_eq!(paths.len(), 3, "expected .claude, .claude.json and .cursor");
+
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let result = execute_sync_with_paths(&paths, &settings).unwrap();
+    assert!(result.is_success());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert_eq!(
+        fs::read_to_string(backup(&settings_json)).unwrap(),
+        "{\"theme\":\"dark\"}"
+    );
+    assert_eq!(fs::read_to_string(backup(&sibling)).unwrap(), "{}");
+    assert_eq!(
+        fs::read_to_string(backup(&cursor_rules)).unwrap(),
+        "be concise"
+    );
+    assert!(!std::path::Path::new(&backup(&format!("{}/Documents", home.display()))).exists());
+
+    // Lose the local configs, then restore them from the backup
+    fs::remove_dir_all(home.join(".claude")).unwrap();
+    fs::write(&cursor_rules, "overwritten").unwrap();
+
+    let result = restore_paths(&paths, &settings).unwrap();
+    assert!(result.is_success());
+    assert_eq!(
+        fs::read_to_string(&settings_json).unwrap(),
+        "{\"theme\":\"dark\"}"
+    );
+    assert_eq!(fs::read_to_string(&cursor_rules).unwrap(), "be concise");
+}
+
+#[test]
+fn e2e_restore_without_backup_returns_error() {
+    let dest_dir = tempfile::tempdir().unwrap();
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+
+    let err = restore_paths(&["/nonexistent/.claude".to_string()], &settings).unwrap_err();
+    assert!(
+        err.to_string().contains("no backed-up paths"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn e2e_restore_entry_recreates_deleted_file_byte_for_byte() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let content: Vec<u8> = (0..=255u8).cycle().take(64 * 1024).collect();
+    let file_path = create_temp_file(source_dir.path(), "deep/nested/blob.bin", "");
+    fs::write(&file_path, &content).unwrap();
+
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entry = BackupEntry::new(file_path.clone(), ItemType::File);
+    execute_sync(std::slice::from_ref(&entry), &settings, false).unwrap();
+
+    // The whole parent chain is gone, not just the file
+    fs::remove_dir_all(source_dir.path().join("deep")).unwrap();
+
+    let preview = restore_entry(&entry, &settings, true, false).unwrap();
+    assert!(preview.is_success());
+    assert!(!std::path::Path::new(&file_path).exists());
+
+    let result = restore_entry(&entry, &settings, false, false).unwrap();
+    assert!(result.is_success());
+    assert_eq!(fs::read(&file_path).unwrap(), content);
+}
+
+#[test]
+fn e2e_restore_entry_directory_requires_force_over_newer_files() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let config = create_temp_file(source_dir.path(), "app/config.toml", "theme = \"dark\"");
+    let dir_path = fs::canonicalize(source_dir.path().join("app"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entry = BackupEntry::new(dir_path, ItemType::Directory);
+    execute_sync(std::slice::from_ref(&entry), &settings, false).unwrap();
+
+    // Edit after the backup, with an mtime that is clearly newer
+    fs::write(&config, "theme = \"light\"").unwrap();
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+    fs::File::options()
+        .write(true)
+        .open(&config)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+
+    let err = restore_entry(&entry, &settings, false, false).unwrap_err();
+    assert!(err.to_string().contains(&config), "unexpected error: {err}");
+    assert_eq!(fs::read_to_string(&config).unwrap(), "theme = \"light\"");
+
+    restore_entry(&entry, &settings, false, true).unwrap();
+    assert_eq!(fs::read_to_string(&config).unwrap(), "theme = \"dark\"");
+}
+
+#[test]
+fn e2e_verify_sync_classifies_backup_state() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let unchanged = create_temp_file(source_dir.path(), "proj/unchanged.txt", "same");
+    let edited = create_temp_file(source_dir.path(), "proj/edited.txt", "before");
+    let dir_path = fs::canonicalize(source_dir.path().join("proj"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entries = vec![BackupEntry::new(dir_path, ItemType::Directory)];
+    execute_sync(&entries, &settings, false).unwrap();
+    assert!(verify_sync(&entries, &settings).unwrap().is_in_sync());
+
+    fs::write(&edited, "after!").unwrap();
+    let added = create_temp_file(source_dir.path(), "proj/added.txt", "new");
+
+    let report = verify_sync(&entries, &settings).unwrap();
+    assert_eq!(report.in_sync, vec![unchanged]);
+    assert_eq!(report.differing, vec![edited.clone()]);
+    assert_eq!(report.missing_in_backup, vec![added]);
+    // Verifying never writes to the backup
+    let backup = format!("{}/Backup/TestMac{edited}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(backup).unwrap(), "before");
+}
+
+#[test]
+fn e2e_sync_appends_summary_line_per_sync() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let file_path = create_temp_file(source_dir.path(), "notes.txt", "v1");
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entries = vec![BackupEntry::new(file_path, ItemType::File)];
+
+    execute_sync(&entries, &settings, false).unwrap();
+    execute_sync(&entries, &settings, false).unwrap();
+
+    let log = fs::read_to_string(format!(
+        "{}/Backup/TestMac/{}",
+        dest_dir.path().display(),
+        shrike::sync::changelog::CHANGELOG_FILE
+    ))
+    .unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for line in lines {
+        assert!(line.contains("  files="), "unexpected line: {line}");
+        assert!(line.ends_with("  ok"), "unexpected line: {line}");
+    }
+}
+
+#[test]
+fn e2e_encrypted_sync_round_trips_without_rsync() {
+    use age::secrecy::ExposeSecret;
+    use shrike::sync::encrypted::{ARCHIVE_FILE, restore_encrypted_archive};
+
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let file_path = create_temp_file(source_dir.path(), ".claude/settings.json", "{}");
+
+    let identity = age::x25519::Identity::generate();
+    let mut settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    settings.encrypt = true;
+    settings.encryption_recipient = Some(identity.to_public().to_string());
+    settings.validate().unwrap();
+
+    let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
+    let result = execute_sync(&entries, &settings, false).unwrap();
+    assert_eq!(result.files_transferred, 1);
+
+    let backup_dir = format!("{}/Backup/TestMac", dest_dir.path().display());
+    let archive = std::path::Path::new(&backup_dir).join(ARCHIVE_FILE);
+    assert!(archive.is_file());
+    // No plaintext mirror of the source next to the archive
+    assert!(!std::path::Path::new(&format!("{backup_dir}{file_path}")).exists());
+
+    let restored = tempfile::tempdir().unwrap();
+    restore_encrypted_archive(
+        &archive,
+        identity.to_string().expose_secret(),
+        &entries,
+        settings.path_layout,
+        restored.path(),
+    )
+    .unwrap();
+    let restored_file = restored.path().join(file_path.trim_start_matches('/'));
+    assert_eq!(fs::read_to_string(restored_file).unwrap(), "{}");
+}
+
+#[test]
+fn e2e_sync_entry_excludes_skip_node_modules() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let kept = create_temp_file(source_dir.path(), ".claude/projects/app/notes.md", "keep");
+    let skipped = create_temp_file(
+        source_dir.path(),
+        ".claude/projects/app/node_modules/left-pad/index.js",
+        "module.exports = 0",
+    );
+    let claude_dir = fs::canonicalize(source_dir.path().join(".claude"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let mut entry = BackupEntry::new(claude_dir, ItemType::Directory);
+    entry.excludes = vec!["node_modules/".to_string()];
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+
+    let result = execute_sync(&[entry], &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(backup(&kept)).unwrap(), "keep");
+    assert!(!std::path::Path::new(&backup(&skipped)).exists());
+    let node_modules = skipped.rsplit_once("/left-pad").unwrap().0;
+    assert!(!std::path::Path::new(&backup(node_modules)).exists());
+}
+
+#[test]
+fn e2e_sync_entry_includes_only_send_matching_files() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let kept = [
+        create_temp_file(source_dir.path(), "notes/todo.md", "todo"),
+        create_temp_file(source_dir.path(), "notes/2024/march.md", "march"),
+    ];
+    let skipped = [
+        create_temp_file(source_dir.path(), "notes/draft.txt", "draft"),
+        create_temp_file(source_dir.path(), "notes/2024/photo.png", "png"),
+    ];
+    let untouched = create_temp_file(source_dir.path(), "notes/assets/logo.svg", "svg");
+    let other = create_temp_file(source_dir.path(), "config/app.toml", "toml");
+    let canonical = |name: &str| {
+        fs::canonicalize(source_dir.path().join(name))
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let mut notes = BackupEntry::new(canonical("notes"), ItemType::Directory);
+    notes.includes = vec!["*.md".to_string()];
+    let config = BackupEntry::new(canonical("config"), ItemType::Directory);
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+
+    let result = execute_sync(&[notes, config], &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(backup(&kept[0])).unwrap(), "todo");
+    assert_eq!(fs::read_to_string(backup(&kept[1])).unwrap(), "march");
+    for path in &skipped {
+        assert!(!std::path::Path::new(&backup(path)).exists(), "{path}");
+    }
+    // Directories without a matching file are pruned
+    let assets = untouched.rsplit_once('/').unwrap().0;
+    assert!(!std::path::Path::new(&backup(assets)).exists());
+    // The allowlist is scoped to its entry
+    assert_eq!(fs::read_to_string(backup(&other)).unwrap(), "toml");
+}
+
+#[test]
+fn e2e_sync_entry_excludes_win_over_includes() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let kept = create_temp_file(source_dir.path(), "notes/todo.md", "todo");
+    let skipped = create_temp_file(source_dir.path(), "notes/archive/old.md", "old");
+    let notes = fs::canonicalize(source_dir.path().join("notes"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let mut entry = BackupEntry::new(notes, ItemType::Directory);
+    entry.includes = vec!["*.md".to_string()];
+    entry.excludes = vec!["archive/".to_string()];
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+
+    let result = execute_sync(&[entry], &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(backup(&kept)).unwrap(), "todo");
+    assert!(!std::path::Path::new(&backup(&skipped)).exists());
+}
+
+#[test]
+fn e2e_sync_global_excludes_skip_matching_files() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let kept = create_temp_file(source_dir.path(), "project/main.py", "print()");
+    let skipped = [
+        create_temp_file(source_dir.path(), "project/.DS_Store", "junk"),
+        create_temp_file(source_dir.path(), "project/main.pyc", "junk"),
+        create_temp_file(
+            source_dir.path(),
+            "project/__pycache__/main.cpython.pyc",
+            "junk",
+        ),
+    ];
+    let project = fs::canonicalize(source_dir.path().join("project"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let mut settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    settings.global_excludes = vec![".DS_Store".into(), "*.pyc".into(), "__pycache__/".into()];
+    let entry = BackupEntry::new(project, ItemType::Directory);
+
+    let result = execute_sync(&[entry], &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(backup(&kept)).unwrap(), "print()");
+    for path in &skipped {
+        assert!(!std::path::Path::new(&backup(path)).exists(), "{path}");
+    }
+}
+
+#[test]
+fn e2e_sync_mirror_deletes_removes_deleted_source_files() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let kept = create_temp_file(source_dir.path(), "notes/kept.md", "kept");
+    let deleted = create_temp_file(source_dir.path(), "notes/deleted.md", "deleted");
+    let notes = fs::canonicalize(source_dir.path().join("notes"))
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let entries = vec![BackupEntry::new(notes, ItemType::Directory)];
+    let mut settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    execute_sync(&entries, &settings, false).unwrap();
+    assert!(std::path::Path::new(&backup(&deleted)).exists());
+
+    // Without mirror mode the backup keeps the deleted file
+    fs::remove_file(&deleted).unwrap();
+    execute_sync(&entries, &settings, false).unwrap();
+    assert!(std::path::Path::new(&backup(&deleted)).exists());
+
+    settings.mirror_deletes = true;
+    let result = execute_sync(&entries, &settings, false).unwrap();
+    assert!(result.is_success());
+    assert!(!std::path::Path::new(&backup(&deleted)).exists());
+    assert_eq!(fs::read_to_string(backup(&kept)).unwrap(), "kept");
+}
+
+#[test]
+fn e2e_sync_all_writes_every_destination() {
+    let source_dir = tempfile::tempdir().unwrap();
+    let drive_dir = tempfile::tempdir().unwrap();
+    let ssd_dir = tempfile::tempdir().unwrap();
+
+    let file_path = create_temp_file(source_dir.path(), "both.txt", "twice");
+    let settings = AppSettings {
+        extra_destinations: vec![Destination {
+            gdrive_path: ssd_dir.path().to_str().unwrap().to_string(),
+            backup_dir_name: "SSDBackup".to_string(),
+        }],
+        ..AppSettings::for_test(drive_dir.path().to_str().unwrap())
+    };
+    let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
+
+    let results = execute_sync_all(&entries, &settings, false).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.is_success()));
+
+    for backup in [
+        format!("{}/Backup/TestMac{file_path}", drive_dir.path().display()),
+        format!("{}/SSDBackup/TestMac{file_path}", ssd_dir.path().display()),
+    ] {
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "twice", "{backup}");
+    }
+}
+
+#[test]
+fn e2e_sync_full_layout_keeps_home_prefix() {
+    let home = dirs::home_dir().unwrap();
+    let source_dir = tempfile::Builder::new()
+        .prefix(".shrike-e2e-")
+        .tempdir_in(&home)
+        .unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+
+    let file_path = create_temp_file(source_dir.path(), "notes/todo.txt", "full");
+    let settings = AppSettings::for_test(dest_dir.path().to_str().unwrap());
+    let entries = vec![BackupEntry::new(file_path.clone(), ItemType::File)];
+
+    let result = execute_sync(&entries, &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let backup = format!("{}/Backup/TestMac{file_path}", dest_dir.path().display());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "full", "{backup}");
+}
+
+#[test]
+fn e2e_sync_relative_to_home_layout_strips_home() {
+    let home = fs::canonicalize(dirs::home_dir().unwrap()).unwrap();
+    let source_dir = tempfile::Builder::new()
+        .prefix(".shrike-e2e-")
+        .tempdir_in(&home)
+        .unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let outside_dir = tempfile::tempdir().unwrap();
+
+    let in_home = create_temp_file(source_dir.path(), "notes/todo.txt", "home");
+    let outside = create_temp_file(outside_dir.path(), "elsewhere.txt", "outside");
+    let settings = AppSettings {
+        path_layout: PathLayout::RelativeToHome,
+        ..AppSettings::for_test(dest_dir.path().to_str().unwrap())
+    };
+    let entries = vec![
+        BackupEntry::new(in_home.clone(), ItemType::File),
+        BackupEntry::new(outside.clone(), ItemType::File),
+    ];
+
+    let result = execute_sync(&entries, &settings, false).unwrap();
+    assert!(result.is_success());
+
+    let machine_dir = format!("{}/Backup/TestMac", dest_dir.path().display());
+    let relative = std::path::Path::new(&in_home).strip_prefix(&home).unwrap();
+    let backup = format!("{machine_dir}/{}", relative.display());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "home", "{backup}");
+    assert!(!std::path::Path::new(&format!("{machine_dir}{in_home}")).exists());
+    // Paths outside home keep the full layout
+    let outside_backup = format!("{machine_dir}{outside}");
+    assert_eq!(fs::read_to_string(&outside_backup).unwrap(), "outside");
+
+    // Restores put the file back under home
+    fs::remove_file(&in_home).unwrap();
+    restore_paths(std::slice::from_ref(&in_home), &settings).unwrap();
+    assert_eq!(fs::read_to_string(&in_home).unwrap(), "home");
+}
@@ -9,14 +9,17 @@
 
 use std::fs;
 use std::io::Write;
+use std::sync::{Arc, Mutex};
 
 use axum::body::Body;
 use axum::http::{self, Request, StatusCode};
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
-use shrike::sync::execute_sync;
-use shrike::types::{AppSettings, BackupEntry, ItemType, SyncResult, SyncStatus};
+use shrike::sync::{execute_sync, try_sync_lock};
+use shrike::types::{
+    AppSettings, BackupEntry, Destination, ItemType, SyncHistoryEntry, SyncResult, SyncStatus,
+};
 use shrike::webhook::{build_router, DataStore};
 
 // ---------------------------------------------------------------------------
@@ -24,15 +27,23 @@ use shrike::webhook::{build_router, DataStore};
 // ---------------------------------------------------------------------------
 
 /// A mock data store that returns pre-configured settings and items.
+///
+/// Items and history are shared between clones so a test can inspect what
+/// a handler wrote back.
 #[derive(Clone)]
 struct MockStore {
     settings: AppSettings,
-    items: Vec<BackupEntry>,
+    items: Arc<Mutex<Vec<BackupEntry>>>,
+    history: Arc<Mutex<Vec<SyncHistoryEntry>>>,
 }
 
 impl MockStore {
     fn new(settings: AppSettings, items: Vec<BackupEntry>) -> Self {
-        Self { settings, items }
+        Self {
+            settings,
+            items: Arc::new(Mutex::new(items)),
+            history: Arc::default(),
+        }
     }
 }
 
@@ -42,7 +53,21 @@ impl DataStore for MockStore {
     }
 
     fn load_items(&self) -> Result<Vec<BackupEntry>, String> {
-        Ok(self.items.clone())
+        Ok(self.items.lock().unwrap().clone())
+    }
+
+    fn save_items(&self, items: &[BackupEntry]) -> Result<(), String> {
+        *self.items.lock().unwrap() = items.to_vec();
+        Ok(())
+    }
+
+    fn load_history(&self) -> Result<Vec<SyncHistoryEntry>, String> {
+        Ok(self.history.lock().unwrap().clone())
+    }
+
+    fn append_history(&self, entry: SyncHistoryEntry) -> Result<(), String> {
+        shrike::types::push_history(&mut self.history.lock().unwrap(), entry);
+        Ok(())
     }
 }
 
@@ -58,6 +83,18 @@ impl DataStore for FailingStore {
     fn load_items(&self) -> Result<Vec<BackupEntry>, String> {
         Err("store corrupted".to_string())
     }
+
+    fn save_items(&self, _items: &[BackupEntry]) -> Result<(), String> {
+        Err("store corrupted".to_string())
+    }
+
+    fn load_history(&self) -> Result<Vec<SyncHistoryEntry>, String> {
+        Err("store corrupted".to_string())
+    }
+
+    fn append_history(&self, _entry: SyncHistoryEntry) -> Result<(), String> {
+        Err("store corrupted".to_string())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -66,16 +103,8 @@ impl DataStore for FailingStore {
 
 fn test_settings() -> AppSettings {
     AppSettings {
-        gdrive_path: "/tmp/test_gdrive".to_string(),
-        backup_dir_name: "Backup".to_string(),
-        machine_name: "TestMac".to_string(),
-        webhook_port: 0,
         webhook_token: "test-token".to_string(),
-        show_tray_icon: true,
-        show_dock_icon: true,
-        autostart: false,
-        theme: "auto".to_string(),
-        language: "auto".to_string(),
+        ..AppSettings::for_test("/tmp/test_gdrive")
     }
 }
 
@@ -95,6 +124,10 @@ async fn send_request(
     (status, json)
 }
 
+/// Held by tests that reach the sync pipeline, which shares one global
+/// sync lock across the test binary.
+static SYNC_SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 // ===========================================================================
 // HTTP integration tests — GET /status
 // ===========================================================================
@@ -115,6 +148,172 @@ async fn status_returns_ok_with_valid_token() {
     assert_eq!(json["status"], "idle");
     assert_eq!(json["entries_count"], 0);
     assert_eq!(json["destination"], "/tmp/test_gdrive/Backup/TestMac");
+    assert_eq!(
+        json["destinations"][0]["path"],
+        "/tmp/test_gdrive/Backup/TestMac"
+    );
+    assert!(json["last_sync"].is_null());
+}
+
+#[tokio::test]
+async fn status_reports_last_sync_from_history() {
+    let store = MockStore::new(test_settings(), vec![]);
+    let now = chrono::Utc::now();
+    let older = SyncHistoryEntry::from_outcome(
+        now,
+        now,
+        &Err(shrike::error::ShrikeError::SyncFailed("boom".into())),
+    );
+    store.append_history(older).unwrap();
+    let result = SyncResult {
+        files_transferred: 3,
+        dirs_transferred: 0,
+        bytes_transferred: 2048,
+        created: 3,
+        updated: 0,
+        deleted: 0,
+        unchanged: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: 0,
+        synced_at: now,
+        warnings: vec![],
+        skipped_paths: vec![],
+        truncated: false,
+    };
+    let finished_at = now + chrono::Duration::seconds(5);
+    let newest = SyncHistoryEntry::from_outcome(now, finished_at, &Ok(result));
+    store.append_history(newest).unwrap();
+
+    let req = Request::builder()
+        .uri("/status")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send_request(build_router(store), req).await;
+    assert_eq!(status, StatusCode::OK);
+    let last_sync = &json["last_sync"];
+    assert_eq!(
+        last_sync["finished_at"],
+        serde_json::to_value(finished_at).unwrap()
+    );
+    assert_eq!(last_sync["exit_code"], 0);
+    assert_eq!(last_sync["files_transferred"], 3);
+    assert_eq!(last_sync["bytes_transferred"], 2048);
+    assert!(last_sync["error"].is_null());
+    assert_eq!(last_sync.as_object().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn status_lists_every_destination() {
+    let settings = AppSettings {
+        extra_destinations: vec![Destination {
+            gdrive_path: "/Volumes/SSD".to_string(),
+            backup_dir_name: "Shrike".to_string(),
+        }],
+        ..test_settings()
+    };
+    let router = build_router(MockStore::new(settings, vec![]));
+
+    let req = Request::builder()
+        .uri("/status")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    let paths: Vec<&str> = json["destinations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "/tmp/test_gdrive/Backup/TestMac",
+            "/Volumes/SSD/Shrike/TestMac"
+        ]
+    );
+}
+
+/// `GET /status` for `settings`, returning its `destinations` array.
+async fn status_destinations(settings: AppSettings) -> Vec<serde_json::Value> {
+    let router = build_router(MockStore::new(settings, vec![]));
+    let req = Request::builder()
+        .uri("/status")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    json["destinations"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn status_reports_reachable_destination_without_creating_it() {
+    let drive = tempfile::tempdir().unwrap();
+    let settings = AppSettings {
+        gdrive_path: drive.path().to_str().unwrap().to_string(),
+        ..test_settings()
+    };
+    let destination = settings.destination_path().unwrap();
+
+    let destinations = status_destinations(settings).await;
+
+    assert_eq!(destinations.len(), 1);
+    assert_eq!(destinations[0]["path"], destination.as_str());
+    assert_eq!(destinations[0]["reachable"], true);
+    assert!(destinations[0]["last_synced"].is_null());
+    assert!(destinations[0]["last_exit_code"].is_null());
+    assert!(!std::path::Path::new(&destination).exists());
+}
+
+#[tokio::test]
+async fn status_reports_unmounted_destination_as_unreachable() {
+    let drive = tempfile::tempdir().unwrap();
+    let offline = drive.path().join("GoogleDrive-offline");
+    let settings = AppSettings {
+        extra_destinations: vec![Destination {
+            gdrive_path: offline.to_str().unwrap().to_string(),
+            backup_dir_name: "Shrike".to_string(),
+        }],
+        gdrive_path: drive.path().to_str().unwrap().to_string(),
+        ..test_settings()
+    };
+
+    let destinations = status_destinations(settings).await;
+
+    assert_eq!(destinations[0]["reachable"], true);
+    assert_eq!(destinations[1]["reachable"], false);
+    assert!(!offline.exists());
+}
+
+#[tokio::test]
+async fn status_reports_each_destination_last_sync() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let source = tempfile::tempdir().unwrap();
+    let drive = tempfile::tempdir().unwrap();
+    let file = source.path().join("notes.txt");
+    fs::write(&file, "synced").unwrap();
+    let settings = AppSettings {
+        gdrive_path: drive.path().to_str().unwrap().to_string(),
+        ..test_settings()
+    };
+    let entries = vec![BackupEntry::new(
+        file.to_str().unwrap().to_string(),
+        ItemType::File,
+    )];
+    let result = execute_sync(&entries, &settings, false).unwrap();
+
+    let destinations = status_destinations(settings).await;
+
+    assert_eq!(destinations[0]["last_exit_code"], result.exit_code);
+    assert_eq!(
+        destinations[0]["last_synced"],
+        serde_json::to_value(result.synced_at).unwrap()
+    );
 }
 
 #[tokio::test]
@@ -150,7 +349,7 @@ async fn status_rejects_missing_auth() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
-    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "unauthorized");
 }
 
 #[tokio::test]
@@ -166,7 +365,7 @@ async fn status_rejects_wrong_token() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
-    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "unauthorized");
 }
 
 #[tokio::test]
@@ -182,7 +381,7 @@ async fn status_rejects_basic_auth_scheme() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
-    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "unauthorized");
 }
 
 #[tokio::test]
@@ -197,7 +396,8 @@ async fn status_returns_500_when_store_fails() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
-    assert!(json["error"].as_str().unwrap().contains("corrupted"));
+    assert_eq!(json["code"], "store_error");
+    assert!(json["message"].as_str().unwrap().contains("corrupted"));
 }
 
 #[tokio::test]
@@ -215,7 +415,7 @@ async fn status_returns_500_when_gdrive_not_configured() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
-    assert!(json["error"]
+    assert!(json["message"]
         .as_str()
         .unwrap()
         .contains("Google Drive path"));
@@ -238,7 +438,7 @@ async fn sync_rejects_missing_auth() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
-    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "unauthorized");
 }
 
 #[tokio::test]
@@ -255,7 +455,7 @@ async fn sync_rejects_wrong_token() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::UNAUTHORIZED);
-    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "unauthorized");
 }
 
 #[tokio::test]
@@ -272,7 +472,489 @@ async fn sync_returns_400_when_no_entries() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::BAD_REQUEST);
-    assert!(json["error"].as_str().unwrap().contains("no entries"));
+    assert_eq!(json["code"], "no_entries");
+    assert!(json["message"].as_str().unwrap().contains("no entries"));
+}
+
+#[tokio::test]
+async fn sync_returns_400_when_all_entries_disabled() {
+    let mut entry = BackupEntry::new("/nonexistent/disabled".into(), ItemType::File);
+    entry.enabled = false;
+    let router = build_router(MockStore::new(test_settings(), vec![entry]));
+
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "no_entries");
+}
+
+#[tokio::test]
+async fn sync_scheduled_only_skips_on_demand_entries() {
+    let mut entry = BackupEntry::new("/nonexistent/on-demand".into(), ItemType::File);
+    entry.scheduled = false;
+    let store = MockStore::new(test_settings(), vec![entry]);
+    let router = build_router(store);
+
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync?scheduled_only=true")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["message"].as_str().unwrap().contains("no entries"));
+}
+
+#[tokio::test]
+async fn sync_without_filter_includes_on_demand_entries() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let mut entry = BackupEntry::new("/nonexistent/on-demand".into(), ItemType::File);
+    entry.scheduled = false;
+    let store = MockStore::new(test_settings(), vec![entry]);
+    let router = build_router(store);
+
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+
+    // The entry reaches the sync pipeline (and fails validation there)
+    // instead of being filtered out
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!json["message"].as_str().unwrap().contains("no entries"));
+}
+
+#[tokio::test]
+async fn sync_dry_run_previews_without_writing() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let path = source_dir.path().join("preview.txt");
+    fs::write(&path, "preview me").unwrap();
+    let canonical = fs::canonicalize(&path)
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let settings = AppSettings {
+        gdrive_path: dest_dir.path().to_str().unwrap().to_string(),
+        ..test_settings()
+    };
+    let store = MockStore::new(settings, vec![BackupEntry::new(canonical, ItemType::File)]);
+    let router = build_router(store);
+
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync?dry_run=true")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::OK, "unexpected response: {json}");
+    assert!(json["files_transferred"].as_u64().unwrap() >= 1);
+    assert!(!dest_dir.path().join("Backup").exists());
+}
+
+#[tokio::test]
+async fn sync_checks_auth_before_the_query() {
+    let store = MockStore::new(test_settings(), vec![]);
+    let request = |token: &str| {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync?dry_run=maybe")
+            .header("authorization", auth_header(token))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let (status, json) = send_request(build_router(store.clone()), request("wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(json["code"], "unauthorized");
+
+    let (status, json) = send_request(build_router(store), request("test-token")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["code"], "invalid_query");
+}
+
+#[tokio::test]
+async fn sync_returns_409_while_another_sync_runs() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let entry = BackupEntry::new("/nonexistent/busy".into(), ItemType::File);
+    let store = MockStore::new(test_settings(), vec![entry]);
+
+    let guard = try_sync_lock().expect("sync lock should be free");
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync?dry_run=true")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send_request(build_router(store), req).await;
+    drop(guard);
+
+    assert_eq!(status, StatusCode::CONFLICT, "{json}");
+    assert_eq!(json["code"], "sync_busy");
+}
+
+#[tokio::test]
+async fn sync_queued_behind_a_running_sync_uses_the_entries_current_when_it_runs() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let source_dir = tempfile::tempdir().unwrap();
+    let dest_dir = tempfile::tempdir().unwrap();
+    let file = |name: &str| {
+        let path = source_dir.path().join(name);
+        fs::write(&path, name).unwrap();
+        fs::canonicalize(&path)
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    };
+    let (first, second) = (file("first.txt"), file("second.txt"));
+    let settings = AppSettings {
+        webhook_token: "test-token".to_string(),
+        ..AppSettings::for_test(dest_dir.path().to_str().unwrap())
+    };
+    let store = MockStore::new(
+        settings,
+        vec![BackupEntry::new(first.clone(), ItemType::File)],
+    );
+
+    let guard = try_sync_lock().expect("sync lock should be free");
+    let req = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send_request(build_router(store.clone()), req).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{json}");
+    assert_eq!(json, serde_json::json!({"status": "queued"}));
+
+    // Edited while the run waits
+    store
+        .save_items(&[BackupEntry::new(second.clone(), ItemType::File)])
+        .unwrap();
+    drop(guard);
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while store.load_history().unwrap().is_empty() || shrike::sync::is_sync_running() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "queued sync never ran"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let backup = |path: &str| format!("{}/Backup/TestMac{path}", dest_dir.path().display());
+    assert!(std::path::Path::new(&backup(&second)).exists());
+    assert!(!std::path::Path::new(&backup(&first)).exists());
+    assert!(store.load_items().unwrap()[0].last_synced.is_some());
+}
+
+// ===========================================================================
+// HTTP integration tests — GET /history
+// ===========================================================================
+
+fn history_request(token: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/history")
+        .header("authorization", auth_header(token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn history_rejects_wrong_token() {
+    let router = build_router(MockStore::new(test_settings(), vec![]));
+    let (status, json) = send_request(router, history_request("wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(json["code"], "unauthorized");
+}
+
+#[tokio::test]
+async fn history_is_empty_before_any_sync() {
+    let router = build_router(MockStore::new(test_settings(), vec![]));
+    let (status, json) = send_request(router, history_request("test-token")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn sync_records_failed_run_in_history() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let entry = BackupEntry::new("/nonexistent/history".into(), ItemType::File);
+    let store = MockStore::new(test_settings(), vec![entry]);
+
+    for uri in ["/sync", "/sync", "/sync?dry_run=true"] {
+        let req = Request::builder()
+            .method(http::Method::POST)
+            .uri(uri)
+            .header("authorization", auth_header("test-token"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, _) = send_request(build_router(store.clone()), req).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    // Dry runs are previews, not syncs, so only the two real attempts count
+    let (status, json) = send_request(build_router(store), history_request("test-token")).await;
+    assert_eq!(status, StatusCode::OK);
+    let runs = json.as_array().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["exit_code"], -1);
+    assert!(runs[0]["error"].is_string());
+    assert!(runs[0]["finished_at"].as_str() >= runs[1]["finished_at"].as_str());
+}
+
+#[tokio::test]
+async fn history_returns_500_when_store_fails() {
+    let (status, json) = send_request(build_router(FailingStore), history_request("x")).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(json["message"].as_str().unwrap().contains("corrupted"));
+}
+
+// ===========================================================================
+// HTTP integration tests — HMAC signatures
+// ===========================================================================
+
+/// HMAC-SHA256 of `SIGNED_BODY` under `shared-secret`.
+const SIGNED_BODY: &str = r#"{"reason":"cron"}"#;
+const BODY_SIGNATURE: &str =
+    "sha256=91beb6eca87a887ffea92e6f3ce986bf357d8c1dd17fa46266695d3eb62280c0";
+
+fn hmac_store() -> MockStore {
+    let settings = AppSettings {
+        webhook_hmac_secret: Some("shared-secret".to_string()),
+        ..test_settings()
+    };
+    let entry = BackupEntry::new("/nonexistent/signed".into(), ItemType::File);
+    MockStore::new(settings, vec![entry])
+}
+
+fn signed_sync_request(body: &'static str, signature: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync?dry_run=true")
+        .header("authorization", auth_header("test-token"));
+    if let Some(signature) = signature {
+        builder = builder.header("x-shrike-signature", signature);
+    }
+    builder.body(Body::from(body)).unwrap()
+}
+
+#[tokio::test]
+async fn sync_accepts_signed_body() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let router = build_router(hmac_store());
+    let req = signed_sync_request(SIGNED_BODY, Some(BODY_SIGNATURE));
+    let (status, json) = send_request(router, req).await;
+    // Authorized: fails later, on the missing source path
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{json}");
+    assert_ne!(json["code"], "unauthorized");
+}
+
+#[tokio::test]
+async fn sync_rejects_tampered_body_and_unsigned_requests() {
+    let tampered = signed_sync_request(r#"{"reason":"evil"}"#, Some(BODY_SIGNATURE));
+    let (status, _) = send_request(build_router(hmac_store()), tampered).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The bearer token alone is not enough without the fallback
+    let unsigned = signed_sync_request(SIGNED_BODY, None);
+    let (status, _) = send_request(build_router(hmac_store()), unsigned).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn status_accepts_bearer_token_with_fallback_enabled() {
+    let mut store = hmac_store();
+    store.settings.webhook_token_fallback = true;
+    let req = Request::builder()
+        .uri("/status")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send_request(build_router(store), req).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+// ===========================================================================
+// HTTP integration tests — POST /sync rate limit
+// ===========================================================================
+
+fn rate_limited_store(limit: u32) -> MockStore {
+    let settings = AppSettings {
+        webhook_rate_limit_per_min: limit,
+        ..test_settings()
+    };
+    let entry = BackupEntry::new("/nonexistent/limited".into(), ItemType::File);
+    MockStore::new(settings, vec![entry])
+}
+
+fn dry_run_request(token: &str) -> Request<Body> {
+    Request::builder()
+        .method(http::Method::POST)
+        .uri("/sync?dry_run=true")
+        .header("authorization", auth_header(token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn sync_returns_429_after_limit_within_window() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let router = build_router(rate_limited_store(3));
+
+    for _ in 0..3 {
+        let (status, _) = send_request(router.clone(), dry_run_request("test-token")).await;
+        // Let through: fails on the missing source path instead
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let response = router
+        .clone()
+        .oneshot(dry_run_request("test-token"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=60).contains(&retry_after), "retry-after {retry_after}");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["code"], "rate_limited");
+}
+
+#[tokio::test]
+async fn sync_rate_limit_ignores_unauthorized_and_status_requests() {
+    let _serial = SYNC_SERIAL.lock().await;
+    let router = build_router(rate_limited_store(1));
+
+    for _ in 0..5 {
+        let (status, _) = send_request(router.clone(), dry_run_request("wrong")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status_req = Request::builder()
+            .uri("/status")
+            .header("authorization", auth_header("test-token"))
+            .body(Body::empty())
+            .unwrap();
+        let (status, _) = send_request(router.clone(), status_req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, _) = send_request(router.clone(), dry_run_request("test-token")).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let (status, _) = send_request(router, dry_run_request("test-token")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+// ===========================================================================
+// HTTP integration tests — GET /healthz
+// ===========================================================================
+
+#[tokio::test]
+async fn healthz_needs_no_token() {
+    let router = build_router(MockStore::new(test_settings(), vec![]));
+    let req = Request::builder()
+        .uri("/healthz")
+        .body(Body::empty())
+        .unwrap();
+    let (status, json) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, serde_json::json!({"ok": true}));
+}
+
+#[tokio::test]
+async fn healthz_returns_200_when_store_fails() {
+    let router = build_router(FailingStore);
+    let (status, json) = send_request(
+        router.clone(),
+        Request::builder()
+            .uri("/healthz")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["ok"], true);
+
+    // /status still reports the broken store
+    let req = Request::builder()
+        .uri("/status")
+        .header("authorization", auth_header("test-token"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, _) = send_request(router, req).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+// ===========================================================================
+// HTTP integration tests — GET /entries
+// ===========================================================================
+
+fn entries_request(token: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/entries");
+    if let Some(token) = token {
+        builder = builder.header("authorization", auth_header(token));
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn entries_returns_tracked_items() {
+    let mut notes = BackupEntry::new("/Users/test/notes".into(), ItemType::Directory);
+    notes.excludes = vec!["*.tmp".into()];
+    let zshrc = BackupEntry::new("/Users/test/.zshrc".into(), ItemType::File);
+    let router = build_router(MockStore::new(
+        test_settings(),
+        vec![notes.clone(), zshrc.clone()],
+    ));
+
+    let (status, json) = send_request(router, entries_request(Some("test-token"))).await;
+    assert_eq!(status, StatusCode::OK);
+    let entries = json.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["id"], notes.id.to_string());
+    assert_eq!(entries[0]["path"], "/Users/test/notes");
+    assert_eq!(entries[0]["item_type"], "directory");
+    assert_eq!(entries[0]["excludes"], serde_json::json!(["*.tmp"]));
+    assert_eq!(entries[1]["path"], "/Users/test/.zshrc");
+    assert_eq!(entries[1]["item_type"], "file");
+
+    // The same shape the store holds, so it round-trips
+    let parsed: Vec<BackupEntry> = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, vec![notes, zshrc]);
+}
+
+#[tokio::test]
+async fn entries_rejects_missing_or_wrong_token() {
+    for token in [None, Some("wrong")] {
+        let router = build_router(MockStore::new(test_settings(), vec![]));
+        let (status, json) = send_request(router, entries_request(token)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "token {token:?}");
+        assert_eq!(json["code"], "unauthorized");
+    }
+}
+
+#[tokio::test]
+async fn entries_returns_500_when_store_fails() {
+    let (status, json) = send_request(build_router(FailingStore), entries_request(Some("x"))).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(json["message"].as_str().unwrap().contains("corrupted"));
 }
 
 #[tokio::test]
@@ -288,11 +970,12 @@ async fn sync_returns_500_when_store_fails() {
 
     let (status, json) = send_request(router, req).await;
     assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
-    assert!(json["error"].as_str().unwrap().contains("corrupted"));
+    assert!(json["message"].as_str().unwrap().contains("corrupted"));
 }
 
 #[tokio::test]
 async fn sync_succeeds_with_real_file() {
+    let _serial = SYNC_SERIAL.lock().await;
     let source_dir = tempfile::tempdir().unwrap();
     let dest_dir = tempfile::tempdir().unwrap();
 
@@ -305,21 +988,14 @@ async fn sync_succeeds_with_real_file() {
         .to_string();
 
     let settings = AppSettings {
-        gdrive_path: dest_dir.path().to_str().unwrap().to_string(),
         backup_dir_name: "WebhookBackup".to_string(),
-        machine_name: "TestMac".to_string(),
-        webhook_port: 0,
         webhook_token: "test-token".to_string(),
-        show_tray_icon: true,
-        show_dock_icon: true,
-        autostart: false,
-        theme: "auto".to_string(),
-        language: "auto".to_string(),
+        ..AppSettings::for_test(dest_dir.path().to_str().unwrap())
     };
 
     let entries = vec![BackupEntry::new(canonical.clone(), ItemType::File)];
     let store = MockStore::new(settings, entries);
-    let router = build_router(store);
+    let router = build_router(store.clone());
 
     let req = Request::builder()
         .method(http::Method::POST)
@@ -341,6 +1017,11 @@ async fn sync_succeeds_with_real_file() {
     );
     assert!(std::path::Path::new(&backup_path).exists());
     assert_eq!(fs::read_to_string(&backup_path).unwrap(), "via HTTP");
+
+    // The synced entry is stamped with the sync time
+    let items = store.load_items().unwrap();
+    let synced_at = items[0].last_synced.expect("last_synced not set");
+    assert_eq!(json["synced_at"], serde_json::json!(synced_at));
 }
 
 // ===========================================================================
@@ -406,11 +1087,12 @@ fn simulate_webhook_sync(
     if entries.is_empty() {
         return Err("no entries to sync".to_string());
     }
-    execute_sync(entries, settings).map_err(|e| e.to_string())
+    execute_sync(entries, settings, false).map_err(|e| e.to_string())
 }
 
 #[test]
 fn webhook_sync_flow_success() {
+    let _serial = SYNC_SERIAL.blocking_lock();
     let source_dir = tempfile::tempdir().unwrap();
     let dest_dir = tempfile::tempdir().unwrap();
 
@@ -423,16 +1105,9 @@ fn webhook_sync_flow_success() {
         .to_string();
 
     let settings = AppSettings {
-        gdrive_path: dest_dir.path().to_str().unwrap().to_string(),
         backup_dir_name: "WebhookBackup".to_string(),
-        machine_name: "TestMac".to_string(),
-        webhook_port: 0,
         webhook_token: "test-token".to_string(),
-        show_tray_icon: true,
-        show_dock_icon: true,
-        autostart: false,
-        theme: "auto".to_string(),
-        language: "auto".to_string(),
+        ..AppSettings::for_test(dest_dir.path().to_str().unwrap())
     };
 
     let entries = vec![BackupEntry::new(canonical.clone(), ItemType::File)];
@@ -453,16 +1128,8 @@ fn webhook_sync_flow_success() {
 #[test]
 fn webhook_sync_flow_empty_entries_error() {
     let settings = AppSettings {
-        gdrive_path: "/tmp".to_string(),
-        backup_dir_name: "Backup".to_string(),
-        machine_name: "TestMac".to_string(),
-        webhook_port: 0,
         webhook_token: "token".to_string(),
-        show_tray_icon: true,
-        show_dock_icon: true,
-        autostart: false,
-        theme: "auto".to_string(),
-        language: "auto".to_string(),
+        ..AppSettings::for_test("/tmp")
     };
 
     let result = simulate_webhook_sync(&[], &settings);
@@ -495,10 +1162,17 @@ fn webhook_sync_result_serialization() {
         files_transferred: 3,
         dirs_transferred: 0,
         bytes_transferred: 4096,
+        created: 0,
+        updated: 0,
+        deleted: 0,
+        unchanged: 0,
         stdout: "sending incremental file list\nfile1.txt\nfile2.txt\nfile3.txt\n".to_string(),
         stderr: String::new(),
         exit_code: 0,
         synced_at: chrono::Utc::now(),
+        warnings: vec![],
+        skipped_paths: vec![],
+        truncated: false,
     };
 
     let json = serde_json::to_value(&result).unwrap();